@@ -11,6 +11,10 @@ pub struct Coords {
     pub line: usize,
     /// The column position
     pub column: usize,
+    /// The byte offset within the underlying input at which the character at this position starts
+    pub byte_offset: usize,
+    /// The number of bytes taken up by the character at this position
+    pub byte_len: usize,
 }
 
 /// Associated functions for the [Coords] struct
@@ -21,6 +25,8 @@ impl Coords {
             line: value.line,
             column: value.column,
             absolute: value.absolute,
+            byte_offset: value.byte_offset,
+            byte_len: value.byte_len,
         }
     }
 
@@ -30,6 +36,8 @@ impl Coords {
             absolute: 0,
             line,
             column: 0,
+            byte_offset: 0,
+            byte_len: 0,
         }
     }
 
@@ -49,20 +57,52 @@ impl Coords {
         self.line = other.line;
         self.column = other.column;
         self.absolute = other.absolute;
+        self.byte_offset = other.byte_offset;
+        self.byte_len = other.byte_len;
     }
 
-    /// In place increment
+    /// The byte offset immediately following the character at this position
+    #[inline]
+    pub fn byte_end(&self) -> usize {
+        self.byte_offset + self.byte_len
+    }
+
+    /// Set the byte offset and length of the character at this position, returning the updated
+    /// struct
+    #[inline]
+    pub fn with_bytes(mut self, byte_offset: usize, byte_len: usize) -> Self {
+        self.byte_offset = byte_offset;
+        self.byte_len = byte_len;
+        self
+    }
+
+    /// In place increment. Unless subsequently adjusted, the new position is assumed to hold a
+    /// single byte character
     #[inline]
     pub fn increment(&mut self) {
         self.column += 1;
         self.absolute += 1;
+        self.byte_offset = self.byte_end();
+        self.byte_len = 1;
     }
 
-    /// In place decrement
+    /// In place decrement. Assumes that the previous position holds a single byte character
     #[inline]
+    #[deprecated(
+        note = "use decrement_over, which takes the byte length of the previous character"
+    )]
     pub fn decrement(&mut self) {
+        self.decrement_over(1)
+    }
+
+    /// In place decrement, where `byte_len` is the number of bytes taken up by the character at
+    /// the previous position
+    #[inline]
+    pub fn decrement_over(&mut self, byte_len: usize) {
         self.column -= 1;
         self.absolute -= 1;
+        self.byte_offset -= byte_len;
+        self.byte_len = byte_len;
     }
 
     /// In place increment with a line return
@@ -71,6 +111,8 @@ impl Coords {
         self.column = 0;
         self.line += 1;
         self.absolute += 1;
+        self.byte_offset = self.byte_end();
+        self.byte_len = 1;
     }
 
     /// Increment the coordinate within the current line and return new struct
@@ -80,6 +122,8 @@ impl Coords {
             line: self.line,
             column: self.column + 1,
             absolute: self.absolute + 1,
+            byte_offset: self.byte_end(),
+            byte_len: 1,
         }
     }
 
@@ -91,17 +135,33 @@ impl Coords {
             line: self.line + 1,
             column: 1,
             absolute: self.absolute + 1,
+            byte_offset: self.byte_end(),
+            byte_len: 1,
         }
     }
 
     /// Decrement the coordinate within the current line, but panic if we try and decrement
-    /// column or absolute below zero, return a new struct
+    /// column or absolute below zero, return a new struct. Assumes that the previous position
+    /// holds a single byte character
     #[inline]
+    #[deprecated(
+        note = "use copy_decrement_over, which takes the byte length of the previous character"
+    )]
     pub fn copy_decrement(&mut self) -> Self {
+        self.copy_decrement_over(1)
+    }
+
+    /// Decrement the coordinate within the current line, but panic if we try and decrement
+    /// column or absolute below zero, return a new struct. `byte_len` is the number of bytes taken
+    /// up by the character at the previous position
+    #[inline]
+    pub fn copy_decrement_over(&self, byte_len: usize) -> Self {
         Coords {
             line: self.line,
             column: self.column - 1,
             absolute: self.absolute - 1,
+            byte_offset: self.byte_offset - byte_len,
+            byte_len,
         }
     }
 }
@@ -110,8 +170,8 @@ impl Display for Coords {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "[abs: {}, line: {}, column: {}]",
-            self.absolute, self.line, self.column
+            "[abs: {}, line: {}, column: {}, byte: {}]",
+            self.absolute, self.line, self.column, self.byte_offset
         )
     }
 }
@@ -123,6 +183,8 @@ impl Default for Coords {
             absolute: 0,
             line: 0,
             column: 0,
+            byte_offset: 0,
+            byte_len: 0,
         }
     }
 }
@@ -131,11 +193,7 @@ impl Eq for Coords {}
 
 impl PartialOrd<Self> for Coords {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self.absolute.cmp(&other.absolute) {
-            Ordering::Less => Some(Ordering::Less),
-            Ordering::Equal => Some(Ordering::Equal),
-            Ordering::Greater => Some(Ordering::Greater),
-        }
+        Some(self.cmp(other))
     }
 }
 
@@ -155,11 +213,15 @@ mod test {
             line: 1,
             column: 1,
             absolute: 1,
+            byte_offset: 0,
+            byte_len: 1,
         };
         let c2 = Coords {
             line: 1,
             column: 12,
             absolute: 12,
+            byte_offset: 11,
+            byte_len: 1,
         };
         assert!(c1.is_before(&c2));
         assert!(!c2.is_before(&c1));
@@ -171,11 +233,15 @@ mod test {
             line: 1,
             column: 1,
             absolute: 1,
+            byte_offset: 0,
+            byte_len: 1,
         };
         let c2 = Coords {
             line: 1,
             column: 12,
             absolute: 12,
+            byte_offset: 11,
+            byte_len: 1,
         };
         assert!(!c1.is_after(&c2));
         assert!(c2.is_after(&c1))
@@ -187,11 +253,15 @@ mod test {
             line: 1,
             column: 1,
             absolute: 1,
+            byte_offset: 0,
+            byte_len: 1,
         };
         let c2 = Coords {
             line: 1,
             column: 1,
             absolute: 1,
+            byte_offset: 0,
+            byte_len: 1,
         };
         assert_eq!(c1, c2)
     }
@@ -207,7 +277,9 @@ mod test {
             Coords {
                 line: 1,
                 column: 5,
-                absolute: 5
+                absolute: 5,
+                byte_offset: 4,
+                byte_len: 1
             }
         )
     }
@@ -224,6 +296,7 @@ mod test {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn dec_works() {
         let mut c1 = Coords::from_line(1);
         for _ in 1..=5 {
@@ -237,13 +310,40 @@ mod test {
             Coords {
                 line: 1,
                 column: 2,
-                absolute: 2
+                absolute: 2,
+                byte_offset: 1,
+                byte_len: 1
             }
         )
     }
 
+    #[test]
+    fn dec_works_over_multibyte_characters() {
+        let mut c1 = Coords::default().with_bytes(0, 1);
+        c1.increment();
+        c1.byte_len = 'é'.len_utf8();
+        c1.increment();
+        c1.increment();
+        assert_eq!(c1.byte_offset, 4);
+        c1.decrement_over(1);
+        c1.decrement_over('é'.len_utf8());
+        assert_eq!(
+            c1,
+            Coords {
+                line: 0,
+                column: 1,
+                absolute: 1,
+                byte_offset: 1,
+                byte_len: 2
+            }
+        );
+        assert_eq!(c1.copy_decrement_over(1).byte_offset, 0);
+        assert_eq!(c1.copy_decrement_over(1).byte_len, 1);
+    }
+
     #[test]
     #[should_panic]
+    #[allow(deprecated)]
     fn dec_panics() {
         let mut c1 = Coords::from_line(1);
         for _ in 1..=5 {
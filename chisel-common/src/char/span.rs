@@ -1,5 +1,6 @@
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
+use std::ops::Range;
 
 use crate::char::coords::Coords;
 
@@ -12,18 +13,21 @@ pub struct Span {
     pub end: Coords,
 }
 
-impl Span {}
+impl Span {
+    /// The range of bytes covered by the span within the underlying input, from the first byte of
+    /// the start character through to the last byte of the end character. Can be used to slice
+    /// the original input, or to seek within a file
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start.byte_offset..self.end.byte_end()
+    }
+}
 
 impl Eq for Span {}
 
 impl PartialOrd<Self> for Span {
     /// The partial order is based on the start coordinates for a span
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self.start.cmp(&other.start) {
-            Ordering::Less => Some(Ordering::Less),
-            Ordering::Equal => Some(Ordering::Equal),
-            Ordering::Greater => Some(Ordering::Greater),
-        }
+        Some(self.cmp(other))
     }
 }
 
@@ -59,7 +63,19 @@ mod test {
             line: 2,
             column: 1,
             absolute: 3,
+            byte_offset: 2,
+            byte_len: 1,
         };
         assert!(s1 > s2)
     }
+
+    #[test]
+    fn byte_range_should_cover_the_end_character() {
+        let span = Span {
+            start: Coords::default().with_bytes(3, 1),
+            end: Coords::default().with_bytes(7, 4),
+        };
+        assert_eq!(span.byte_range(), 3..11);
+        assert_eq!(Span::default().byte_range(), 0..0);
+    }
 }
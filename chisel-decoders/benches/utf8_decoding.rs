@@ -23,7 +23,8 @@ macro_rules! build_decode_benchmark {
 build_decode_benchmark!(canada, "canada");
 build_decode_benchmark!(twitter, "twitter");
 build_decode_benchmark!(citm_catalog, "citm_catalog");
-build_decode_benchmark!(asciiart, "asciiart");
+// ASCII is valid UTF-8, so the fixture used by the ASCII benchmarks is shared
+build_decode_benchmark!(asciiart, "../ascii/asciiart");
 
 fn benchmark_canada(c: &mut Criterion) {
     c.bench_function("(Utf8) iter decode canada.json file", |b| b.iter(canada));
@@ -1,84 +1,108 @@
 #![allow(dead_code)]
-#![allow(clippy::transmute_int_to_char)]
 //! A character-oriented decoder implementation that will take an underlying [std::u8] (byte) source
 //! and produce a stream of decoded ASCII characters
 use std::io::BufRead;
-use std::mem::transmute;
 
 use crate::common::*;
-use crate::decoder_error;
+use crate::scan::AsciiRun;
+use crate::stream::ByteStream;
+use crate::{decoder_error, end_of_input, Encoding};
 
 /// An ASCII decoder, which takes a ref to a [BufRead] instance.
 pub struct AsciiDecoder<'a, B: BufRead> {
     /// The input stream
-    input: &'a mut B,
-    /// Staging buffer
-    buffer: Vec<u8>,
-    /// Initialisation flag
-    init: bool,
-    /// The current index into the input
-    index: usize,
+    input: ByteStream<'a, B>,
+    /// How malformed input should be dealt with
+    recovery: Recovery,
+    /// Run of ASCII bytes at the front of the input which can be served without any further checks
+    run: AsciiRun,
 }
 
 impl<'a, Buffer: BufRead> AsciiDecoder<'a, Buffer> {
-    /// Create a new decoder with a default buffer size
+    /// Create a new decoder around a [BufRead] instance. Input is read in chunks as decoding
+    /// progresses
     pub fn new(r: &'a mut Buffer) -> Self {
-        AsciiDecoder {
-            input: r,
-            buffer: vec![],
-            init: false,
-            index: 0,
-        }
+        Self::with_policy(r, DecodePolicy::Strict)
     }
 
-    /// Initialise and read the input into an internal buffer for decoding
-    fn init(&mut self) -> DecoderResult<()> {
-        match self.input.read_to_end(&mut self.buffer) {
-            Ok(_) => {
-                self.init = true;
-                Ok(())
-            }
-            Err(_) => Err(decoder_error!(
-                DecoderErrorCode::StreamFailure,
-                "failed to read input"
-            )),
+    /// Create a new decoder around a [BufRead] instance, which deals with malformed input
+    /// according to a given [DecodePolicy]
+    pub fn with_policy(r: &'a mut Buffer, policy: DecodePolicy) -> Self {
+        AsciiDecoder {
+            input: ByteStream::new(r),
+            recovery: Recovery::new(policy),
+            run: AsciiRun::new(),
         }
     }
 
     /// Attempt to decode the next character in the underlying stream.
     fn decode_next(&mut self) -> DecoderResult<char> {
-        if !self.init {
-            self.init()?;
+        if let Some(ch) = self.run.next(&mut self.input)? {
+            return Ok(ch);
         }
 
-        if self.index >= self.buffer.len() {
-            return Err(decoder_error!(
-                DecoderErrorCode::EndOfInput,
-                "end of input reached"
-            ));
-        }
+        let byte = match self.input.peek(1)?.first() {
+            Some(b) => *b,
+            None => return end_of_input!(),
+        };
 
-        if self.buffer[self.index] >> 7 == 0 {
-            return unsafe {
-                self.index += 1;
-                Ok(transmute(self.buffer[self.index - 1] as u32))
-            };
+        self.input.consume(1);
+        if byte >> 7 == 0 {
+            Ok(char::from(byte))
         } else {
-            return Err(decoder_error!(
+            Err(decoder_error!(
                 DecoderErrorCode::OutOfRange,
                 "non-ascii character detected"
-            ));
+            ))
         }
     }
+
+    /// The offset of the next character to be decoded, taking into account any ASCII which has been
+    /// served from the front of the input but not yet consumed
+    #[inline]
+    fn offset(&self) -> usize {
+        self.input.offset() + self.run.served()
+    }
+}
+
+impl<'a, B: BufRead> CharSource for AsciiDecoder<'a, B> {
+    /// Decode the next character from the underlying stream, dealing with any malformed input
+    /// according to the current [DecodePolicy]
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        loop {
+            let offset = self.offset();
+            match self.decode_next() {
+                Ok(ch) => {
+                    let len = self.offset() - offset;
+                    return Some(Ok(CharWithOffset { ch, offset, len }));
+                }
+                Err(err) if err.code == DecoderErrorCode::EndOfInput => return None,
+                Err(err) => {
+                    let len = self.offset() - offset;
+                    if let Some(result) = self.recovery.recover(err, offset, len) {
+                        return Some(result);
+                    }
+                }
+            }
+        }
+    }
+
+    fn replacements(&self) -> usize {
+        self.recovery.replacements()
+    }
+
+    fn encoding(&self) -> Option<Encoding> {
+        Some(Encoding::Ascii)
+    }
 }
 
 impl<'a, B: BufRead> Iterator for AsciiDecoder<'a, B> {
     type Item = char;
     /// Decode the next character from the underlying stream
     fn next(&mut self) -> Option<Self::Item> {
-        match self.decode_next() {
-            Ok(c) => Some(c),
-            Err(_) => None,
+        match self.next_char() {
+            Some(Ok(c)) => Some(c.ch),
+            _ => None,
         }
     }
 }
@@ -90,7 +114,7 @@ mod tests {
     use std::time::Instant;
 
     use crate::ascii::AsciiDecoder;
-    use crate::common::DecoderErrorCode;
+    use crate::common::{CharSource, DecodePolicy, DecoderErrorCode};
 
     fn utf8_fuzz_file() -> File {
         File::open("fixtures/fuzz.txt").unwrap()
@@ -145,6 +169,15 @@ mod tests {
         assert_eq!(count, 6406307);
     }
 
+    #[test]
+    fn should_replace_non_ascii_bytes() {
+        let buffer: &[u8] = &[0x61, 0xe9, 0x62, 0xff];
+        let mut reader = BufReader::new(buffer);
+        let mut decoder = AsciiDecoder::with_policy(&mut reader, DecodePolicy::Replace);
+        assert_eq!((&mut decoder).collect::<String>(), "a\u{fffd}b\u{fffd}");
+        assert_eq!(decoder.replacements(), 2);
+    }
+
     #[test]
     fn should_be_an_iterator() {
         let start = Instant::now();
@@ -0,0 +1,246 @@
+//! A decoder which works out the encoding of its input on the fly, before handing off to one of the
+//! other decoder implementations.
+//!
+//! Detection first looks for a byte order mark (BOM), which is stripped from the input if present.
+//! Failing that, the pattern of zero bytes within the first four bytes of the input is used to pick
+//! between UTF-8, UTF-16 and UTF-32, as described in section 3 of RFC 4627. This works because a
+//! JSON text always starts with two ASCII characters (whitespace included).
+use std::io::BufRead;
+
+use crate::common::*;
+use crate::stream::ByteStream;
+use crate::utf16::Utf16Decoder;
+use crate::utf32::Utf32Decoder;
+use crate::utf8::Utf8Decoder;
+use crate::Encoding;
+
+/// The number of bytes needed in order to detect an encoding
+const DETECTION_LEN: usize = 4;
+
+/// Work out the encoding of some input based on its first few bytes, returning the detected
+/// [Encoding] along with the length of any BOM which should be skipped. Anything that doesn't look
+/// like UTF-16 or UTF-32 is assumed to be UTF-8
+pub fn detect_encoding(bytes: &[u8]) -> (Encoding, usize) {
+    match bytes {
+        [0xef, 0xbb, 0xbf, ..] => (Encoding::Utf8, 3),
+        [0x00, 0x00, 0xfe, 0xff, ..] => (Encoding::Utf32Be, 4),
+        [0xff, 0xfe, 0x00, 0x00, ..] => (Encoding::Utf32Le, 4),
+        [0xfe, 0xff, ..] => (Encoding::Utf16Be, 2),
+        [0xff, 0xfe, ..] => (Encoding::Utf16Le, 2),
+        [0x00, 0x00, 0x00, _, ..] => (Encoding::Utf32Be, 0),
+        [_, 0x00, 0x00, 0x00, ..] => (Encoding::Utf32Le, 0),
+        [0x00, _, ..] => (Encoding::Utf16Be, 0),
+        [_, 0x00, ..] => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+/// A decoder which detects the encoding of the underlying input, and then delegates to the
+/// appropriate decoder implementation.
+pub struct AutoDecoder<'a> {
+    /// The decoder selected for the detected encoding
+    inner: Box<dyn CharSource + 'a>,
+    /// The detected encoding
+    encoding: Encoding,
+    /// A failure to read the start of the input, which is reported by the first call to
+    /// [CharSource::next_char]
+    pending: Option<DecoderError>,
+}
+
+impl<'a> AutoDecoder<'a> {
+    /// Create a new decoder around a [BufRead] instance. The first few bytes of the input are
+    /// inspected straight away in order to determine the encoding, and any BOM is skipped.  If the
+    /// input can't be read, then UTF-8 is assumed and the failure is reported by the first call to
+    /// [CharSource::next_char]
+    pub fn new<Buffer: BufRead>(r: &'a mut Buffer) -> Self {
+        Self::with_policy(r, DecodePolicy::Strict)
+    }
+
+    /// Create a new decoder around a [BufRead] instance, which deals with malformed input
+    /// according to a given [DecodePolicy]
+    pub fn with_policy<Buffer: BufRead>(r: &'a mut Buffer, policy: DecodePolicy) -> Self {
+        let mut input = ByteStream::new(r);
+        let ((encoding, bom_len), pending) = match input.peek(DETECTION_LEN) {
+            Ok(bytes) => (detect_encoding(bytes), None),
+            Err(err) => ((Encoding::Utf8, 0), Some(err)),
+        };
+        input.consume(bom_len);
+
+        let inner: Box<dyn CharSource + 'a> = match encoding {
+            Encoding::Utf16Le => Box::new(Utf16Decoder::from_stream(
+                input,
+                ByteOrder::LittleEndian,
+                policy,
+            )),
+            Encoding::Utf16Be => Box::new(Utf16Decoder::from_stream(
+                input,
+                ByteOrder::BigEndian,
+                policy,
+            )),
+            Encoding::Utf32Le => Box::new(Utf32Decoder::from_stream(
+                input,
+                ByteOrder::LittleEndian,
+                policy,
+            )),
+            Encoding::Utf32Be => Box::new(Utf32Decoder::from_stream(
+                input,
+                ByteOrder::BigEndian,
+                policy,
+            )),
+            _ => Box::new(Utf8Decoder::from_stream(input, policy)),
+        };
+        AutoDecoder {
+            inner,
+            encoding,
+            pending,
+        }
+    }
+}
+
+impl<'a> CharSource for AutoDecoder<'a> {
+    /// Decode the next character from the underlying stream, surfacing any errors
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        if let Some(err) = self.pending.take() {
+            return Some(Err(err));
+        }
+        self.inner.next_char()
+    }
+
+    fn replacements(&self) -> usize {
+        self.inner.replacements()
+    }
+
+    fn encoding(&self) -> Option<Encoding> {
+        Some(self.encoding)
+    }
+}
+
+impl<'a> Iterator for AutoDecoder<'a> {
+    type Item = char;
+    /// Decode the next character from the underlying stream
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_char() {
+            Some(Ok(c)) => Some(c.ch),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufReader, Read};
+
+    use crate::auto::{detect_encoding, AutoDecoder};
+    use crate::common::{CharSource, DecoderErrorCode};
+    use crate::Encoding;
+
+    const SOURCE: &str = "{\"a\": [\"\u{1f600}\"]}";
+
+    fn encode(encoding: Encoding, bom: bool) -> Vec<u8> {
+        let source = if bom {
+            format!("\u{feff}{}", SOURCE)
+        } else {
+            SOURCE.to_string()
+        };
+        match encoding {
+            Encoding::Utf16Le => source.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            Encoding::Utf16Be => source.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            Encoding::Utf32Le => source
+                .chars()
+                .flat_map(|c| (c as u32).to_le_bytes())
+                .collect(),
+            Encoding::Utf32Be => source
+                .chars()
+                .flat_map(|c| (c as u32).to_be_bytes())
+                .collect(),
+            _ => source.into_bytes(),
+        }
+    }
+
+    #[test]
+    fn should_detect_byte_order_marks() {
+        let cases = [
+            (Encoding::Utf8, 3),
+            (Encoding::Utf16Le, 2),
+            (Encoding::Utf16Be, 2),
+            (Encoding::Utf32Le, 4),
+            (Encoding::Utf32Be, 4),
+        ];
+        for (encoding, bom_len) in cases {
+            assert_eq!(
+                detect_encoding(&encode(encoding, true)),
+                (encoding, bom_len)
+            );
+        }
+    }
+
+    #[test]
+    fn should_detect_encodings_from_zero_bytes() {
+        for encoding in [
+            Encoding::Utf8,
+            Encoding::Utf16Le,
+            Encoding::Utf16Be,
+            Encoding::Utf32Le,
+            Encoding::Utf32Be,
+        ] {
+            assert_eq!(detect_encoding(&encode(encoding, false)), (encoding, 0));
+        }
+    }
+
+    #[test]
+    fn should_detect_short_inputs() {
+        assert_eq!(detect_encoding(&[]), (Encoding::Utf8, 0));
+        assert_eq!(detect_encoding(b"1"), (Encoding::Utf8, 0));
+        assert_eq!(detect_encoding(&[0x31, 0x00]), (Encoding::Utf16Le, 0));
+        assert_eq!(detect_encoding(&[0x00, 0x31]), (Encoding::Utf16Be, 0));
+    }
+
+    #[test]
+    fn should_strip_boms_and_decode() {
+        for encoding in [
+            Encoding::Utf8,
+            Encoding::Utf16Le,
+            Encoding::Utf16Be,
+            Encoding::Utf32Le,
+            Encoding::Utf32Be,
+        ] {
+            for bom in [true, false] {
+                let bytes = encode(encoding, bom);
+                let mut reader = BufReader::with_capacity(1, bytes.as_slice());
+                let decoder = AutoDecoder::new(&mut reader);
+                assert_eq!(decoder.encoding(), Some(encoding));
+                assert_eq!(decoder.collect::<String>(), SOURCE);
+            }
+        }
+    }
+
+    #[test]
+    fn should_report_offsets_after_the_bom() {
+        let bytes = encode(Encoding::Utf8, true);
+        let mut reader = BufReader::new(bytes.as_slice());
+        let mut decoder = AutoDecoder::new(&mut reader);
+        let first = decoder.next_char().unwrap().unwrap();
+        assert_eq!((first.ch, first.offset), ('{', 3));
+    }
+
+    #[test]
+    fn should_report_a_failure_to_read_the_start_of_the_input() {
+        /// A reader which fails the first time it's read from, and then succeeds
+        struct FailsOnce<'a>(bool, &'a [u8]);
+        impl<'a> Read for FailsOnce<'a> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0 {
+                    return self.1.read(buf);
+                }
+                self.0 = true;
+                Err(io::Error::new(io::ErrorKind::Other, "failed"))
+            }
+        }
+        let mut reader = BufReader::new(FailsOnce(false, b"{}"));
+        let mut decoder = AutoDecoder::new(&mut reader);
+        assert_eq!(decoder.encoding(), Some(Encoding::Utf8));
+        let err = decoder.next_char().unwrap().unwrap_err();
+        assert_eq!(err.code, DecoderErrorCode::StreamFailure);
+        assert_eq!(decoder.collect::<String>(), "{}");
+    }
+}
@@ -2,6 +2,8 @@
 use std::borrow::Cow;
 use std::fmt::{Debug, Display, Formatter};
 
+use crate::Encoding;
+
 /// General result type used by a decoder instance
 pub type DecoderResult<T> = Result<T, DecoderError>;
 
@@ -14,6 +16,8 @@ pub enum DecoderErrorCode {
     InvalidByteSequence,
     /// Out of range error
     OutOfRange,
+    /// Detected a surrogate code unit without a matching partner
+    UnpairedSurrogate,
     /// The end of the input has been reached
     EndOfInput,
 }
@@ -64,3 +68,193 @@ macro_rules! invalid_byte_sequence {
         })
     };
 }
+
+/// Convert specific error codes into human-readable strings
+impl Display for DecoderErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecoderErrorCode::StreamFailure => write!(f, "failure in the underlying stream"),
+            DecoderErrorCode::InvalidByteSequence => write!(f, "invalid byte sequence"),
+            DecoderErrorCode::OutOfRange => write!(f, "value out of range"),
+            DecoderErrorCode::UnpairedSurrogate => write!(f, "unpaired surrogate"),
+            DecoderErrorCode::EndOfInput => write!(f, "end of input reached"),
+        }
+    }
+}
+
+/// The order in which the bytes of multi-byte code units are laid out within the input
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ByteOrder {
+    /// Least significant byte first
+    LittleEndian,
+    /// Most significant byte first
+    BigEndian,
+}
+
+/// A decoded [char] along with the offset of its first byte within the underlying input, and the
+/// number of bytes it occupies
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CharWithOffset {
+    /// The decoded character
+    pub ch: char,
+    /// The byte offset at which the encoded character starts
+    pub offset: usize,
+    /// The number of bytes taken up by the encoded character. For a replacement character, this is
+    /// the length of the malformed sequence which was replaced
+    pub len: usize,
+}
+
+/// A fallible source of decoded characters.  Unlike the [Iterator] implementations provided by
+/// the individual decoders, which simply stop on the first failure, a [CharSource] will surface any
+/// [DecoderError] so that bad input can be distinguished from the end of the input
+pub trait CharSource {
+    /// Decode the next character, returning [None] once the end of the input has been reached
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>>;
+
+    /// The number of malformed sequences which have been replaced or skipped so far, as a result
+    /// of a lossy [DecodePolicy]
+    fn replacements(&self) -> usize {
+        0
+    }
+
+    /// The [Encoding] being decoded, which for an [crate::auto::AutoDecoder] is the encoding that
+    /// was detected. Sources of characters which have already been decoded return [None]
+    fn encoding(&self) -> Option<Encoding> {
+        None
+    }
+}
+
+impl<S: CharSource + ?Sized> CharSource for &mut S {
+    #[inline]
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        (**self).next_char()
+    }
+
+    fn replacements(&self) -> usize {
+        (**self).replacements()
+    }
+
+    fn encoding(&self) -> Option<Encoding> {
+        (**self).encoding()
+    }
+}
+
+impl<S: CharSource + ?Sized> CharSource for Box<S> {
+    #[inline]
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        (**self).next_char()
+    }
+
+    fn replacements(&self) -> usize {
+        (**self).replacements()
+    }
+
+    fn encoding(&self) -> Option<Encoding> {
+        (**self).encoding()
+    }
+}
+
+/// The character used to replace malformed input when using [DecodePolicy::Replace]
+pub const REPLACEMENT_CHARACTER: char = '\u{fffd}';
+
+/// Enumeration of the different ways in which a decoder may deal with malformed input
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DecodePolicy {
+    /// Report an error for any malformed input
+    Strict,
+    /// Replace each maximal subpart of a malformed sequence with [REPLACEMENT_CHARACTER], as per
+    /// the WHATWG encoding standard, and then carry on decoding
+    Replace,
+    /// Silently drop malformed input and carry on decoding
+    Skip,
+}
+
+impl Default for DecodePolicy {
+    fn default() -> Self {
+        DecodePolicy::Strict
+    }
+}
+
+/// Applies a [DecodePolicy] to the failures reported by a decoder, keeping a tally of the number of
+/// malformed sequences that have been replaced or skipped
+#[derive(Debug, Default)]
+pub(crate) struct Recovery {
+    /// The policy to apply
+    policy: DecodePolicy,
+    /// The number of malformed sequences replaced or skipped so far
+    replacements: usize,
+}
+
+impl Recovery {
+    /// Create a new instance for a given policy
+    pub fn new(policy: DecodePolicy) -> Self {
+        Recovery {
+            policy,
+            replacements: 0,
+        }
+    }
+
+    /// The number of malformed sequences replaced or skipped so far
+    #[inline]
+    pub fn replacements(&self) -> usize {
+        self.replacements
+    }
+
+    /// Deal with a failure which occurred whilst decoding a sequence of `len` bytes starting at
+    /// `offset`.  Returns
+    /// [None] if the failure has been skipped, in which case decoding should simply carry on.
+    /// Failures in the underlying stream are always reported, regardless of the policy
+    pub fn recover(
+        &mut self,
+        err: DecoderError,
+        offset: usize,
+        len: usize,
+    ) -> Option<DecoderResult<CharWithOffset>> {
+        if err.code == DecoderErrorCode::StreamFailure {
+            return Some(Err(err));
+        }
+        match self.policy {
+            DecodePolicy::Strict => Some(Err(err)),
+            DecodePolicy::Replace => {
+                self.replacements += 1;
+                Some(Ok(CharWithOffset {
+                    ch: REPLACEMENT_CHARACTER,
+                    offset,
+                    len,
+                }))
+            }
+            DecodePolicy::Skip => {
+                self.replacements += 1;
+                None
+            }
+        }
+    }
+}
+
+/// Adapter which allows any [Iterator] over already decoded [char]s to be used as a [CharSource].
+/// Byte offsets are calculated based on the UTF-8 encoded length of each character
+pub struct CharIterSource<I: Iterator<Item = char>> {
+    /// The underlying iterator
+    chars: I,
+    /// The current byte offset
+    offset: usize,
+}
+
+impl<I: Iterator<Item = char>> CharIterSource<I> {
+    /// Wrap an [Iterator] of [char]
+    pub fn new(chars: I) -> Self {
+        CharIterSource { chars, offset: 0 }
+    }
+}
+
+impl<I: Iterator<Item = char>> CharSource for CharIterSource<I> {
+    #[inline]
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        self.chars.next().map(|ch| {
+            let offset = self.offset;
+            let len = ch.len_utf8();
+            self.offset += len;
+            Ok(CharWithOffset { ch, offset, len })
+        })
+    }
+}
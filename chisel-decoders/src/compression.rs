@@ -0,0 +1,202 @@
+//! Transparent decompression of input, available when either the `gzip` or `zstd` features are
+//! enabled.
+//!
+//! A [DecompressingReader] sniffs the first few bytes of its input for a known magic number, and
+//! if one is found, inserts the appropriate decompressor between the input and whichever decoder
+//! is subsequently used.  Input which doesn't appear to be compressed is passed straight through.
+//! Because decompression happens before decoding, any offsets reported by a decoder are relative to
+//! the *decompressed* text.
+use std::io::{self, BufRead, Cursor, Read};
+
+/// Magic number found at the start of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Magic number found at the start of a zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// The number of bytes needed in order to detect a compression format
+const DETECTION_LEN: usize = 4;
+
+/// Enumeration of supported compression formats
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Compression {
+    /// The input isn't compressed
+    None,
+    /// The input is gzip compressed
+    Gzip,
+    /// The input is zstd compressed
+    Zstd,
+}
+
+/// Work out the compression format of some input based on its first few bytes
+pub fn detect_compression(bytes: &[u8]) -> Compression {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        Compression::Gzip
+    } else if bytes.starts_with(&ZSTD_MAGIC) {
+        Compression::Zstd
+    } else {
+        Compression::None
+    }
+}
+
+/// A [BufRead] implementation which transparently decompresses its input
+pub struct DecompressingReader<'a> {
+    /// The (possibly decompressing) reader
+    inner: Box<dyn BufRead + 'a>,
+    /// The detected compression format
+    compression: Compression,
+}
+
+impl<'a> DecompressingReader<'a> {
+    /// Create a new reader around a [BufRead] instance. The first few bytes of the input are read
+    /// straight away in order to determine the compression format.  An error is returned if the
+    /// input can't be read, or if it has been compressed using a format for which support hasn't
+    /// been enabled
+    pub fn new<B: BufRead + 'a>(mut input: B) -> io::Result<Self> {
+        let mut prefix = Vec::with_capacity(DETECTION_LEN);
+        (&mut input)
+            .take(DETECTION_LEN as u64)
+            .read_to_end(&mut prefix)?;
+        let compression = detect_compression(&prefix);
+
+        // put the sniffed bytes back in front of the remaining input
+        let input = Cursor::new(prefix).chain(input);
+        let inner: Box<dyn BufRead + 'a> = match compression {
+            Compression::None => Box::new(input),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Box::new(io::BufReader::new(
+                flate2::bufread::MultiGzDecoder::new(input),
+            )),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Box::new(io::BufReader::new(zstd::Decoder::with_buffer(input)?)),
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "support for {:?} compressed input isn't enabled",
+                        compression
+                    ),
+                ))
+            }
+        };
+        Ok(DecompressingReader { inner, compression })
+    }
+
+    /// The compression format which was detected for the input
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+}
+
+impl<'a> Read for DecompressingReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<'a> BufRead for DecompressingReader<'a> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::BufReader;
+
+    use crate::compression::{detect_compression, Compression, DecompressingReader};
+    use crate::utf8::Utf8Decoder;
+
+    fn fuzz_bytes() -> Vec<u8> {
+        fs::read("fixtures/fuzz.txt").unwrap()
+    }
+
+    fn decompress(bytes: &[u8]) -> (Compression, String) {
+        let mut reader = DecompressingReader::new(BufReader::with_capacity(3, bytes)).unwrap();
+        let compression = reader.compression();
+        (compression, Utf8Decoder::new(&mut reader).collect())
+    }
+
+    #[cfg(feature = "gzip")]
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn should_detect_magic_numbers() {
+        assert_eq!(detect_compression(&[0x1f, 0x8b, 0x08]), Compression::Gzip);
+        assert_eq!(
+            detect_compression(&[0x28, 0xb5, 0x2f, 0xfd]),
+            Compression::Zstd
+        );
+        assert_eq!(detect_compression(b"{}"), Compression::None);
+        assert_eq!(detect_compression(&[]), Compression::None);
+    }
+
+    #[test]
+    fn should_pass_through_uncompressed_input() {
+        let bytes = fuzz_bytes();
+        let (compression, decoded) = decompress(&bytes);
+        assert_eq!(compression, Compression::None);
+        assert_eq!(decoded.as_bytes(), bytes.as_slice());
+        assert_eq!(decompress(b"1").1, "1");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn should_decompress_gzip_input() {
+        let bytes = fuzz_bytes();
+        let (compression, decoded) = decompress(&gzip(&bytes));
+        assert_eq!(compression, Compression::Gzip);
+        assert_eq!(decoded.as_bytes(), bytes.as_slice());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn should_decompress_concatenated_gzip_members() {
+        let mut compressed = gzip(b"[1, ");
+        compressed.extend(gzip(b"2]"));
+        assert_eq!(decompress(&compressed).1, "[1, 2]");
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn should_reject_formats_which_are_not_enabled() {
+        let bytes: &[u8] = &[0x1f, 0x8b, 0x08, 0x00];
+        let err = DecompressingReader::new(bytes).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn should_decompress_zstd_input() {
+        let bytes = fuzz_bytes();
+        let compressed = zstd::encode_all(bytes.as_slice(), 3).unwrap();
+        let (compression, decoded) = decompress(&compressed);
+        assert_eq!(compression, Compression::Zstd);
+        assert_eq!(decoded.as_bytes(), bytes.as_slice());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn should_report_offsets_within_the_decompressed_text() {
+        use crate::common::CharSource;
+
+        let compressed = gzip(b"ab\xffc");
+        let mut reader = DecompressingReader::new(compressed.as_slice()).unwrap();
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        assert_eq!(decoder.next_char().unwrap().unwrap().offset, 0);
+        assert_eq!(decoder.next_char().unwrap().unwrap().offset, 1);
+        assert!(decoder.next_char().unwrap().is_err());
+        assert_eq!(decoder.next_char().unwrap().unwrap().offset, 3);
+    }
+}
@@ -0,0 +1,395 @@
+//! Character encoders, which go in the opposite direction to the decoders: [char]s in, bytes out.
+//!
+//! Each encoder wraps a mutable reference to a [Write] instance, in the same way that the decoders
+//! wrap a [std::io::BufRead].  No buffering is carried out by the encoders themselves, so for best
+//! performance the output should be wrapped in a [std::io::BufWriter].
+//!
+//! Pairing a decoder with an encoder allows for input to be transcoded end to end:
+//!
+//! ```rust
+//!     # use std::io::BufReader;
+//!     # use chisel_decoders::common::ByteOrder;
+//!     # use chisel_decoders::encoders::{AsciiEncoder, AsciiFallback, Encoder};
+//!     # use chisel_decoders::utf16::Utf16Decoder;
+//!
+//!     let input: &[u8] = &[0x22, 0x00, 0xe9, 0x00, 0x22, 0x00];
+//!     let mut reader = BufReader::new(input);
+//!     let mut output = vec![];
+//!     let mut encoder = AsciiEncoder::with_fallback(&mut output, AsciiFallback::Escape);
+//!     for ch in Utf16Decoder::new(&mut reader, ByteOrder::LittleEndian) {
+//!         encoder.encode_char(ch).unwrap();
+//!     }
+//!     assert_eq!(output, b"\"\\u00e9\"");
+//! ```
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+
+use crate::common::ByteOrder;
+
+/// General result type used by an encoder instance
+pub type EncoderResult<T> = Result<T, EncoderError>;
+
+/// Enumeration of different encoder errors
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EncoderErrorCode {
+    /// Something went pear-shaped in the underlying stream
+    StreamFailure,
+    /// A character can't be represented in the target encoding
+    Unencodable,
+}
+
+/// Structure for encoding errors
+#[derive(Debug, Clone)]
+pub struct EncoderError {
+    /// The error code
+    pub code: EncoderErrorCode,
+
+    /// Associated error message
+    pub message: Cow<'static, str>,
+}
+
+impl Display for EncoderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Code: {:?}, Message: {}", self.code, self.message)
+    }
+}
+
+/// Convert specific error codes into human-readable strings
+impl Display for EncoderErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncoderErrorCode::StreamFailure => write!(f, "failure in the underlying stream"),
+            EncoderErrorCode::Unencodable => write!(f, "unencodable character"),
+        }
+    }
+}
+
+/// Helper macro for generating errors
+#[macro_export]
+macro_rules! encoder_error {
+    ($code : expr, $msg : expr) => {
+        EncoderError {
+            code: $code,
+            message: $msg.into(),
+        }
+    };
+}
+
+/// Write a slice of bytes to the output, mapping any failure into an [EncoderError]
+#[inline]
+fn write_bytes<W: Write>(output: &mut W, bytes: &[u8]) -> EncoderResult<()> {
+    output
+        .write_all(bytes)
+        .map_err(|_| encoder_error!(EncoderErrorCode::StreamFailure, "failed to write output"))
+}
+
+/// Flush the output, mapping any failure into an [EncoderError]
+#[inline]
+fn flush_output<W: Write>(output: &mut W) -> EncoderResult<()> {
+    output
+        .flush()
+        .map_err(|_| encoder_error!(EncoderErrorCode::StreamFailure, "failed to flush output"))
+}
+
+/// Lower case hexadecimal digits, used when writing out `\uXXXX` escapes
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Build the JSON-style `\uXXXX` escape for a single UTF-16 code unit
+#[inline]
+fn escape_utf16_unit(unit: u16) -> [u8; 6] {
+    let mut escape = *b"\\u0000";
+    for (i, shift) in [12, 8, 4, 0].iter().enumerate() {
+        escape[i + 2] = HEX_DIGITS[((unit >> shift) & 0xf) as usize];
+    }
+    escape
+}
+
+/// A sink for [char]s, which encodes each one and writes the result to an underlying output
+pub trait Encoder {
+    /// Encode a single character
+    fn encode_char(&mut self, ch: char) -> EncoderResult<()>;
+
+    /// Encode each of the characters within a string slice
+    fn encode_str(&mut self, s: &str) -> EncoderResult<()> {
+        for ch in s.chars() {
+            self.encode_char(ch)?;
+        }
+        Ok(())
+    }
+
+    /// Flush the underlying output
+    fn flush(&mut self) -> EncoderResult<()>;
+
+    /// The number of bytes written so far
+    fn bytes_written(&self) -> usize;
+}
+
+/// A UTF-8 encoder, which takes a ref to a [Write] instance
+pub struct Utf8Encoder<'a, W: Write> {
+    /// The output stream
+    output: &'a mut W,
+    /// The number of bytes written so far
+    written: usize,
+}
+
+impl<'a, W: Write> Utf8Encoder<'a, W> {
+    /// Create a new encoder around a [Write] instance
+    pub fn new(w: &'a mut W) -> Self {
+        Utf8Encoder {
+            output: w,
+            written: 0,
+        }
+    }
+}
+
+impl<'a, W: Write> Encoder for Utf8Encoder<'a, W> {
+    fn encode_char(&mut self, ch: char) -> EncoderResult<()> {
+        let mut buffer = [0; 4];
+        let bytes = ch.encode_utf8(&mut buffer).as_bytes();
+        write_bytes(self.output, bytes)?;
+        self.written += bytes.len();
+        Ok(())
+    }
+
+    /// Strings are already UTF-8, so can be written out as is
+    fn encode_str(&mut self, s: &str) -> EncoderResult<()> {
+        write_bytes(self.output, s.as_bytes())?;
+        self.written += s.len();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> EncoderResult<()> {
+        flush_output(self.output)
+    }
+
+    fn bytes_written(&self) -> usize {
+        self.written
+    }
+}
+
+/// A UTF-16 encoder, which takes a ref to a [Write] instance. Characters outside of the basic
+/// multilingual plane are written out as surrogate pairs
+pub struct Utf16Encoder<'a, W: Write> {
+    /// The output stream
+    output: &'a mut W,
+    /// The byte order of the output
+    order: ByteOrder,
+    /// The number of bytes written so far
+    written: usize,
+}
+
+impl<'a, W: Write> Utf16Encoder<'a, W> {
+    /// Create a new encoder around a [Write] instance, using a given [ByteOrder]
+    pub fn new(w: &'a mut W, order: ByteOrder) -> Self {
+        Utf16Encoder {
+            output: w,
+            order,
+            written: 0,
+        }
+    }
+}
+
+impl<'a, W: Write> Encoder for Utf16Encoder<'a, W> {
+    fn encode_char(&mut self, ch: char) -> EncoderResult<()> {
+        let mut units = [0; 2];
+        for unit in ch.encode_utf16(&mut units).iter() {
+            let bytes = match self.order {
+                ByteOrder::LittleEndian => unit.to_le_bytes(),
+                ByteOrder::BigEndian => unit.to_be_bytes(),
+            };
+            write_bytes(self.output, &bytes)?;
+            self.written += 2;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> EncoderResult<()> {
+        flush_output(self.output)
+    }
+
+    fn bytes_written(&self) -> usize {
+        self.written
+    }
+}
+
+/// Enumeration of the different ways in which an [AsciiEncoder] may deal with non-ASCII characters
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AsciiFallback {
+    /// Report an error for any non-ASCII character
+    Error,
+    /// Write out non-ASCII characters as JSON-style `\uXXXX` escapes, using a surrogate pair for
+    /// anything outside of the basic multilingual plane
+    Escape,
+}
+
+impl Default for AsciiFallback {
+    fn default() -> Self {
+        AsciiFallback::Error
+    }
+}
+
+/// An ASCII encoder, which takes a ref to a [Write] instance
+pub struct AsciiEncoder<'a, W: Write> {
+    /// The output stream
+    output: &'a mut W,
+    /// What to do with non-ASCII characters
+    fallback: AsciiFallback,
+    /// The number of bytes written so far
+    written: usize,
+}
+
+impl<'a, W: Write> AsciiEncoder<'a, W> {
+    /// Create a new encoder around a [Write] instance, which will report an error for any
+    /// non-ASCII characters
+    pub fn new(w: &'a mut W) -> Self {
+        Self::with_fallback(w, AsciiFallback::Error)
+    }
+
+    /// Create a new encoder around a [Write] instance, which deals with non-ASCII characters
+    /// according to a given [AsciiFallback]
+    pub fn with_fallback(w: &'a mut W, fallback: AsciiFallback) -> Self {
+        AsciiEncoder {
+            output: w,
+            fallback,
+            written: 0,
+        }
+    }
+}
+
+impl<'a, W: Write> Encoder for AsciiEncoder<'a, W> {
+    fn encode_char(&mut self, ch: char) -> EncoderResult<()> {
+        if ch.is_ascii() {
+            write_bytes(self.output, &[ch as u8])?;
+            self.written += 1;
+            return Ok(());
+        }
+        match self.fallback {
+            AsciiFallback::Error => Err(encoder_error!(
+                EncoderErrorCode::Unencodable,
+                format!("non-ascii character detected: {:?}", ch)
+            )),
+            AsciiFallback::Escape => {
+                let mut units = [0; 2];
+                for unit in ch.encode_utf16(&mut units).iter() {
+                    write_bytes(self.output, &escape_utf16_unit(*unit))?;
+                    self.written += 6;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> EncoderResult<()> {
+        flush_output(self.output)
+    }
+
+    fn bytes_written(&self) -> usize {
+        self.written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{self, BufReader, Write};
+
+    use crate::common::ByteOrder;
+    use crate::encoders::{
+        escape_utf16_unit, AsciiEncoder, AsciiFallback, Encoder, EncoderErrorCode, Utf16Encoder,
+        Utf8Encoder,
+    };
+    use crate::utf16::Utf16Decoder;
+    use crate::utf8::Utf8Decoder;
+
+    fn fuzz_text() -> String {
+        fs::read_to_string("fixtures/fuzz.txt").unwrap()
+    }
+
+    #[test]
+    fn should_round_trip_utf8() {
+        let text = fuzz_text();
+        let mut output = vec![];
+        let mut encoder = Utf8Encoder::new(&mut output);
+        for ch in text.chars() {
+            encoder.encode_char(ch).unwrap();
+        }
+        assert_eq!(encoder.bytes_written(), text.len());
+        let mut reader = BufReader::new(output.as_slice());
+        assert_eq!(Utf8Decoder::new(&mut reader).collect::<String>(), text);
+    }
+
+    #[test]
+    fn should_round_trip_utf16_in_both_byte_orders() {
+        let text = fuzz_text();
+        for order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+            let mut output = vec![];
+            let mut encoder = Utf16Encoder::new(&mut output, order);
+            encoder.encode_str(&text).unwrap();
+            assert_eq!(encoder.bytes_written(), text.encode_utf16().count() * 2);
+            let mut reader = BufReader::new(output.as_slice());
+            assert_eq!(
+                Utf16Decoder::new(&mut reader, order).collect::<String>(),
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn should_write_surrogate_pairs() {
+        let mut output = vec![];
+        Utf16Encoder::new(&mut output, ByteOrder::BigEndian)
+            .encode_char('\u{1f600}')
+            .unwrap();
+        assert_eq!(output, vec![0xd8, 0x3d, 0xde, 0x00]);
+    }
+
+    #[test]
+    fn should_escape_non_ascii_characters() {
+        let mut output = vec![];
+        let mut encoder = AsciiEncoder::with_fallback(&mut output, AsciiFallback::Escape);
+        encoder.encode_str("caf\u{e9} \u{1f600}").unwrap();
+        assert_eq!(encoder.bytes_written(), 22);
+        assert_eq!(output, b"caf\\u00e9 \\ud83d\\ude00");
+    }
+
+    #[test]
+    fn should_escape_utf16_units() {
+        for unit in [0x0080_u16, 0x0abc, 0xd83d, 0xffff] {
+            let expected = format!("\\u{:04x}", unit);
+            assert_eq!(&escape_utf16_unit(unit), expected.as_bytes());
+        }
+    }
+
+    #[test]
+    fn should_reject_non_ascii_characters() {
+        let mut output = vec![];
+        let mut encoder = AsciiEncoder::new(&mut output);
+        let err = encoder.encode_str("ab\u{e9}c").err().unwrap();
+        assert_eq!(err.code, EncoderErrorCode::Unencodable);
+        assert_eq!(output, b"ab");
+    }
+
+    #[test]
+    fn should_report_stream_failures() {
+        struct Failing;
+        impl Write for Failing {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "failed"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Err(io::Error::new(io::ErrorKind::Other, "failed"))
+            }
+        }
+        let mut output = Failing;
+        let mut encoder = Utf8Encoder::new(&mut output);
+        assert_eq!(
+            encoder.encode_char('a').err().unwrap().code,
+            EncoderErrorCode::StreamFailure
+        );
+        assert_eq!(
+            encoder.flush().err().unwrap().code,
+            EncoderErrorCode::StreamFailure
+        );
+    }
+}
@@ -0,0 +1,91 @@
+#![allow(dead_code)]
+//! A character-oriented decoder implementation that will take an underlying [std::u8] (byte) source
+//! and produce a stream of decoded ISO-8859-1 (Latin-1) characters
+use std::io::BufRead;
+
+use crate::common::*;
+use crate::stream::ByteStream;
+use crate::{end_of_input, Encoding};
+
+/// A Latin-1 decoder, which takes a ref to a [BufRead] instance.  Every byte value maps directly
+/// onto the Unicode code point with the same value, so decoding can never fail
+pub struct Latin1Decoder<'a, B: BufRead> {
+    /// The input stream
+    input: ByteStream<'a, B>,
+}
+
+impl<'a, Buffer: BufRead> Latin1Decoder<'a, Buffer> {
+    /// Create a new decoder around a [BufRead] instance. Input is read in chunks as decoding
+    /// progresses
+    pub fn new(r: &'a mut Buffer) -> Self {
+        Latin1Decoder {
+            input: ByteStream::new(r),
+        }
+    }
+
+    /// Attempt to decode the next character in the underlying stream.
+    fn decode_next(&mut self) -> DecoderResult<char> {
+        let byte = match self.input.peek(1)?.first() {
+            Some(b) => *b,
+            None => return end_of_input!(),
+        };
+        self.input.consume(1);
+        Ok(char::from(byte))
+    }
+}
+
+impl<'a, B: BufRead> CharSource for Latin1Decoder<'a, B> {
+    /// Decode the next character from the underlying stream, surfacing any errors
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        let offset = self.input.offset();
+        match self.decode_next() {
+            Ok(ch) => Some(Ok(CharWithOffset { ch, offset, len: 1 })),
+            Err(err) if err.code == DecoderErrorCode::EndOfInput => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    fn encoding(&self) -> Option<Encoding> {
+        Some(Encoding::Latin1)
+    }
+}
+
+impl<'a, B: BufRead> Iterator for Latin1Decoder<'a, B> {
+    type Item = char;
+    /// Decode the next character from the underlying stream
+    fn next(&mut self) -> Option<Self::Item> {
+        self.decode_next().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::common::CharSource;
+    use crate::latin1::Latin1Decoder;
+
+    #[test]
+    fn should_decode_every_byte_value() {
+        let bytes: Vec<u8> = (0..=0xff).collect();
+        let mut reader = BufReader::with_capacity(7, bytes.as_slice());
+        let decoder = Latin1Decoder::new(&mut reader);
+        let expected: String = (0..=0xffu32).map(|b| char::from_u32(b).unwrap()).collect();
+        assert_eq!(decoder.collect::<String>(), expected);
+    }
+
+    #[test]
+    fn should_report_byte_offsets() {
+        let bytes: &[u8] = b"caf\xe9!";
+        let mut reader = BufReader::new(bytes);
+        let mut decoder = Latin1Decoder::new(&mut reader);
+        let mut decoded = vec![];
+        while let Some(Ok(c)) = decoder.next_char() {
+            decoded.push((c.ch, c.offset));
+        }
+        assert_eq!(
+            decoded,
+            vec![('c', 0), ('a', 1), ('f', 2), ('\u{e9}', 3), ('!', 4)]
+        );
+    }
+}
@@ -1,13 +1,25 @@
+#![cfg_attr(feature = "portable-simd", feature(portable_simd))]
 //! ## Overview
 //!
 //! This crate contains a very simple, lean implementations of decoders that will consume `u8` bytes from a given
-//! `Read` implementation, and decode into the Rust internal `char` type using either UTF-8 or ASCII.
+//! `Read` implementation, and decode into the Rust internal `char` type using either UTF-8, UTF-16, UTF-32,
+//! ASCII or one of the single-byte legacy encodings (Latin-1 and Windows-1252).
 //!
 //! The decoder implementations are pretty fast and loose: under the covers they utilise some bit-twiddlin' in
-//! conjunction with the *unsafe* `transmute` function to do the conversions.
+//! conjunction with the *unsafe* `char::from_u32_unchecked` function to do the conversions.
+//!
+//! Input is pulled from the underlying [BufRead] in chunks as decoding progresses, rather than being read
+//! into memory up front, so the memory used by a decoder stays constant regardless of the size of the input.
+//!
+//! Runs of ASCII input (which make up the bulk of most JSON) are detected several bytes at a time, and then
+//! served without per-byte classification. Enabling the `portable-simd` feature (which requires a nightly
+//! toolchain) switches this detection over to `std::simd`, otherwise a portable word-at-a-time check is used.
 //!
 //! *No string allocations are used during conversion*.
 //!
+//! Going in the other direction, the `encoders` module provides UTF-8, UTF-16 and ASCII encoders which write
+//! `char`s back out to any `Write` implementation, so that input can be transcoded end to end.
+//!
 //! ### Usage
 //!
 //! Usage is very simple, provided you have something that implements `Read` in order to source some bytes:
@@ -35,6 +47,54 @@
 //!     let _decoder = AsciiDecoder::new(&mut reader);
 //! ```
 //!
+//! UTF-16 and UTF-32 input may be laid out in either byte order, so the decoders for these encodings take a
+//! `ByteOrder` in addition to the input:
+//!
+//! ```rust
+//!     # use std::io::BufReader;
+//!     # use chisel_decoders::common::ByteOrder;
+//!     # use chisel_decoders::utf16::Utf16Decoder;
+//!
+//!     let buffer: &[u8] = &[0x7b, 0x00, 0x7d, 0x00];
+//!     let mut reader = BufReader::new(buffer);
+//!     let decoder = Utf16Decoder::new(&mut reader, ByteOrder::LittleEndian);
+//!     assert_eq!(decoder.collect::<String>(), "{}");
+//! ```
+//!
+//! If you don't know the encoding of the input up front, then the `AutoDecoder` will work it out from any byte
+//! order mark (which is skipped) or from the pattern of zero bytes at the start of the input:
+//!
+//! ```rust
+//!     # use std::io::BufReader;
+//!     # use chisel_decoders::auto::AutoDecoder;
+//!     # use chisel_decoders::common::CharSource;
+//!     # use chisel_decoders::Encoding;
+//!
+//!     let buffer: &[u8] = &[0xff, 0xfe, 0x7b, 0x00, 0x7d, 0x00];
+//!     let mut reader = BufReader::new(buffer);
+//!     let decoder = AutoDecoder::new(&mut reader);
+//!     assert_eq!(decoder.encoding(), Some(Encoding::Utf16Le));
+//!     assert_eq!(decoder.collect::<String>(), "{}");
+//! ```
+//!
+//! ### Dealing with malformed input
+//!
+//! By default, malformed input results in an error. Alternatively, a decoder may be created with a lossy
+//! `DecodePolicy`, in which case malformed sequences are either replaced with U+FFFD or skipped entirely:
+//!
+//! ```rust
+//!     # use std::io::BufReader;
+//!     # use chisel_decoders::common::{CharSource, DecodePolicy};
+//!     # use chisel_decoders::utf8::Utf8Decoder;
+//!
+//!     let buffer: &[u8] = &[0x61, 0xe2, 0x82, 0x62];
+//!     let mut reader = BufReader::new(buffer);
+//!     let mut decoder = Utf8Decoder::with_policy(&mut reader, DecodePolicy::Replace);
+//!     let decoded: String = (&mut decoder).collect();
+//!     assert_eq!(decoded, "a\u{fffd}b");
+//!     assert_eq!(decoder.replacements(), 1);
+//! ```
+//!
 //! ### Create from a file
 //!
 //! Just crack open your file, wrap in a `Read` instance and then plug into a new instance of `Utf8Decoder`:
@@ -50,6 +110,44 @@
 //!     let mut reader = BufReader::new(f.unwrap());
 //!     let _decoder = Utf8Decoder::new(&mut reader);
 //! ```
+//! ### Create from a memory-mapped file
+//!
+//! With the `mmap` feature enabled, very large files can be mapped into memory so that the decoders read straight
+//! from the mapped bytes, rather than copying each chunk of the file into an intermediate buffer:
+//!
+//! ```rust
+//!     # #[cfg(feature = "mmap")]
+//!     # {
+//!     # use chisel_decoders::mmap::MappedFile;
+//!     # use chisel_decoders::utf8::Utf8Decoder;
+//!
+//!     let mapped = MappedFile::open("./Cargo.toml").unwrap();
+//!     let mut reader = mapped.reader();
+//!     let _decoder = Utf8Decoder::new(&mut reader);
+//!     # }
+//! ```
+//!
+//! ### Compressed input
+//!
+//! With either the `gzip` or `zstd` features enabled, a `DecompressingReader` can be placed in front of any decoder
+//! in order to transparently decompress input. The compression format is detected from the magic number at the
+//! start of the input, and uncompressed input is simply passed through:
+//!
+//! ```rust
+//!     # #[cfg(any(feature = "gzip", feature = "zstd"))]
+//!     # {
+//!     # use std::io::BufReader;
+//!     # use chisel_decoders::compression::{Compression, DecompressingReader};
+//!     # use chisel_decoders::utf8::Utf8Decoder;
+//!
+//!     let buffer: &[u8] = b"{}";
+//!     let mut reader = DecompressingReader::new(BufReader::new(buffer)).unwrap();
+//!     assert_eq!(reader.compression(), Compression::None);
+//!     let decoder = Utf8Decoder::new(&mut reader);
+//!     assert_eq!(decoder.collect::<String>(), "{}");
+//!     # }
+//! ```
+//!
 //! ### Consuming Decoded `chars`
 //!
 //! Once you've created an instance of a specific decoder, you simply iterate over the `chars` in
@@ -70,20 +168,62 @@
 //!     }
 //! ```
 //!
+//! Note that the [Iterator] implementations will simply stop at the first decoding failure. If you need to be
+//! able to tell the difference between malformed input and the end of the input, then use the [CharSource] trait
+//! instead, which yields a [common::DecoderResult] for each character along with its byte offset:
+//!
+//! ```rust
+//!     # use std::io::BufReader;
+//!     # use chisel_decoders::common::CharSource;
+//!     # use chisel_decoders::utf8::Utf8Decoder;
+//!
+//!     let buffer: &[u8] = &[0x61, 0xff, 0x62];
+//!     let mut reader = BufReader::new(buffer);
+//!     let mut decoder = Utf8Decoder::new(&mut reader);
+//!     assert_eq!(decoder.next_char().unwrap().unwrap().ch, 'a');
+//!     assert!(decoder.next_char().unwrap().is_err());
+//! ```
+//!
 use std::io::BufRead;
 
 use crate::ascii::AsciiDecoder;
+use crate::auto::AutoDecoder;
+use crate::common::{ByteOrder, CharSource, DecodePolicy};
+use crate::latin1::Latin1Decoder;
+use crate::utf16::Utf16Decoder;
+use crate::utf32::Utf32Decoder;
 use crate::utf8::Utf8Decoder;
+use crate::windows1252::Windows1252Decoder;
 
 pub mod ascii;
+pub mod auto;
 pub mod common;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub mod compression;
+pub mod encoders;
+pub mod latin1;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+mod scan;
+mod stream;
+pub mod utf16;
+pub mod utf32;
 pub mod utf8;
+pub mod windows1252;
 
 /// Enumeration of different supported encoding types
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Encoding {
+    /// Detect the encoding from the start of the input (see [auto::AutoDecoder])
+    Auto,
     Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
     Ascii,
+    Latin1,
+    Windows1252,
 }
 
 /// Default encoding is UTF-8
@@ -94,9 +234,7 @@ impl Default for Encoding {
 }
 
 /// Helper function for constructing a default decoder, wrapped around an input buffer
-pub fn default_decoder<'a, Buffer: BufRead>(
-    buffer: &'a mut Buffer,
-) -> Box<dyn Iterator<Item = char> + 'a> {
+pub fn default_decoder<'a, Buffer: BufRead>(buffer: &'a mut Buffer) -> Box<dyn CharSource + 'a> {
     Box::new(Utf8Decoder::new(buffer))
 }
 
@@ -104,10 +242,29 @@ pub fn default_decoder<'a, Buffer: BufRead>(
 pub fn new_decoder<'a, Buffer: BufRead>(
     buffer: &'a mut Buffer,
     encoding: Encoding,
-) -> Box<dyn Iterator<Item = char> + 'a> {
+) -> Box<dyn CharSource + 'a> {
+    new_decoder_with_policy(buffer, encoding, DecodePolicy::Strict)
+}
+
+/// Helper function for constructing a specific decoder, wrapped around an input buffer, which deals
+/// with malformed input according to a given [DecodePolicy]. The single-byte encodings have a
+/// mapping for every possible byte, so the policy has no effect on them
+pub fn new_decoder_with_policy<'a, Buffer: BufRead>(
+    buffer: &'a mut Buffer,
+    encoding: Encoding,
+    policy: DecodePolicy,
+) -> Box<dyn CharSource + 'a> {
+    use ByteOrder::*;
     match encoding {
-        Encoding::Ascii => Box::new(AsciiDecoder::new(buffer)),
-        Encoding::Utf8 => Box::new(Utf8Decoder::new(buffer)),
+        Encoding::Auto => Box::new(AutoDecoder::with_policy(buffer, policy)),
+        Encoding::Ascii => Box::new(AsciiDecoder::with_policy(buffer, policy)),
+        Encoding::Utf8 => Box::new(Utf8Decoder::with_policy(buffer, policy)),
+        Encoding::Utf16Le => Box::new(Utf16Decoder::with_policy(buffer, LittleEndian, policy)),
+        Encoding::Utf16Be => Box::new(Utf16Decoder::with_policy(buffer, BigEndian, policy)),
+        Encoding::Utf32Le => Box::new(Utf32Decoder::with_policy(buffer, LittleEndian, policy)),
+        Encoding::Utf32Be => Box::new(Utf32Decoder::with_policy(buffer, BigEndian, policy)),
+        Encoding::Latin1 => Box::new(Latin1Decoder::new(buffer)),
+        Encoding::Windows1252 => Box::new(Windows1252Decoder::new(buffer)),
     }
 }
 
@@ -116,30 +273,71 @@ mod lib {
     use std::fs::File;
     use std::io::BufReader;
 
+    use crate::common::CharSource;
     use crate::{default_decoder, new_decoder, Encoding};
 
     fn fuzz_file() -> File {
         File::open("fixtures/fuzz.txt").unwrap()
     }
 
+    fn count_chars(source: &mut dyn CharSource) -> usize {
+        let mut count = 0;
+        while let Some(Ok(_)) = source.next_char() {
+            count += 1;
+        }
+        count
+    }
+
     #[test]
     fn should_create_a_default_decoder() {
         let mut reader = BufReader::new(fuzz_file());
-        let decoder = default_decoder(&mut reader);
-        assert!(decoder.count() > 0)
+        let mut decoder = default_decoder(&mut reader);
+        assert!(count_chars(&mut decoder) > 0)
     }
 
     #[test]
     fn should_create_a_new_ascii_decoder() {
         let mut reader = BufReader::new(fuzz_file());
-        let decoder = new_decoder(&mut reader, Encoding::Ascii);
-        assert!(decoder.count() > 0)
+        let mut decoder = new_decoder(&mut reader, Encoding::Ascii);
+        assert!(count_chars(&mut decoder) > 0)
     }
 
     #[test]
     fn should_create_a_new_utf8_decoder() {
         let mut reader = BufReader::new(fuzz_file());
-        let decoder = new_decoder(&mut reader, Encoding::Utf8);
-        assert!(decoder.count() > 0)
+        let mut decoder = new_decoder(&mut reader, Encoding::Utf8);
+        assert!(count_chars(&mut decoder) > 0)
+    }
+
+    #[test]
+    fn should_create_new_single_byte_decoders() {
+        let buffer: &[u8] = &[0x61, 0x80];
+        for (encoding, expected) in [
+            (Encoding::Latin1, '\u{80}'),
+            (Encoding::Windows1252, '\u{20ac}'),
+        ] {
+            let mut reader = BufReader::new(buffer);
+            let mut decoder = new_decoder(&mut reader, encoding);
+            assert_eq!(decoder.next_char().unwrap().unwrap().ch, 'a');
+            assert_eq!(decoder.next_char().unwrap().unwrap().ch, expected);
+            assert!(decoder.next_char().is_none())
+        }
+    }
+
+    #[test]
+    fn should_create_new_utf16_utf32_and_auto_decoders() {
+        let buffers: [(&[u8], Encoding); 5] = [
+            (&[0x61, 0x00], Encoding::Utf16Le),
+            (&[0x00, 0x61], Encoding::Utf16Be),
+            (&[0x61, 0x00, 0x00, 0x00], Encoding::Utf32Le),
+            (&[0x00, 0x00, 0x00, 0x61], Encoding::Utf32Be),
+            (&[0xfe, 0xff, 0x00, 0x61], Encoding::Auto),
+        ];
+        for (buffer, encoding) in buffers {
+            let mut reader = BufReader::new(buffer);
+            let mut decoder = new_decoder(&mut reader, encoding);
+            assert_eq!(decoder.next_char().unwrap().unwrap().ch, 'a');
+            assert!(decoder.next_char().is_none())
+        }
     }
 }
@@ -0,0 +1,88 @@
+//! A memory-mapped input source, available when the `mmap` feature is enabled.
+//!
+//! Mapping a file into memory means that the decoders can read directly from the mapped pages
+//! (a `&[u8]` is itself a [std::io::BufRead]), rather than having each chunk of the file copied
+//! into an intermediate buffer first. The operating system takes care of paging the file in as
+//! decoding progresses, which makes this a good fit for very large inputs.
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+/// A read-only memory mapping of a file
+pub struct MappedFile {
+    /// The underlying mapping
+    map: Mmap,
+}
+
+impl MappedFile {
+    /// Map the file at a given path into memory.
+    ///
+    /// The file must not be modified (or truncated) by this, or any other, process whilst the
+    /// mapping is alive, otherwise the contents seen by a decoder are undefined
+    pub fn open<PathLike: AsRef<Path>>(path: PathLike) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the file must not be truncated or modified whilst it is mapped, as documented
+        // above, since the mapped bytes would otherwise change underneath any outstanding borrows
+        let map = unsafe { Mmap::map(&file)? };
+        #[cfg(unix)]
+        {
+            // purely a hint to the kernel, so failure is of no consequence
+            let _ = map.advise(memmap2::Advice::Sequential);
+        }
+        Ok(MappedFile { map })
+    }
+
+    /// The length of the mapped file in bytes
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Checks whether the mapped file is empty
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Get hold of a reader over the mapped bytes, which can then be handed to any of the decoders
+    pub fn reader(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::common::CharSource;
+    use crate::mmap::MappedFile;
+    use crate::utf8::Utf8Decoder;
+
+    #[test]
+    fn should_decode_directly_from_a_mapping() {
+        let expected = fs::read_to_string("fixtures/fuzz.txt").unwrap();
+        let mapped = MappedFile::open("fixtures/fuzz.txt").unwrap();
+        assert_eq!(mapped.len(), expected.len());
+        let mut reader = mapped.reader();
+        let decoder = Utf8Decoder::new(&mut reader);
+        assert_eq!(decoder.collect::<String>(), expected);
+    }
+
+    #[test]
+    fn should_report_offsets_within_the_mapping() {
+        let mapped = MappedFile::open("fixtures/fuzz.txt").unwrap();
+        let mut reader = mapped.reader();
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut last = None;
+        while let Some(Ok(c)) = decoder.next_char() {
+            last = Some(c);
+        }
+        let last = last.unwrap();
+        assert_eq!(last.offset + last.ch.len_utf8(), mapped.len());
+    }
+
+    #[test]
+    fn should_fail_to_map_missing_files() {
+        assert!(MappedFile::open("fixtures/missing.json").is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! Fast path support for runs of ASCII input, which make up the bulk of most JSON documents.
+//!
+//! Rather than classifying each byte individually, the front of the current chunk is checked for
+//! high bits several bytes at a time, and characters are then served straight from the stream for
+//! the length of the run without any further checks.  With the
+//! `portable-simd` feature enabled (nightly only), the check is carried out 16 bytes at a time
+//! using [std::simd], otherwise a scalar word-at-a-time check over 8 bytes is used.
+use std::io::BufRead;
+
+use crate::common::DecoderResult;
+use crate::stream::ByteStream;
+
+/// Mask for the high bit of each byte within a word
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+/// Number of bytes checked at once by the scalar implementation
+const WORD_LEN: usize = 8;
+
+/// Number of bytes checked at once by the SIMD implementation
+#[cfg(feature = "portable-simd")]
+const LANES: usize = 16;
+
+/// Calculate the length of the run of ASCII bytes at the front of a slice
+#[inline]
+pub(crate) fn ascii_prefix_len(bytes: &[u8]) -> usize {
+    #[cfg(feature = "portable-simd")]
+    {
+        simd_ascii_prefix_len(bytes)
+    }
+    #[cfg(not(feature = "portable-simd"))]
+    {
+        scalar_ascii_prefix_len(bytes)
+    }
+}
+
+/// Word-at-a-time implementation of [ascii_prefix_len]
+#[inline]
+fn scalar_ascii_prefix_len(bytes: &[u8]) -> usize {
+    let mut words = bytes.chunks_exact(WORD_LEN);
+    let mut len = 0;
+    for word in &mut words {
+        let high = u64::from_le_bytes(word.try_into().unwrap()) & HIGH_BITS;
+        if high != 0 {
+            return len + (high.trailing_zeros() / 8) as usize;
+        }
+        len += WORD_LEN;
+    }
+    len + words
+        .remainder()
+        .iter()
+        .take_while(|b| b.is_ascii())
+        .count()
+}
+
+/// SIMD implementation of [ascii_prefix_len]
+#[cfg(feature = "portable-simd")]
+#[inline]
+fn simd_ascii_prefix_len(bytes: &[u8]) -> usize {
+    use std::simd::cmp::SimdPartialOrd;
+    use std::simd::u8x16;
+
+    let mut lanes = bytes.chunks_exact(LANES);
+    let mut len = 0;
+    for chunk in &mut lanes {
+        let high = u8x16::from_slice(chunk)
+            .simd_ge(u8x16::splat(0x80))
+            .to_bitmask();
+        if high != 0 {
+            return len + high.trailing_zeros() as usize;
+        }
+        len += LANES;
+    }
+    len + scalar_ascii_prefix_len(lanes.remainder())
+}
+
+/// Tracks a run of ASCII bytes at the front of a [ByteStream]. The bytes are served straight from
+/// the stream's buffer, and only consumed once the whole run has been served, so nothing is copied
+pub(crate) struct AsciiRun {
+    /// The number of bytes at the front of the stream which are known to be ASCII
+    len: usize,
+    /// The number of bytes within the run which have already been served
+    pos: usize,
+}
+
+impl AsciiRun {
+    /// Create a new, empty run
+    pub fn new() -> Self {
+        AsciiRun { len: 0, pos: 0 }
+    }
+
+    /// The number of bytes which have been served but not yet consumed from the stream
+    #[inline]
+    pub fn served(&self) -> usize {
+        self.pos
+    }
+
+    /// Serve the next ASCII character, scanning the front of the stream for a new run if needed.
+    /// Returns [None] if the next byte in the stream isn't ASCII, or the end of the input has been
+    /// reached, in which case everything served so far has been consumed from the stream
+    #[inline]
+    pub fn next<B: BufRead>(
+        &mut self,
+        input: &mut ByteStream<'_, B>,
+    ) -> DecoderResult<Option<char>> {
+        if self.pos < self.len {
+            if let Some(&byte) = input.buffered().get(self.pos) {
+                self.pos += 1;
+                return Ok(Some(char::from(byte)));
+            }
+        }
+        self.refill(input)
+    }
+
+    /// Consume the current run from the stream and start a new one, serving its first character
+    #[inline(never)]
+    fn refill<B: BufRead>(&mut self, input: &mut ByteStream<'_, B>) -> DecoderResult<Option<char>> {
+        input.consume(self.pos);
+        self.pos = 0;
+        let bytes = input.peek(1)?;
+        self.len = ascii_prefix_len(bytes);
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.pos = 1;
+        Ok(Some(char::from(bytes[0])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::scan::{ascii_prefix_len, scalar_ascii_prefix_len, AsciiRun};
+    use crate::stream::ByteStream;
+
+    #[test]
+    fn should_find_the_end_of_ascii_prefixes() {
+        let mut bytes = vec![b'a'; 40];
+        for i in 0..40 {
+            bytes[i] = 0xc3;
+            assert_eq!(ascii_prefix_len(&bytes), i);
+            assert_eq!(scalar_ascii_prefix_len(&bytes), i);
+            assert_eq!(ascii_prefix_len(&bytes[..i]), i);
+            bytes[i] = b'a';
+        }
+        assert_eq!(ascii_prefix_len(&bytes), 40);
+        assert_eq!(ascii_prefix_len(&[]), 0);
+    }
+
+    #[test]
+    fn should_serve_runs_from_the_stream() {
+        let bytes: &[u8] = b"abc\xc3\xa9d";
+        let mut reader = BufReader::with_capacity(2, bytes);
+        let mut input = ByteStream::new(&mut reader);
+        let mut run = AsciiRun::new();
+        assert_eq!(run.next(&mut input).unwrap(), Some('a'));
+        assert_eq!(run.served(), 1);
+        assert_eq!(run.next(&mut input).unwrap(), Some('b'));
+        assert_eq!(run.next(&mut input).unwrap(), Some('c'));
+        assert_eq!(run.next(&mut input).unwrap(), None);
+        assert_eq!(input.offset(), 3);
+    }
+}
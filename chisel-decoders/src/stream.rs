@@ -0,0 +1,136 @@
+//! An internal, bounded view over a [BufRead] instance which is shared by the decoder
+//! implementations.
+//!
+//! Bytes are pulled from the underlying reader one chunk at a time through
+//! [BufRead::fill_buf]/[BufRead::consume], so the amount of memory used by a decoder stays constant
+//! regardless of how large the input is.  The only extra storage is a tiny carry buffer used to
+//! stitch together multi-byte sequences which happen to straddle a chunk boundary.
+use std::io::{BufRead, ErrorKind};
+
+use crate::common::*;
+use crate::decoder_error;
+
+/// The maximum number of bytes that ever need to be stitched together across chunk boundaries
+pub(crate) const MAX_SEQUENCE_LEN: usize = 4;
+
+/// Pull the next chunk from a [BufRead], retrying if the read was interrupted
+#[inline]
+fn fill<B: BufRead>(input: &mut B) -> DecoderResult<&[u8]> {
+    loop {
+        match input.fill_buf() {
+            Ok(_) => break,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => {
+                return Err(decoder_error!(
+                    DecoderErrorCode::StreamFailure,
+                    "failed to read input"
+                ))
+            }
+        }
+    }
+    input
+        .fill_buf()
+        .map_err(|_| decoder_error!(DecoderErrorCode::StreamFailure, "failed to read input"))
+}
+
+/// A chunked byte stream wrapped around a [BufRead] instance
+pub(crate) struct ByteStream<'a, B: BufRead> {
+    /// The underlying input
+    input: &'a mut B,
+    /// Carry buffer for sequences split across chunks
+    carry: [u8; MAX_SEQUENCE_LEN],
+    /// The number of valid bytes currently held in the carry buffer
+    carry_len: usize,
+    /// The number of bytes consumed so far
+    offset: usize,
+}
+
+impl<'a, B: BufRead> ByteStream<'a, B> {
+    /// Create a new stream around a given [BufRead]
+    pub fn new(input: &'a mut B) -> Self {
+        ByteStream {
+            input,
+            carry: [0; MAX_SEQUENCE_LEN],
+            carry_len: 0,
+            offset: 0,
+        }
+    }
+
+    /// The offset of the next unconsumed byte within the input
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Return a view of the next available bytes, which will contain at least `n` bytes unless the
+    /// end of the input is reached first. An empty slice signals the end of the input. `n` must
+    /// not exceed [MAX_SEQUENCE_LEN].
+    #[inline]
+    pub fn peek(&mut self, n: usize) -> DecoderResult<&[u8]> {
+        debug_assert!(n <= MAX_SEQUENCE_LEN);
+        if self.carry_len == 0 {
+            let available = fill(self.input)?.len();
+            if available >= n || available == 0 {
+                return fill(self.input);
+            }
+        }
+
+        // stitch together a contiguous run of bytes from successive chunks
+        while self.carry_len < n {
+            let chunk = fill(self.input)?;
+            if chunk.is_empty() {
+                break;
+            }
+            let take = chunk.len().min(n - self.carry_len);
+            self.carry[self.carry_len..self.carry_len + take].copy_from_slice(&chunk[..take]);
+            self.input.consume(take);
+            self.carry_len += take;
+        }
+        Ok(&self.carry[..self.carry_len])
+    }
+
+    /// Return a view of the bytes at the front of the stream. So long as some of the bytes returned
+    /// by the last call to [ByteStream::peek] haven't been consumed yet, this doesn't go back to the
+    /// underlying reader for more input, which makes it cheap enough to call once per byte
+    #[inline]
+    pub fn buffered(&mut self) -> &[u8] {
+        if self.carry_len > 0 {
+            &self.carry[..self.carry_len]
+        } else {
+            self.input.fill_buf().unwrap_or(&[])
+        }
+    }
+
+    /// Mark `n` bytes (previously returned by [ByteStream::peek]) as consumed
+    #[inline]
+    pub fn consume(&mut self, n: usize) {
+        self.offset += n;
+        if self.carry_len > 0 {
+            self.carry.copy_within(n..self.carry_len, 0);
+            self.carry_len -= n;
+        } else {
+            self.input.consume(n);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::stream::ByteStream;
+
+    #[test]
+    fn should_stitch_sequences_across_chunks() {
+        let bytes: &[u8] = &[0x01, 0x02, 0x03, 0x04, 0x05];
+        let mut reader = BufReader::with_capacity(1, bytes);
+        let mut stream = ByteStream::new(&mut reader);
+        assert_eq!(stream.peek(3).unwrap(), &[0x01, 0x02, 0x03]);
+        stream.consume(1);
+        assert_eq!(stream.peek(1).unwrap(), &[0x02, 0x03]);
+        stream.consume(2);
+        assert_eq!(stream.peek(4).unwrap(), &[0x04, 0x05]);
+        stream.consume(2);
+        assert!(stream.peek(1).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,282 @@
+#![allow(dead_code)]
+//! A character-oriented decoder implementation that will take an underlying [std::u8] (byte) source
+//! and produce a stream of decoded Unicode (UTF-16) characters, in either byte order
+use std::io::BufRead;
+
+use crate::common::*;
+use crate::stream::ByteStream;
+use crate::{decoder_error, end_of_input, invalid_byte_sequence, Encoding};
+
+/// Lower bound for a high (leading) surrogate
+const HIGH_SURROGATE_LOW_BOUND: u16 = 0xd800;
+/// Upper bound for a high (leading) surrogate
+const HIGH_SURROGATE_HIGH_BOUND: u16 = 0xdbff;
+/// Lower bound for a low (trailing) surrogate
+const LOW_SURROGATE_LOW_BOUND: u16 = 0xdc00;
+/// Upper bound for a low (trailing) surrogate
+const LOW_SURROGATE_HIGH_BOUND: u16 = 0xdfff;
+/// Mask for extracting the 10 payload bits from a surrogate
+const SURROGATE_MASK: u32 = 0b0011_1111_1111;
+/// Offset applied to values encoded as a surrogate pair
+const SUPPLEMENTARY_OFFSET: u32 = 0x10000;
+
+/// Convenience macro for checking whether a code unit is a high surrogate
+macro_rules! high_surrogate {
+    ($unit : expr) => {
+        (HIGH_SURROGATE_LOW_BOUND..=HIGH_SURROGATE_HIGH_BOUND).contains(&$unit)
+    };
+}
+
+/// Convenience macro for checking whether a code unit is a low surrogate
+macro_rules! low_surrogate {
+    ($unit : expr) => {
+        (LOW_SURROGATE_LOW_BOUND..=LOW_SURROGATE_HIGH_BOUND).contains(&$unit)
+    };
+}
+
+/// Assemble a code unit from a pair of bytes using a given [ByteOrder]
+#[inline]
+fn code_unit(bytes: &[u8], order: ByteOrder) -> u16 {
+    match order {
+        ByteOrder::LittleEndian => u16::from_le_bytes([bytes[0], bytes[1]]),
+        ByteOrder::BigEndian => u16::from_be_bytes([bytes[0], bytes[1]]),
+    }
+}
+
+/// A UTF-16 decoder, which takes a ref to a [BufRead] instance.
+pub struct Utf16Decoder<'a, B: BufRead> {
+    /// The input stream
+    input: ByteStream<'a, B>,
+    /// How malformed input should be dealt with
+    recovery: Recovery,
+    /// The byte order of the input
+    order: ByteOrder,
+}
+
+impl<'a, Buffer: BufRead> Utf16Decoder<'a, Buffer> {
+    /// Create a new decoder around a [BufRead] instance, using a given [ByteOrder]. Input is read
+    /// in chunks as decoding progresses
+    pub fn new(r: &'a mut Buffer, order: ByteOrder) -> Self {
+        Self::with_policy(r, order, DecodePolicy::Strict)
+    }
+
+    /// Create a new decoder around a [BufRead] instance, using a given [ByteOrder], which deals
+    /// with malformed input according to a given [DecodePolicy]
+    pub fn with_policy(r: &'a mut Buffer, order: ByteOrder, policy: DecodePolicy) -> Self {
+        Self::from_stream(ByteStream::new(r), order, policy)
+    }
+
+    /// Create a new decoder around an existing [ByteStream], which may already be part way through
+    /// the input
+    pub(crate) fn from_stream(
+        input: ByteStream<'a, Buffer>,
+        order: ByteOrder,
+        policy: DecodePolicy,
+    ) -> Self {
+        Utf16Decoder {
+            input,
+            recovery: Recovery::new(policy),
+            order,
+        }
+    }
+
+    /// Attempt to decode the next character in the underlying stream. Surrogate pairs are
+    /// combined into a single character, whereas an unpaired surrogate results in an error
+    fn decode_next(&mut self) -> DecoderResult<char> {
+        let bytes = self.input.peek(2)?;
+        if bytes.is_empty() {
+            return end_of_input!();
+        }
+        if bytes.len() < 2 {
+            let len = bytes.len();
+            self.input.consume(len);
+            return invalid_byte_sequence!();
+        }
+
+        let unit = code_unit(bytes, self.order);
+        if low_surrogate!(unit) {
+            self.input.consume(2);
+            return self.unpaired();
+        }
+        if !high_surrogate!(unit) {
+            self.input.consume(2);
+            return unsafe { Ok(char::from_u32_unchecked(unit as u32)) };
+        }
+
+        let bytes = self.input.peek(4)?;
+        if bytes.len() < 4 {
+            // a dangling high surrogate followed by a truncated code unit
+            let len = bytes.len();
+            self.input.consume(len);
+            return if len == 2 {
+                self.unpaired()
+            } else {
+                invalid_byte_sequence!()
+            };
+        }
+
+        let trailing = code_unit(&bytes[2..], self.order);
+        if !low_surrogate!(trailing) {
+            // leave the following code unit in place so that it can be decoded in its own right
+            self.input.consume(2);
+            return self.unpaired();
+        }
+        self.input.consume(4);
+        let value = SUPPLEMENTARY_OFFSET
+            + (((unit as u32 & SURROGATE_MASK) << 10) | (trailing as u32 & SURROGATE_MASK));
+        unsafe { Ok(char::from_u32_unchecked(value)) }
+    }
+
+    /// Report an unpaired surrogate
+    #[inline]
+    fn unpaired(&self) -> DecoderResult<char> {
+        Err(decoder_error!(
+            DecoderErrorCode::UnpairedSurrogate,
+            "unpaired surrogate detected"
+        ))
+    }
+}
+
+impl<'a, B: BufRead> CharSource for Utf16Decoder<'a, B> {
+    /// Decode the next character from the underlying stream, dealing with any malformed input
+    /// according to the current [DecodePolicy]
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        loop {
+            let offset = self.input.offset();
+            match self.decode_next() {
+                Ok(ch) => {
+                    let len = self.input.offset() - offset;
+                    return Some(Ok(CharWithOffset { ch, offset, len }));
+                }
+                Err(err) if err.code == DecoderErrorCode::EndOfInput => return None,
+                Err(err) => {
+                    let len = self.input.offset() - offset;
+                    if let Some(result) = self.recovery.recover(err, offset, len) {
+                        return Some(result);
+                    }
+                }
+            }
+        }
+    }
+
+    fn replacements(&self) -> usize {
+        self.recovery.replacements()
+    }
+
+    fn encoding(&self) -> Option<Encoding> {
+        Some(match self.order {
+            ByteOrder::LittleEndian => Encoding::Utf16Le,
+            ByteOrder::BigEndian => Encoding::Utf16Be,
+        })
+    }
+}
+
+impl<'a, B: BufRead> Iterator for Utf16Decoder<'a, B> {
+    type Item = char;
+    /// Decode the next character from the underlying stream
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_char() {
+            Some(Ok(c)) => Some(c.ch),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::BufReader;
+
+    use crate::common::{ByteOrder, CharSource, DecoderErrorCode};
+    use crate::utf16::Utf16Decoder;
+
+    fn encode(s: &str, order: ByteOrder) -> Vec<u8> {
+        s.encode_utf16()
+            .flat_map(|u| match order {
+                ByteOrder::LittleEndian => u.to_le_bytes(),
+                ByteOrder::BigEndian => u.to_be_bytes(),
+            })
+            .collect()
+    }
+
+    fn units(units: &[u16], order: ByteOrder) -> Vec<u8> {
+        units
+            .iter()
+            .flat_map(|u| match order {
+                ByteOrder::LittleEndian => u.to_le_bytes(),
+                ByteOrder::BigEndian => u.to_be_bytes(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_decode_in_both_byte_orders() {
+        let expected = fs::read_to_string("fixtures/fuzz.txt").unwrap();
+        for order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+            let bytes = encode(&expected, order);
+            for capacity in [1, 3, 8192] {
+                let mut reader = BufReader::with_capacity(capacity, bytes.as_slice());
+                let decoder = Utf16Decoder::new(&mut reader, order);
+                assert_eq!(decoder.collect::<String>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn should_combine_surrogate_pairs() {
+        let bytes = units(&[0xd83d, 0xde00, 0x0041], ByteOrder::BigEndian);
+        let mut reader = BufReader::new(bytes.as_slice());
+        let mut decoder = Utf16Decoder::new(&mut reader, ByteOrder::BigEndian);
+        let first = decoder.next_char().unwrap().unwrap();
+        assert_eq!((first.ch, first.offset, first.len), ('\u{1f600}', 0, 4));
+        let second = decoder.next_char().unwrap().unwrap();
+        assert_eq!((second.ch, second.offset, second.len), ('A', 4, 2));
+        assert!(decoder.next_char().is_none());
+    }
+
+    #[test]
+    fn should_report_unpaired_surrogates() {
+        let cases: [&[u16]; 4] = [
+            &[0xdc00, 0x0041],
+            &[0xd800, 0x0041],
+            &[0xd800, 0xd800, 0xdc00],
+            &[0x0041, 0xd800],
+        ];
+        for case in cases {
+            let bytes = units(case, ByteOrder::LittleEndian);
+            let mut reader = BufReader::new(bytes.as_slice());
+            let mut decoder = Utf16Decoder::new(&mut reader, ByteOrder::LittleEndian);
+            let mut failed = false;
+            while let Some(result) = decoder.next_char() {
+                if let Err(err) = result {
+                    assert_eq!(err.code, DecoderErrorCode::UnpairedSurrogate);
+                    failed = true;
+                }
+            }
+            assert!(failed, "input: {:x?}", case);
+        }
+    }
+
+    #[test]
+    fn should_resume_after_an_unpaired_surrogate() {
+        let bytes = units(&[0xd800, 0x0041], ByteOrder::LittleEndian);
+        let mut reader = BufReader::new(bytes.as_slice());
+        let mut decoder = Utf16Decoder::new(&mut reader, ByteOrder::LittleEndian);
+        assert!(decoder.next_char().unwrap().is_err());
+        let next = decoder.next_char().unwrap().unwrap();
+        assert_eq!((next.ch, next.offset), ('A', 2));
+    }
+
+    #[test]
+    fn should_error_on_truncated_input() {
+        let bytes: &[u8] = &[0x41, 0x00, 0x42];
+        let mut reader = BufReader::new(bytes);
+        let mut decoder = Utf16Decoder::new(&mut reader, ByteOrder::LittleEndian);
+        assert_eq!(decoder.next_char().unwrap().unwrap().ch, 'A');
+        assert_eq!(
+            decoder.next_char().unwrap().unwrap_err().code,
+            DecoderErrorCode::InvalidByteSequence
+        );
+        assert!(decoder.next_char().is_none());
+    }
+}
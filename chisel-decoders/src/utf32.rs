@@ -0,0 +1,196 @@
+#![allow(dead_code)]
+//! A character-oriented decoder implementation that will take an underlying [std::u8] (byte) source
+//! and produce a stream of decoded Unicode (UTF-32) characters, in either byte order
+use std::io::BufRead;
+
+use crate::common::*;
+use crate::stream::ByteStream;
+use crate::{decoder_error, end_of_input, invalid_byte_sequence, Encoding};
+
+/// Lower bound for the surrogate range, which isn't valid within UTF-32
+const SURROGATE_LOW_BOUND: u32 = 0xd800;
+/// Upper bound for the surrogate range, which isn't valid within UTF-32
+const SURROGATE_HIGH_BOUND: u32 = 0xdfff;
+
+/// Assemble a code unit from four bytes using a given [ByteOrder]
+#[inline]
+fn code_unit(bytes: &[u8], order: ByteOrder) -> u32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    match order {
+        ByteOrder::LittleEndian => u32::from_le_bytes(bytes),
+        ByteOrder::BigEndian => u32::from_be_bytes(bytes),
+    }
+}
+
+/// A UTF-32 decoder, which takes a ref to a [BufRead] instance.
+pub struct Utf32Decoder<'a, B: BufRead> {
+    /// The input stream
+    input: ByteStream<'a, B>,
+    /// How malformed input should be dealt with
+    recovery: Recovery,
+    /// The byte order of the input
+    order: ByteOrder,
+}
+
+impl<'a, Buffer: BufRead> Utf32Decoder<'a, Buffer> {
+    /// Create a new decoder around a [BufRead] instance, using a given [ByteOrder]. Input is read
+    /// in chunks as decoding progresses
+    pub fn new(r: &'a mut Buffer, order: ByteOrder) -> Self {
+        Self::with_policy(r, order, DecodePolicy::Strict)
+    }
+
+    /// Create a new decoder around a [BufRead] instance, using a given [ByteOrder], which deals
+    /// with malformed input according to a given [DecodePolicy]
+    pub fn with_policy(r: &'a mut Buffer, order: ByteOrder, policy: DecodePolicy) -> Self {
+        Self::from_stream(ByteStream::new(r), order, policy)
+    }
+
+    /// Create a new decoder around an existing [ByteStream], which may already be part way through
+    /// the input
+    pub(crate) fn from_stream(
+        input: ByteStream<'a, Buffer>,
+        order: ByteOrder,
+        policy: DecodePolicy,
+    ) -> Self {
+        Utf32Decoder {
+            input,
+            recovery: Recovery::new(policy),
+            order,
+        }
+    }
+
+    /// Attempt to decode the next character in the underlying stream. Code units which fall
+    /// within the surrogate range or above 0x10ffff result in an error
+    fn decode_next(&mut self) -> DecoderResult<char> {
+        let bytes = self.input.peek(4)?;
+        if bytes.is_empty() {
+            return end_of_input!();
+        }
+        if bytes.len() < 4 {
+            let len = bytes.len();
+            self.input.consume(len);
+            return invalid_byte_sequence!();
+        }
+
+        let value = code_unit(bytes, self.order);
+        self.input.consume(4);
+        if (SURROGATE_LOW_BOUND..=SURROGATE_HIGH_BOUND).contains(&value) {
+            return Err(decoder_error!(
+                DecoderErrorCode::UnpairedSurrogate,
+                "surrogate code points are not valid within UTF-32"
+            ));
+        }
+        char::from_u32(value).ok_or_else(|| {
+            decoder_error!(DecoderErrorCode::OutOfRange, "value falls above 0x10ffff")
+        })
+    }
+}
+
+impl<'a, B: BufRead> CharSource for Utf32Decoder<'a, B> {
+    /// Decode the next character from the underlying stream, dealing with any malformed input
+    /// according to the current [DecodePolicy]
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        loop {
+            let offset = self.input.offset();
+            match self.decode_next() {
+                Ok(ch) => {
+                    let len = self.input.offset() - offset;
+                    return Some(Ok(CharWithOffset { ch, offset, len }));
+                }
+                Err(err) if err.code == DecoderErrorCode::EndOfInput => return None,
+                Err(err) => {
+                    let len = self.input.offset() - offset;
+                    if let Some(result) = self.recovery.recover(err, offset, len) {
+                        return Some(result);
+                    }
+                }
+            }
+        }
+    }
+
+    fn replacements(&self) -> usize {
+        self.recovery.replacements()
+    }
+
+    fn encoding(&self) -> Option<Encoding> {
+        Some(match self.order {
+            ByteOrder::LittleEndian => Encoding::Utf32Le,
+            ByteOrder::BigEndian => Encoding::Utf32Be,
+        })
+    }
+}
+
+impl<'a, B: BufRead> Iterator for Utf32Decoder<'a, B> {
+    type Item = char;
+    /// Decode the next character from the underlying stream
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_char() {
+            Some(Ok(c)) => Some(c.ch),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::BufReader;
+
+    use crate::common::{ByteOrder, CharSource, DecoderErrorCode};
+    use crate::utf32::Utf32Decoder;
+
+    fn units(units: &[u32], order: ByteOrder) -> Vec<u8> {
+        units
+            .iter()
+            .flat_map(|u| match order {
+                ByteOrder::LittleEndian => u.to_le_bytes(),
+                ByteOrder::BigEndian => u.to_be_bytes(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_decode_in_both_byte_orders() {
+        let expected = fs::read_to_string("fixtures/fuzz.txt").unwrap();
+        let values: Vec<u32> = expected.chars().map(|c| c as u32).collect();
+        for order in [ByteOrder::LittleEndian, ByteOrder::BigEndian] {
+            let bytes = units(&values, order);
+            for capacity in [1, 3, 8192] {
+                let mut reader = BufReader::with_capacity(capacity, bytes.as_slice());
+                let decoder = Utf32Decoder::new(&mut reader, order);
+                assert_eq!(decoder.collect::<String>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_code_points() {
+        let bytes = units(&[0x41, 0xd800, 0x110000, 0x1f600], ByteOrder::BigEndian);
+        let mut reader = BufReader::new(bytes.as_slice());
+        let mut decoder = Utf32Decoder::new(&mut reader, ByteOrder::BigEndian);
+        assert_eq!(decoder.next_char().unwrap().unwrap().ch, 'A');
+        assert_eq!(
+            decoder.next_char().unwrap().unwrap_err().code,
+            DecoderErrorCode::UnpairedSurrogate
+        );
+        assert_eq!(
+            decoder.next_char().unwrap().unwrap_err().code,
+            DecoderErrorCode::OutOfRange
+        );
+        let last = decoder.next_char().unwrap().unwrap();
+        assert_eq!((last.ch, last.offset), ('\u{1f600}', 12));
+    }
+
+    #[test]
+    fn should_error_on_truncated_input() {
+        let bytes: &[u8] = &[0x41, 0x00, 0x00, 0x00, 0x42, 0x00];
+        let mut reader = BufReader::new(bytes);
+        let mut decoder = Utf32Decoder::new(&mut reader, ByteOrder::LittleEndian);
+        assert_eq!(decoder.next_char().unwrap().unwrap().ch, 'A');
+        assert_eq!(
+            decoder.next_char().unwrap().unwrap_err().code,
+            DecoderErrorCode::InvalidByteSequence
+        );
+        assert!(decoder.next_char().is_none());
+    }
+}
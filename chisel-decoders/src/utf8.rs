@@ -1,13 +1,13 @@
 #![allow(dead_code)]
-#![allow(clippy::transmute_int_to_char)]
 //! A character-oriented decoder implementation that will take an underlying [std::u8] (byte) source
 //! and produce a stream of decoded Unicode (UTF-8) characters
 use std::io::BufRead;
-use std::mem::transmute;
 
 use crate::common::*;
+use crate::scan::AsciiRun;
+use crate::stream::ByteStream;
 use crate::utf8::SequenceType::Unrecognised;
-use crate::{decoder_error, invalid_byte_sequence};
+use crate::{decoder_error, end_of_input, invalid_byte_sequence, Encoding};
 
 enum SequenceType {
     Single,
@@ -28,14 +28,20 @@ const QUAD_BYTE_MASK: u32 = 0b0000_0111;
 /// Mask for extracting 6 bits from following byte UTF-8 ssequences
 const FOLLOWING_BYTE_MASK: u32 = 0b0011_1111;
 
-/// Low bound for checking excluded triples
-const TRIPLE_EXCLUDED_LOW_BOUND: u32 = 0xd800;
+/// Lower bound for a continuation byte
+const CONTINUATION_LOW_BOUND: u8 = 0x80;
 
-/// High bound for checking excluded triples
-const TRIPLE_EXCLUDED_HIGH_BOUND: u32 = 0xdfff;
+/// Upper bound for a continuation byte
+const CONTINUATION_HIGH_BOUND: u8 = 0xbf;
 
-/// High bound for checking quads
-const QUAD_HIGH_BOUND: u32 = 0x10ffff;
+/// Lowest lead byte which doesn't result in an overlong pair
+const PAIR_LOW_BOUND: u8 = 0xc2;
+
+/// Highest lead byte which can produce a value within the bound 0x10ffff
+const QUAD_HIGH_BOUND: u8 = 0xf4;
+
+/// The UTF-8 encoded byte order mark
+const BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
 
 /// Convenience macro for some bit twiddlin'
 macro_rules! single_byte_sequence {
@@ -106,115 +112,218 @@ fn sequence_type(b: u8) -> SequenceType {
     Unrecognised
 }
 
+/// Determine the valid range for the *first* continuation byte following a given lead byte. As per
+/// table 3-7 of the Unicode standard, narrowing this range is what rules out overlong forms,
+/// surrogates and values above 0x10ffff
+#[inline]
+fn first_continuation_bounds(lead: u8) -> (u8, u8) {
+    match lead {
+        0xe0 => (0xa0, CONTINUATION_HIGH_BOUND),
+        0xed => (CONTINUATION_LOW_BOUND, 0x9f),
+        0xf0 => (0x90, CONTINUATION_HIGH_BOUND),
+        0xf4 => (CONTINUATION_LOW_BOUND, 0x8f),
+        _ => (CONTINUATION_LOW_BOUND, CONTINUATION_HIGH_BOUND),
+    }
+}
+
+/// Check that a multi-byte sequence of length `len` at the front of `bytes` is well-formed. On
+/// failure, the error code is returned along with the length of the *maximal subpart* of the
+/// sequence, i.e. the number of bytes which should be skipped before decoding can resume
+#[inline]
+fn validate_sequence(bytes: &[u8], len: usize) -> Result<(), (DecoderErrorCode, usize)> {
+    let lead = bytes[0];
+    for (i, b) in bytes.iter().enumerate().take(len).skip(1) {
+        let (low, high) = if i == 1 {
+            first_continuation_bounds(lead)
+        } else {
+            (CONTINUATION_LOW_BOUND, CONTINUATION_HIGH_BOUND)
+        };
+        if !(low..=high).contains(b) {
+            // a genuine continuation byte which falls outside the narrowed bounds indicates either
+            // an encoded surrogate or a value above 0x10ffff, anything else is just malformed
+            let out_of_range = i == 1
+                && (lead == 0xed || lead == QUAD_HIGH_BOUND)
+                && (CONTINUATION_LOW_BOUND..=CONTINUATION_HIGH_BOUND).contains(b);
+            return if out_of_range {
+                Err((DecoderErrorCode::OutOfRange, i))
+            } else {
+                Err((DecoderErrorCode::InvalidByteSequence, i))
+            };
+        }
+    }
+    if bytes.len() < len {
+        return Err((DecoderErrorCode::InvalidByteSequence, bytes.len()));
+    }
+    Ok(())
+}
+
 /// A UTF-8 decoder, which takes a ref to a [BufRead] instance.
 pub struct Utf8Decoder<'a, B: BufRead> {
     /// The input stream
-    input: &'a mut B,
-    /// Staging buffer
-    buffer: Vec<u8>,
-    init: bool,
-    index: usize,
+    input: ByteStream<'a, B>,
+    /// How malformed input should be dealt with
+    recovery: Recovery,
+    /// Run of ASCII bytes at the front of the input which can be served without any further checks
+    run: AsciiRun,
+    /// A failure to read the start of the input while looking for a byte order mark, which is
+    /// reported by the first call to [CharSource::next_char]
+    pending: Option<DecoderError>,
 }
 
 impl<'a, Buffer: BufRead> Utf8Decoder<'a, Buffer> {
-    /// Create a new decoder with a default buffer size
+    /// Create a new decoder around a [BufRead] instance. Input is read in chunks as decoding
+    /// progresses, and a byte order mark at the very start of the input is skipped
     pub fn new(r: &'a mut Buffer) -> Self {
+        Self::with_policy(r, DecodePolicy::Strict)
+    }
+
+    /// Create a new decoder around a [BufRead] instance, which deals with malformed input
+    /// according to a given [DecodePolicy]. A byte order mark at the very start of the input is
+    /// skipped
+    pub fn with_policy(r: &'a mut Buffer, policy: DecodePolicy) -> Self {
+        let mut input = ByteStream::new(r);
+        let pending = match input.peek(BOM.len()) {
+            Ok(bytes) => {
+                if bytes.starts_with(&BOM) {
+                    input.consume(BOM.len());
+                }
+                None
+            }
+            Err(err) => Some(err),
+        };
         Utf8Decoder {
-            input: r,
-            buffer: vec![],
-            init: false,
-            index: 0,
+            pending,
+            ..Self::from_stream(input, policy)
         }
     }
 
-    /// Initialise and read the input into an internal buffer for decoding
-    fn init(&mut self) -> DecoderResult<()> {
-        match self.input.read_to_end(&mut self.buffer) {
-            Ok(_) => {
-                self.init = true;
-                Ok(())
-            }
-            Err(_) => Err(decoder_error!(
-                DecoderErrorCode::StreamFailure,
-                "failed to read input"
-            )),
+    /// Create a new decoder around an existing [ByteStream], which may already be part way through
+    /// the input
+    pub(crate) fn from_stream(input: ByteStream<'a, Buffer>, policy: DecodePolicy) -> Self {
+        Utf8Decoder {
+            input,
+            recovery: Recovery::new(policy),
+            run: AsciiRun::new(),
+            pending: None,
         }
     }
 
     /// Attempt to decode the next character in the underlying stream. Assumes the maximum
-    /// number of unicode bytes is 4 *not* 6
+    /// number of unicode bytes is 4 *not* 6. Only well-formed sequences (as defined by the Unicode
+    /// standard) are accepted, so overlong forms, encoded surrogates, values above 0x10ffff and
+    /// truncated sequences all result in an error
     fn decode_next(&mut self) -> DecoderResult<char> {
-        if !self.init {
-            self.init()?;
+        if let Some(ch) = self.run.next(&mut self.input)? {
+            return Ok(ch);
         }
 
-        if self.index >= self.buffer.len() {
-            return Err(decoder_error!(
-                DecoderErrorCode::EndOfInput,
-                "end of input reached"
-            ));
-        }
+        let lead = match self.input.peek(1)?.first() {
+            Some(b) => *b,
+            None => return end_of_input!(),
+        };
 
-        match sequence_type(self.buffer[self.index]) {
+        let len = match sequence_type(lead) {
             SequenceType::Single => unsafe {
-                self.index += 1;
-                Ok(transmute(self.buffer[self.index - 1] as u32))
+                self.input.consume(1);
+                return Ok(char::from_u32_unchecked(lead as u32));
             },
-            SequenceType::Pair => unsafe {
-                self.index += 2;
-                Ok(transmute(decode_pair!(
-                    &self.buffer[self.index - 2..self.index]
-                )))
+            SequenceType::Pair if lead >= PAIR_LOW_BOUND => 2,
+            SequenceType::Triple => 3,
+            SequenceType::Quad if lead <= QUAD_HIGH_BOUND => 4,
+            SequenceType::Quad => return self.malformed(DecoderErrorCode::OutOfRange, 1),
+            _ => return self.malformed(DecoderErrorCode::InvalidByteSequence, 1),
+        };
+
+        let bytes = self.input.peek(len)?;
+        match validate_sequence(bytes, len) {
+            Ok(_) => unsafe {
+                let value = match len {
+                    2 => decode_pair!(bytes),
+                    3 => decode_triple!(bytes),
+                    _ => decode_quad!(bytes),
+                };
+                self.input.consume(len);
+                Ok(char::from_u32_unchecked(value))
             },
-            SequenceType::Triple => unsafe {
-                self.index += 3;
-                let value = decode_triple!(&self.buffer[self.index - 3..self.index]);
-                if (TRIPLE_EXCLUDED_LOW_BOUND..=TRIPLE_EXCLUDED_HIGH_BOUND).contains(&value) {
-                    Err(decoder_error!(
-                        DecoderErrorCode::OutOfRange,
-                        "value falls within forbidden range [0xd800, 0xdfff]"
-                    ))
-                } else {
-                    Ok(transmute(value))
+            Err((code, subpart)) => self.malformed(code, subpart),
+        }
+    }
+
+    /// The offset of the next character to be decoded, taking into account any ASCII which has been
+    /// served from the front of the input but not yet consumed
+    #[inline]
+    fn offset(&self) -> usize {
+        self.input.offset() + self.run.served()
+    }
+
+    /// Skip over the maximal subpart of a malformed sequence, and then report the failure
+    #[inline]
+    fn malformed(&mut self, code: DecoderErrorCode, subpart: usize) -> DecoderResult<char> {
+        self.input.consume(subpart);
+        match code {
+            DecoderErrorCode::OutOfRange => Err(decoder_error!(
+                code,
+                "value falls within the surrogate range or above 0x10ffff"
+            )),
+            _ => invalid_byte_sequence!(),
+        }
+    }
+}
+
+impl<'a, B: BufRead> CharSource for Utf8Decoder<'a, B> {
+    /// Decode the next character from the underlying stream, dealing with any malformed input
+    /// according to the current [DecodePolicy]
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        if let Some(err) = self.pending.take() {
+            return Some(Err(err));
+        }
+        loop {
+            let offset = self.offset();
+            match self.decode_next() {
+                Ok(ch) => {
+                    let len = self.offset() - offset;
+                    return Some(Ok(CharWithOffset { ch, offset, len }));
                 }
-            },
-            SequenceType::Quad => unsafe {
-                self.index += 4;
-                let value = decode_quad!(&self.buffer[self.index - 4..self.index]);
-                if value > QUAD_HIGH_BOUND {
-                    Err(decoder_error!(
-                        DecoderErrorCode::OutOfRange,
-                        "value falls outside maximum bound 0x10ffff"
-                    ))
-                } else {
-                    Ok(transmute(value))
+                Err(err) if err.code == DecoderErrorCode::EndOfInput => return None,
+                Err(err) => {
+                    let len = self.offset() - offset;
+                    if let Some(result) = self.recovery.recover(err, offset, len) {
+                        return Some(result);
+                    }
                 }
-            },
-            Unrecognised => {
-                invalid_byte_sequence!()
             }
         }
     }
+
+    fn replacements(&self) -> usize {
+        self.recovery.replacements()
+    }
+
+    fn encoding(&self) -> Option<Encoding> {
+        Some(Encoding::Utf8)
+    }
 }
 
 impl<'a, B: BufRead> Iterator for Utf8Decoder<'a, B> {
     type Item = char;
     /// Decode the next character from the underlying stream
     fn next(&mut self) -> Option<Self::Item> {
-        match self.decode_next() {
-            Ok(c) => Some(c),
-            Err(_) => None,
+        match self.next_char() {
+            Some(Ok(c)) => Some(c.ch),
+            _ => None,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
-    use std::io::BufReader;
+    use std::fs::{self, File};
+    use std::io::{self, BufReader, Read};
     use std::time::Instant;
 
-    use crate::utf8::Utf8Decoder;
+    use crate::common::{CharSource, DecodePolicy, DecoderErrorCode};
+    use crate::utf8::{Utf8Decoder, BOM};
+    use crate::Encoding;
 
     fn fuzz_file() -> File {
         File::open("fixtures/fuzz.txt").unwrap()
@@ -265,6 +374,284 @@ mod tests {
         assert_eq!(count, 567916);
     }
 
+    #[test]
+    fn should_decode_sequences_split_across_chunks() {
+        let expected = "aé€𝄞 mixed ünïcødé 🎉 text";
+        for capacity in 1..=5 {
+            let mut reader = BufReader::with_capacity(capacity, expected.as_bytes());
+            let decoder = Utf8Decoder::new(&mut reader);
+            assert_eq!(decoder.collect::<String>(), expected);
+        }
+    }
+
+    #[test]
+    fn should_skip_a_leading_bom() {
+        let source = "\u{feff}a\u{feff}";
+        for capacity in 1..=4 {
+            let mut reader = BufReader::with_capacity(capacity, source.as_bytes());
+            let mut decoder = Utf8Decoder::new(&mut reader);
+            assert_eq!(decoder.encoding(), Some(Encoding::Utf8));
+            let first = decoder.next_char().unwrap().unwrap();
+            assert_eq!((first.ch, first.offset), ('a', 3));
+            assert_eq!(decoder.collect::<String>(), "\u{feff}");
+        }
+    }
+
+    #[test]
+    fn should_report_a_failure_to_read_the_start_of_the_input() {
+        /// A reader which fails the first time it's read from, and then succeeds
+        struct FailsOnce<'a>(bool, &'a [u8]);
+        impl<'a> Read for FailsOnce<'a> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0 {
+                    return self.1.read(buf);
+                }
+                self.0 = true;
+                Err(io::Error::new(io::ErrorKind::Other, "failed"))
+            }
+        }
+        let mut reader = BufReader::new(FailsOnce(false, "\u{feff}a".as_bytes()));
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let err = decoder.next_char().unwrap().unwrap_err();
+        assert_eq!(err.code, DecoderErrorCode::StreamFailure);
+    }
+
+    #[test]
+    fn should_error_on_truncated_input() {
+        let buffer: &[u8] = &[0x61, 0xe2, 0x82];
+        let mut reader = BufReader::new(buffer);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        assert_eq!(decoder.decode_next().unwrap(), 'a');
+        assert_eq!(
+            decoder.decode_next().err().unwrap().code,
+            DecoderErrorCode::InvalidByteSequence
+        );
+    }
+
+    /// Strictly decode a byte slice using a given read capacity, returning everything decoded prior
+    /// to the first failure along with the failure itself and the offset at which decoding resumes
+    fn strict_decode(bytes: &[u8], capacity: usize) -> (String, Option<(DecoderErrorCode, usize)>) {
+        let mut reader = BufReader::with_capacity(capacity, bytes);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut decoded = String::new();
+        loop {
+            match decoder.decode_next() {
+                Ok(ch) => decoded.push(ch),
+                Err(err) if err.code == DecoderErrorCode::EndOfInput => return (decoded, None),
+                Err(err) => return (decoded, Some((err.code, decoder.offset()))),
+            }
+        }
+    }
+
+    /// Check the behaviour of the decoder against [std::str::from_utf8] for a given input, bearing
+    /// in mind that the decoder skips a leading BOM
+    fn check_against_std(input: &[u8], capacity: usize) {
+        let (decoded, failure) = strict_decode(input, capacity);
+        let skipped = if input.starts_with(&BOM) {
+            BOM.len()
+        } else {
+            0
+        };
+        let bytes = &input[skipped..];
+        match std::str::from_utf8(bytes) {
+            Ok(expected) => {
+                assert_eq!(decoded, expected, "input: {:x?}", bytes);
+                assert!(failure.is_none(), "input: {:x?}", bytes);
+            }
+            Err(err) => {
+                let valid = std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap();
+                assert_eq!(decoded, valid, "input: {:x?}", bytes);
+                let (code, resume) = failure.expect("a failure should have been reported");
+                assert!(
+                    code == DecoderErrorCode::InvalidByteSequence
+                        || code == DecoderErrorCode::OutOfRange
+                );
+                let subpart = err.error_len().unwrap_or(bytes.len() - err.valid_up_to());
+                assert_eq!(
+                    resume,
+                    skipped + err.valid_up_to() + subpart,
+                    "input: {:x?}",
+                    bytes
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_match_std_for_all_short_sequences() {
+        let interesting: [u8; 12] = [
+            0x00, 0x41, 0x7f, 0x80, 0x8f, 0x90, 0x9f, 0xa0, 0xbf, 0xc0, 0xf4, 0xff,
+        ];
+        for first in 0..=0xffu8 {
+            check_against_std(&[first], 8);
+            for second in 0..=0xffu8 {
+                check_against_std(&[first, second], 8);
+                for third in interesting {
+                    check_against_std(&[first, second, third], 8);
+                    if first >= 0xf0 {
+                        for fourth in interesting {
+                            check_against_std(&[first, second, third, fourth], 8);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_match_std_for_pseudo_random_inputs() {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..20_000 {
+            let mut bytes = vec![];
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            for i in 0..(state % 24) {
+                // bias the distribution towards bytes which may form multi-byte sequences
+                let byte = (state >> (i % 8 * 8)) as u8;
+                bytes.push(if i % 3 == 0 { byte | 0x80 } else { byte });
+            }
+            for capacity in [1, 3, 8] {
+                check_against_std(&bytes, capacity);
+            }
+        }
+    }
+
+    /// Decode a byte slice using a given [DecodePolicy], returning the decoded string and the
+    /// number of replacements made
+    fn lossy_decode(bytes: &[u8], capacity: usize, policy: DecodePolicy) -> (String, usize) {
+        let mut reader = BufReader::with_capacity(capacity, bytes);
+        let mut decoder = Utf8Decoder::with_policy(&mut reader, policy);
+        let decoded = (&mut decoder).collect::<String>();
+        (decoded, decoder.replacements())
+    }
+
+    #[test]
+    fn should_replace_maximal_subparts() {
+        // the example given alongside table 3-8 of the Unicode standard
+        let bytes: &[u8] = &[
+            0x61, 0xf1, 0x80, 0x80, 0xe1, 0x80, 0xc2, 0x62, 0x80, 0x63, 0x80, 0xbf, 0x64,
+        ];
+        let (decoded, replacements) = lossy_decode(bytes, 8, DecodePolicy::Replace);
+        assert_eq!(
+            decoded,
+            "a\u{fffd}\u{fffd}\u{fffd}b\u{fffd}c\u{fffd}\u{fffd}d"
+        );
+        assert_eq!(replacements, 6);
+    }
+
+    #[test]
+    fn should_replace_in_line_with_std() {
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        for _ in 0..20_000 {
+            let mut bytes = vec![];
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            for i in 0..(state % 24) {
+                let byte = (state >> (i % 8 * 8)) as u8;
+                bytes.push(if i % 3 == 0 { byte | 0x80 } else { byte });
+            }
+            for capacity in [1, 8] {
+                let (decoded, _) = lossy_decode(&bytes, capacity, DecodePolicy::Replace);
+                assert_eq!(
+                    decoded,
+                    String::from_utf8_lossy(&bytes),
+                    "input: {:x?}",
+                    bytes
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_skip_malformed_sequences() {
+        let bytes: &[u8] = &[0x61, 0xed, 0xa0, 0x80, 0x62, 0xe2, 0x82];
+        let (decoded, replacements) = lossy_decode(bytes, 8, DecodePolicy::Skip);
+        assert_eq!(decoded, "ab");
+        assert_eq!(replacements, 4);
+    }
+
+    #[test]
+    fn should_report_replacement_offsets() {
+        let bytes: &[u8] = &[0x61, 0xc0, 0x62, 0xe1, 0x80, 0x63];
+        let mut reader = BufReader::new(bytes);
+        let mut decoder = Utf8Decoder::with_policy(&mut reader, DecodePolicy::Replace);
+        let offsets: Vec<(char, usize, usize)> = std::iter::from_fn(|| decoder.next_char())
+            .map(|c| c.map(|c| (c.ch, c.offset, c.len)).unwrap())
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![
+                ('a', 0, 1),
+                ('\u{fffd}', 1, 1),
+                ('b', 2, 1),
+                ('\u{fffd}', 3, 2),
+                ('c', 5, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_byte_lengths() {
+        let text = fs::read_to_string("fixtures/fuzz.txt").unwrap();
+        let mut reader = BufReader::with_capacity(7, text.as_bytes());
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        for (offset, ch) in text.char_indices() {
+            let decoded = decoder.next_char().unwrap().unwrap();
+            assert_eq!(
+                (decoded.ch, decoded.offset, decoded.len),
+                (ch, offset, ch.len_utf8())
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_malformed_sequences() {
+        let cases: [(&[u8], DecoderErrorCode); 9] = [
+            (&[0xc0, 0xaf], DecoderErrorCode::InvalidByteSequence),
+            (&[0xe0, 0x80, 0xaf], DecoderErrorCode::InvalidByteSequence),
+            (
+                &[0xf0, 0x80, 0x80, 0xaf],
+                DecoderErrorCode::InvalidByteSequence,
+            ),
+            (&[0xed, 0xa0, 0x80], DecoderErrorCode::OutOfRange),
+            (&[0xf4, 0x90, 0x80, 0x80], DecoderErrorCode::OutOfRange),
+            (&[0xf5, 0x80, 0x80, 0x80], DecoderErrorCode::OutOfRange),
+            (&[0xc3, 0x28], DecoderErrorCode::InvalidByteSequence),
+            (&[0x80], DecoderErrorCode::InvalidByteSequence),
+            (&[0xf0, 0x9f, 0x98], DecoderErrorCode::InvalidByteSequence),
+        ];
+        for (bytes, code) in cases {
+            let (_, failure) = strict_decode(bytes, 8);
+            assert_eq!(failure.unwrap().0, code, "input: {:x?}", bytes);
+        }
+    }
+
+    #[test]
+    fn should_surface_errors_as_a_char_source() {
+        let buffer: &[u8] = "a€".as_bytes();
+        let mut bytes = buffer.to_vec();
+        bytes.push(0xff);
+        let mut reader = BufReader::new(bytes.as_slice());
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let first = decoder.next_char().unwrap().unwrap();
+        assert_eq!((first.ch, first.offset), ('a', 0));
+        let second = decoder.next_char().unwrap().unwrap();
+        assert_eq!((second.ch, second.offset, second.len), ('€', 1, 3));
+        assert_eq!(
+            decoder.next_char().unwrap().err().unwrap().code,
+            DecoderErrorCode::InvalidByteSequence
+        );
+    }
+
+    #[test]
+    fn should_stream_large_inputs() {
+        let mut reader = BufReader::new(io::repeat(b'x').take(16 * 1024 * 1024));
+        let decoder = Utf8Decoder::new(&mut reader);
+        assert_eq!(decoder.count(), 16 * 1024 * 1024);
+    }
+
     #[test]
     fn should_be_an_iterator() {
         let start = Instant::now();
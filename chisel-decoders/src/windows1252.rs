@@ -0,0 +1,115 @@
+#![allow(dead_code)]
+//! A character-oriented decoder implementation that will take an underlying [std::u8] (byte) source
+//! and produce a stream of decoded Windows-1252 (CP1252) characters
+use std::io::BufRead;
+
+use crate::common::*;
+use crate::stream::ByteStream;
+use crate::{end_of_input, Encoding};
+
+/// Mapping for the bytes 0x80 to 0x9f, which is where Windows-1252 differs from Latin-1.  As per
+/// the WHATWG encoding standard, the five bytes left undefined by Windows-1252 (0x81, 0x8d, 0x8f,
+/// 0x90 and 0x9d) map onto the C1 control with the same value
+const HIGH_CONTROL_MAPPING: [char; 32] = [
+    '\u{20ac}', '\u{0081}', '\u{201a}', '\u{0192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02c6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008d}', '\u{017d}', '\u{008f}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02dc}', '\u{2122}', '\u{0161}', '\u{203a}', '\u{0153}', '\u{009d}', '\u{017e}', '\u{0178}',
+];
+
+/// Lower bound for the range of bytes which need to be looked up in [HIGH_CONTROL_MAPPING]
+const HIGH_CONTROL_LOW_BOUND: u8 = 0x80;
+/// Upper bound for the range of bytes which need to be looked up in [HIGH_CONTROL_MAPPING]
+const HIGH_CONTROL_HIGH_BOUND: u8 = 0x9f;
+
+/// A Windows-1252 decoder, which takes a ref to a [BufRead] instance.  Every byte value has a
+/// mapping, so decoding can never fail
+pub struct Windows1252Decoder<'a, B: BufRead> {
+    /// The input stream
+    input: ByteStream<'a, B>,
+}
+
+impl<'a, Buffer: BufRead> Windows1252Decoder<'a, Buffer> {
+    /// Create a new decoder around a [BufRead] instance. Input is read in chunks as decoding
+    /// progresses
+    pub fn new(r: &'a mut Buffer) -> Self {
+        Windows1252Decoder {
+            input: ByteStream::new(r),
+        }
+    }
+
+    /// Attempt to decode the next character in the underlying stream.
+    fn decode_next(&mut self) -> DecoderResult<char> {
+        let byte = match self.input.peek(1)?.first() {
+            Some(b) => *b,
+            None => return end_of_input!(),
+        };
+        self.input.consume(1);
+        if (HIGH_CONTROL_LOW_BOUND..=HIGH_CONTROL_HIGH_BOUND).contains(&byte) {
+            Ok(HIGH_CONTROL_MAPPING[(byte - HIGH_CONTROL_LOW_BOUND) as usize])
+        } else {
+            Ok(char::from(byte))
+        }
+    }
+}
+
+impl<'a, B: BufRead> CharSource for Windows1252Decoder<'a, B> {
+    /// Decode the next character from the underlying stream, surfacing any errors
+    fn next_char(&mut self) -> Option<DecoderResult<CharWithOffset>> {
+        let offset = self.input.offset();
+        match self.decode_next() {
+            Ok(ch) => Some(Ok(CharWithOffset { ch, offset, len: 1 })),
+            Err(err) if err.code == DecoderErrorCode::EndOfInput => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    fn encoding(&self) -> Option<Encoding> {
+        Some(Encoding::Windows1252)
+    }
+}
+
+impl<'a, B: BufRead> Iterator for Windows1252Decoder<'a, B> {
+    type Item = char;
+    /// Decode the next character from the underlying stream
+    fn next(&mut self) -> Option<Self::Item> {
+        self.decode_next().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::windows1252::Windows1252Decoder;
+
+    fn decode(bytes: &[u8]) -> String {
+        let mut reader = BufReader::with_capacity(3, bytes);
+        Windows1252Decoder::new(&mut reader).collect()
+    }
+
+    #[test]
+    fn should_map_the_high_control_range() {
+        assert_eq!(
+            decode(b"\x80\x85\x8a\x91\x92\x93\x94\x96\x97\x99\x9f"),
+            "\u{20ac}\u{2026}\u{0160}\u{2018}\u{2019}\u{201c}\u{201d}\u{2013}\u{2014}\u{2122}\u{0178}"
+        );
+    }
+
+    #[test]
+    fn should_pass_through_undefined_bytes() {
+        assert_eq!(
+            decode(b"\x81\x8d\x8f\x90\x9d"),
+            "\u{0081}\u{008d}\u{008f}\u{0090}\u{009d}"
+        );
+    }
+
+    #[test]
+    fn should_match_latin1_outside_the_high_control_range() {
+        let bytes: Vec<u8> = (0..=0xffu8)
+            .filter(|b| !(0x80..=0x9f).contains(b))
+            .collect();
+        let expected: String = bytes.iter().map(|b| char::from(*b)).collect();
+        assert_eq!(decode(&bytes), expected);
+    }
+}
@@ -2,7 +2,13 @@
 //! A representation of a JSON Pointer with associated operations, as per RFC 6901
 //!
 //!
-use std::{borrow::Cow, collections::VecDeque, fmt::Display, ops::Add};
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    ops::Add,
+};
 
 /// Each pointer is a series of segments delineated by a separator char
 const PATH_SEPARATOR: char = '/';
@@ -37,7 +43,7 @@ impl<'a> Display for JsonPointerComponent<'a> {
 }
 
 /// A structure representing a complete pointer, comprising multiple [JsonPointerComponent]s
-#[derive(Debug, Default, Hash, Eq)]
+#[derive(Debug, Default, Eq)]
 pub struct JsonPointer<'a> {
     /// The components that go together to make up the pointer
     components: VecDeque<JsonPointerComponent<'a>>,
@@ -49,6 +55,13 @@ impl<'a> PartialEq for JsonPointer<'a> {
     }
 }
 
+/// Pointers are compared through their serialised form, so they're hashed in the same way
+impl<'a> Hash for JsonPointer<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
 impl<'a> JsonPointer<'a> {
     /// Returns a root [JsonPointer] instance
     pub fn root() -> Self {
@@ -59,9 +72,9 @@ impl<'a> JsonPointer<'a> {
 
     /// Checks to see if a pointer is just a top level root element
     pub fn is_root(&self) -> bool {
-        return !self.components.is_empty()
+        !self.components.is_empty()
             && self.components.len() == 1
-            && self.components[0] == JsonPointerComponent::Root;
+            && self.components[0] == JsonPointerComponent::Root
     }
 
     /// Returns the number of [JsonPointerComponent]s within the pointer
@@ -155,6 +168,9 @@ impl<'a> Add<&JsonPointer<'a>> for JsonPointer<'a> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
     use super::JsonPointer;
 
     #[test]
@@ -172,14 +188,14 @@ mod tests {
     #[test]
     fn pointers_should_serialise_correctly() {
         let mut s = JsonPointer::default();
-        s.push_names(&vec!["a", "b"]);
+        s.push_names(&["a", "b"]);
         assert_eq!("/a/b", s.as_str())
     }
 
     #[test]
     fn pointers_should_serialise_with_escapes_correctly() {
         let mut s = JsonPointer::default();
-        s.push_names(&vec!["a/b", "c~d"]);
+        s.push_names(&["a/b", "c~d"]);
         s.push_index(3);
         assert_eq!("/a~1b/c~0d/3", s.as_str())
     }
@@ -187,7 +203,7 @@ mod tests {
     #[test]
     fn popping_should_shorten_pointers_correctly() {
         let mut s = JsonPointer::default();
-        s.push_names(&vec!["a", "b", "c"]);
+        s.push_names(&["a", "b", "c"]);
         assert_eq!("/a/b/c", s.as_str());
         s.pop();
         assert_eq!("/a/b", s.as_str())
@@ -196,7 +212,7 @@ mod tests {
     #[test]
     fn popping_all_components_should_result_in_a_root_pointer() {
         let mut s = JsonPointer::default();
-        s.push_names(&vec!["a", "b", "c"]);
+        s.push_names(&["a", "b", "c"]);
         s.pop();
         s.pop();
         s.pop();
@@ -237,4 +253,19 @@ mod tests {
         assert_ne!(t, u);
         assert_ne!(s, u)
     }
+
+    #[test]
+    fn equal_pointers_should_hash_equally() {
+        let hash = |p: &JsonPointer| {
+            let mut hasher = DefaultHasher::new();
+            p.hash(&mut hasher);
+            hasher.finish()
+        };
+        let mut s = JsonPointer::default();
+        let mut t = JsonPointer::default();
+        s.push_name("0".to_string());
+        t.push_index(0);
+        assert_eq!(s, t);
+        assert_eq!(hash(&s), hash(&t))
+    }
 }
@@ -5,7 +5,8 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use pprof::criterion::{Output, PProfProfiler};
 
 use chisel_decoders::utf8::Utf8Decoder;
-use chisel_lexers::json::lexer::Lexer;
+use chisel_decoders::{new_decoder, Encoding};
+use chisel_lexers::json::lexer::{DynLexer, Lexer};
 use chisel_lexers::json::tokens::Token;
 
 macro_rules! build_lex_benchmark {
@@ -31,12 +32,41 @@ macro_rules! build_lex_benchmark {
     };
 }
 
+macro_rules! build_dyn_lex_benchmark {
+    ($func : tt, $filename : expr) => {
+        fn $func() {
+            let f = File::open(format!("fixtures/json/bench/{}.json", $filename)).unwrap();
+            let mut reader = BufReader::new(f);
+            let mut decoder = new_decoder(&mut reader, Encoding::Utf8);
+            let mut lexer: DynLexer = Lexer::new(&mut decoder);
+            loop {
+                match lexer.consume() {
+                    Ok(t) => {
+                        if t.0 == Token::EndOfInput {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        println!("error occurred: {:?}", err);
+                    }
+                }
+            }
+        }
+    };
+}
+
 build_lex_benchmark!(canada, "canada");
 build_lex_benchmark!(citm_catalog, "citm_catalog");
 build_lex_benchmark!(twitter, "twitter");
 build_lex_benchmark!(simple, "simple");
 build_lex_benchmark!(colours, "colours");
 
+build_dyn_lex_benchmark!(canada_dyn, "canada");
+build_dyn_lex_benchmark!(citm_catalog_dyn, "citm_catalog");
+build_dyn_lex_benchmark!(twitter_dyn, "twitter");
+build_dyn_lex_benchmark!(simple_dyn, "simple");
+build_dyn_lex_benchmark!(colours_dyn, "colours");
+
 fn benchmark_canada(c: &mut Criterion) {
     c.bench_function("JSON lex of canada", |b| b.iter(canada));
 }
@@ -53,9 +83,37 @@ fn benchmark_simple(c: &mut Criterion) {
 fn benchmark_colours(c: &mut Criterion) {
     c.bench_function("JSON lex of colours", |b| b.iter(colours));
 }
+
+fn benchmark_canada_dyn(c: &mut Criterion) {
+    c.bench_function("Dynamic JSON lex of canada", |b| b.iter(canada_dyn));
+}
+fn benchmark_citm_catalog_dyn(c: &mut Criterion) {
+    c.bench_function("Dynamic JSON lex of citm_catalog", |b| {
+        b.iter(citm_catalog_dyn)
+    });
+}
+fn benchmark_twitter_dyn(c: &mut Criterion) {
+    c.bench_function("Dynamic JSON lex of twitter", |b| b.iter(twitter_dyn));
+}
+fn benchmark_simple_dyn(c: &mut Criterion) {
+    c.bench_function("Dynamic JSON lex of simple", |b| b.iter(simple_dyn));
+}
+fn benchmark_colours_dyn(c: &mut Criterion) {
+    c.bench_function("Dynamic JSON lex of colours", |b| b.iter(colours_dyn));
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
-    targets= benchmark_twitter, benchmark_citm_catalog, benchmark_canada, benchmark_simple, benchmark_colours
+    targets= benchmark_twitter,
+    benchmark_twitter_dyn,
+    benchmark_citm_catalog,
+    benchmark_citm_catalog_dyn,
+    benchmark_canada,
+    benchmark_canada_dyn,
+    benchmark_simple,
+    benchmark_simple_dyn,
+    benchmark_colours,
+    benchmark_colours_dyn,
 }
 criterion_main!(benches);
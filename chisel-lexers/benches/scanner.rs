@@ -3,7 +3,8 @@ use std::io::BufReader;
 
 use chisel_decoders::ascii::AsciiDecoder;
 use chisel_decoders::utf8::Utf8Decoder;
-use chisel_lexers::scanner::Scanner;
+use chisel_decoders::{new_decoder, Encoding};
+use chisel_lexers::scanner::{DynScanner, Scan, Scanner};
 use criterion::{criterion_group, criterion_main, Criterion};
 use pprof::criterion::{Output, PProfProfiler};
 
@@ -39,12 +40,31 @@ macro_rules! build_ascii_scanner_benchmark {
     };
 }
 
+macro_rules! build_dyn_scanner_benchmark {
+    ($func : tt, $filename : expr) => {
+        fn $func() {
+            let f = File::open(format!("fixtures/json/bench/{}.json", $filename)).unwrap();
+            let mut reader = BufReader::new(f);
+            let mut decoder = new_decoder(&mut reader, Encoding::Utf8);
+            let mut scanner: DynScanner = Scanner::new(&mut decoder);
+            loop {
+                if scanner.advance(true).is_err() {
+                    break;
+                }
+            }
+        }
+    };
+}
+
 build_utf8_scanner_benchmark!(canada_utf8, "canada");
 build_utf8_scanner_benchmark!(citm_catalog_utf8, "citm_catalog");
 build_utf8_scanner_benchmark!(twitter_utf8, "twitter");
 build_utf8_scanner_benchmark!(simple_utf8, "simple");
 build_utf8_scanner_benchmark!(colours_utf8, "colours");
 
+build_dyn_scanner_benchmark!(canada_dyn, "canada");
+build_dyn_scanner_benchmark!(twitter_dyn, "twitter");
+
 build_ascii_scanner_benchmark!(canada_ascii, "canada");
 build_ascii_scanner_benchmark!(citm_catalog_ascii, "citm_catalog");
 build_ascii_scanner_benchmark!(simple_ascii, "simple");
@@ -67,6 +87,13 @@ fn benchmark_colours_utf8(c: &mut Criterion) {
     c.bench_function("UTF-8 scan of colours", |b| b.iter(colours_utf8));
 }
 
+fn benchmark_canada_dyn(c: &mut Criterion) {
+    c.bench_function("Dynamic UTF-8 scan of canada", |b| b.iter(canada_dyn));
+}
+fn benchmark_twitter_dyn(c: &mut Criterion) {
+    c.bench_function("Dynamic UTF-8 scan of twitter", |b| b.iter(twitter_dyn));
+}
+
 fn benchmark_canada_ascii(c: &mut Criterion) {
     c.bench_function("ASCII scan of canada", |b| b.iter(canada_ascii));
 }
@@ -85,9 +112,11 @@ criterion_group! {
     name = benches;
     config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
     targets= benchmark_twitter_utf8,
+    benchmark_twitter_dyn,
     benchmark_citm_catalog_utf8,
     benchmark_citm_catalog_ascii,
     benchmark_canada_utf8,
+    benchmark_canada_dyn,
     benchmark_canada_ascii,
     benchmark_simple_utf8,
     benchmark_simple_ascii,
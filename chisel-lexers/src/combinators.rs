@@ -0,0 +1,195 @@
+//! A small set of combinators, built on top of the [Scan] trait, for writing new lexers.
+//!
+//! Rather than hand-rolling a loop for every matching routine, a lexer can compose the operations
+//! provided by [Combinators], which is implemented for any scanner.  For example, a lexer for
+//! simple `key=value` pairs might look something like this:
+//!
+//! ```rust
+//!  use chisel_lexers::combinators::Combinators;
+//!  use chisel_lexers::scanner::{Scan, ScannerErrorDetails};
+//!  use chisel_lexers::slice::SliceScanner;
+//!
+//!  let mut scanner = SliceScanner::new("colour = red\nsize=large");
+//!  let mut pairs = vec![];
+//!  while scanner.try_lookahead().is_some() {
+//!     scanner.clear();
+//!     scanner.advance_while(|ch| ch.is_alphanumeric()).unwrap();
+//!     let key = scanner.buffer_as_str();
+//!     scanner.advance_while(|ch| ch == ' ').unwrap();
+//!     scanner.expect_one_of(&['=', ':']).unwrap();
+//!     scanner.advance_while(|ch| ch == ' ').unwrap();
+//!     scanner.clear();
+//!     if scanner.take_until('\n').is_err() {
+//!         // the last value runs up to the end of the input
+//!     }
+//!     pairs.push((key, scanner.buffer_as_str()));
+//!     scanner.advance(false).ok();
+//!  }
+//!  assert_eq!(pairs, vec![("colour".into(), "red".into()), ("size".into(), "large".into())]);
+//!
+//!  // errors are positioned at the first character which fails to match
+//!  let mut scanner = SliceScanner::new("nul1");
+//!  let err = scanner.match_literal("null").err().unwrap();
+//!  assert_eq!(err.details, ScannerErrorDetails::MatchFailed("null".into(), "nul1".into()));
+//!  assert_eq!(err.coords.unwrap().column, 4);
+//! ```
+use crate::scanner::{CharWithCoords, Scan, ScannerError, ScannerErrorDetails, ScannerResult};
+
+/// Something which can be looked for in the input, such as a [char] or a [str]
+pub trait Needle {
+    /// Check whether the needle occurs at the next position in the input, without consuming any
+    /// of it
+    fn is_next<'a, S: Scan<'a> + ?Sized>(&self, scanner: &mut S) -> bool;
+}
+
+impl Needle for char {
+    fn is_next<'a, S: Scan<'a> + ?Sized>(&self, scanner: &mut S) -> bool {
+        scanner.peek_n(0).map_or(false, |cwc| cwc.ch == *self)
+    }
+}
+
+impl Needle for &str {
+    fn is_next<'a, S: Scan<'a> + ?Sized>(&self, scanner: &mut S) -> bool {
+        self.chars()
+            .enumerate()
+            .all(|(n, ch)| scanner.peek_n(n).map_or(false, |cwc| cwc.ch == ch))
+    }
+}
+
+/// Combinators available for any implementation of [Scan].  All of these accumulate the
+/// characters they consume into the scanner buffer, and none of them skip whitespace
+pub trait Combinators<'a>: Scan<'a> {
+    /// Advance over the next character, returning it
+    fn advance_char(&mut self) -> ScannerResult<CharWithCoords> {
+        self.advance(false)?;
+        self.front().ok_or(ScannerError {
+            details: ScannerErrorDetails::EndOfInput,
+            coords: None,
+        })
+    }
+
+    /// Advance over characters for as long as they satisfy a predicate, returning the number of
+    /// characters consumed.  Reaching the end of the input isn't an error
+    fn advance_while<P: FnMut(char) -> bool>(&mut self, mut predicate: P) -> ScannerResult<usize> {
+        let mut count = 0;
+        while self.peek_n(0).map_or(false, |cwc| predicate(cwc.ch)) {
+            self.advance(false)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Advance over characters up until the next occurrence of a [Needle], which is left in the
+    /// input, returning the number of characters consumed. Returns an error if the end of the
+    /// input is reached first
+    fn take_until<N: Needle>(&mut self, needle: N) -> ScannerResult<usize> {
+        let mut count = 0;
+        while !needle.is_next(self) {
+            self.advance(false)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Advance over a literal string. If the input doesn't match, then a
+    /// [ScannerErrorDetails::MatchFailed] error is returned, positioned at the first character
+    /// which differs from the literal. That character is consumed, along with everything before it
+    fn match_literal(&mut self, literal: &str) -> ScannerResult<()> {
+        let mut found = String::with_capacity(literal.len());
+        for expected in literal.chars() {
+            let cwc = self.advance_char()?;
+            found.push(cwc.ch);
+            if cwc.ch != expected {
+                return Err(ScannerError {
+                    details: ScannerErrorDetails::MatchFailed(literal.to_string(), found),
+                    coords: Some(cwc.coords),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Advance over a single character, which must be one of a given set.  If it isn't, then a
+    /// [ScannerErrorDetails::UnexpectedCharacter] error is returned (and the character is still
+    /// consumed)
+    fn expect_one_of(&mut self, set: &[char]) -> ScannerResult<char> {
+        let cwc = self.advance_char()?;
+        if set.contains(&cwc.ch) {
+            Ok(cwc.ch)
+        } else {
+            Err(ScannerError {
+                details: ScannerErrorDetails::UnexpectedCharacter(cwc.ch),
+                coords: Some(cwc.coords),
+            })
+        }
+    }
+}
+
+impl<'a, S: Scan<'a> + ?Sized> Combinators<'a> for S {}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use chisel_common::reader_from_bytes;
+    use chisel_decoders::utf8::Utf8Decoder;
+
+    use crate::combinators::Combinators;
+    use crate::scanner::{Scan, Scanner, ScannerErrorDetails};
+    use crate::slice::SliceScanner;
+
+    #[test]
+    fn should_advance_while_a_predicate_holds() {
+        let mut scanner = SliceScanner::new("1234abc");
+        assert_eq!(scanner.advance_while(|ch| ch.is_ascii_digit()).unwrap(), 4);
+        assert_eq!(scanner.buffer_as_str(), "1234");
+        assert_eq!(scanner.advance_while(|ch| ch.is_ascii_digit()).unwrap(), 0);
+        assert_eq!(scanner.advance_while(|_| true).unwrap(), 3);
+        assert_eq!(scanner.buffer_as_str(), "1234abc");
+    }
+
+    #[test]
+    fn should_take_until_a_needle() {
+        let mut reader = reader_from_bytes!("a /* b */ c");
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut scanner = Scanner::new(&mut decoder);
+        scanner.match_literal("a /*").unwrap();
+        scanner.clear();
+        assert_eq!(scanner.take_until("*/").unwrap(), 3);
+        assert_eq!(scanner.buffer_as_str(), " b ");
+        assert_eq!(scanner.peek_n(0).unwrap().ch, '*');
+        assert_eq!(scanner.take_until('c').unwrap(), 3);
+        let err = scanner.take_until('d').err().unwrap();
+        assert_eq!(err.details, ScannerErrorDetails::EndOfInput);
+    }
+
+    #[test]
+    fn should_report_the_first_mismatch_in_a_literal() {
+        let mut scanner = SliceScanner::new("  tru\u{e9}");
+        scanner.advance_while(char::is_whitespace).unwrap();
+        scanner.clear();
+        let err = scanner.match_literal("true").err().unwrap();
+        assert_eq!(
+            err.details,
+            ScannerErrorDetails::MatchFailed("true".into(), "tru\u{e9}".into())
+        );
+        let coords = err.coords.unwrap();
+        assert_eq!((coords.column, coords.byte_offset), (6, 5));
+
+        let mut scanner = SliceScanner::new("tr");
+        let err = scanner.match_literal("true").err().unwrap();
+        assert_eq!(err.details, ScannerErrorDetails::EndOfInput);
+    }
+
+    #[test]
+    fn should_expect_one_of_a_set() {
+        let mut scanner = SliceScanner::new("+-*");
+        assert_eq!(scanner.expect_one_of(&['+', '-']).unwrap(), '+');
+        assert_eq!(scanner.expect_one_of(&['+', '-']).unwrap(), '-');
+        let err = scanner.expect_one_of(&['+', '-']).err().unwrap();
+        assert_eq!(err.details, ScannerErrorDetails::UnexpectedCharacter('*'));
+        assert_eq!(err.coords.unwrap().column, 3);
+        let err = scanner.expect_one_of(&['+', '-']).err().unwrap();
+        assert_eq!(err.details, ScannerErrorDetails::EndOfInput);
+    }
+}
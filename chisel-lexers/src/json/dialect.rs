@@ -0,0 +1,100 @@
+/// Enumeration of the dialects of JSON which may be lexed (and parsed)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Dialect {
+    /// Strict JSON, as defined by RFC 8259
+    Json,
+    /// [JSON5](https://spec.json5.org), which extends JSON with comments, trailing commas,
+    /// single-quoted strings, identifier keys, hexadecimal numbers, leading and trailing decimal
+    /// points, explicit plus signs, `Infinity`, `NaN` and multi-line strings
+    Json5,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect::Json
+    }
+}
+
+/// Check whether a character is whitespace in JSON5, which allows for any Unicode space separator
+/// (along with the byte order mark) rather than just the four characters allowed by JSON
+#[inline]
+pub(crate) fn is_json5_whitespace(ch: char) -> bool {
+    ch == '\u{feff}' || (ch.is_whitespace() && ch != '\u{85}')
+}
+
+/// Check whether a character terminates a line, and therefore a JSON5 single line comment
+#[inline]
+pub(crate) fn is_line_terminator(ch: char) -> bool {
+    matches!(ch, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+/// Check whether a character may start an unquoted JSON5 identifier. As per the ES5
+/// IdentifierName grammar, this is any character with the Unicode ID_Start property (letters and
+/// letter numbers), along with `$` and `_`
+#[inline]
+pub(crate) fn is_identifier_start(ch: char) -> bool {
+    ch == '$' || ch == '_' || unicode_ident::is_xid_start(ch)
+}
+
+/// Check whether a character may appear after the start of an unquoted JSON5 identifier. Along
+/// with anything which may start an identifier, this allows for characters with the Unicode
+/// ID_Continue property (combining marks, decimal digits and connector punctuation), together with
+/// the zero width non-joiner and joiner
+#[inline]
+pub(crate) fn is_identifier_part(ch: char) -> bool {
+    ch == '$' || ch == '\u{200c}' || ch == '\u{200d}' || unicode_ident::is_xid_continue(ch)
+}
+
+/// Rewrite a decimal JSON5 number so that it follows the JSON number grammar, by removing any
+/// explicit plus sign and adding a zero to either side of a bare decimal point
+pub(crate) fn normalise_number(raw: &str) -> String {
+    let (sign, unsigned) = match raw.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", raw.strip_prefix('+').unwrap_or(raw)),
+    };
+    let (mantissa, exponent) = unsigned.split_at(
+        unsigned
+            .find(|ch| ch == 'e' || ch == 'E')
+            .unwrap_or(unsigned.len()),
+    );
+    let leading = if mantissa.starts_with('.') { "0" } else { "" };
+    let trailing = if mantissa.ends_with('.') { "0" } else { "" };
+    format!("{}{}{}{}{}", sign, leading, mantissa, trailing, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::dialect::{is_identifier_part, is_identifier_start, normalise_number};
+
+    #[test]
+    fn should_follow_the_es5_identifier_grammar() {
+        for ch in ['a', '\u{e9}', '\u{2160}', '$', '_'] {
+            assert!(is_identifier_start(ch), "{:?}", ch);
+        }
+        for ch in ['1', '\u{301}', '\u{203f}', '\u{b2}', '\u{200c}'] {
+            assert!(!is_identifier_start(ch), "{:?}", ch);
+        }
+        for ch in [
+            'a', '1', '\u{301}', '\u{903}', '\u{203f}', '\u{200c}', '\u{200d}',
+        ] {
+            assert!(is_identifier_part(ch), "{:?}", ch);
+        }
+        for ch in ['\u{b2}', '\u{bd}', '-', '\u{a0}'] {
+            assert!(!is_identifier_part(ch), "{:?}", ch);
+        }
+    }
+
+    #[test]
+    fn should_normalise_numbers() {
+        for (raw, normalised) in [
+            ("+1", "1"),
+            (".5", "0.5"),
+            ("-.5e3", "-0.5e3"),
+            ("5.", "5.0"),
+            ("+5.E-2", "5.0E-2"),
+            ("12.25", "12.25"),
+        ] {
+            assert_eq!(normalise_number(raw), normalised);
+        }
+    }
+}
@@ -1,18 +1,32 @@
-//!
+//! The JSON lexer backend
 #![allow(unused_assignments)]
 #![allow(unused_variables)]
 #![allow(unreachable_code)]
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
+use std::iter::FusedIterator;
 
+use crate::combinators::Combinators;
+use crate::json::dialect::{
+    is_identifier_part, is_identifier_start, is_json5_whitespace, is_line_terminator,
+    normalise_number, Dialect,
+};
 use crate::json::numerics::LazyNumeric;
+use crate::json::strings::{unescape, LazyString};
 use chisel_common::char::coords::Coords;
 use chisel_common::char::span::Span;
+use chisel_decoders::common::{CharSource, DecoderErrorCode};
 
 use crate::json::tokens::{PackedToken, Token};
-use crate::scanner::{CharWithCoords, Scanner};
+use crate::lex::Lex;
+use crate::position::PositionModel;
+use crate::scanner::{
+    is_whitespace, CharWithCoords, DynScanner, Scan, Scanner, ScannerError, ScannerErrorDetails,
+};
+use crate::slice::SliceScanner;
 
 /// JSON lexer backend result type
 pub type LexerResult<T> = Result<T, LexerError>;
@@ -33,7 +47,7 @@ pub enum LexerErrorDetails {
     NonUtf8InputDetected,
     /// Edge case error condition. This means that something has gone horribly wrong with the
     /// parse.
-    UnexpectedToken(Token),
+    UnexpectedToken(Token<'static>),
     /// KV pair is expected but not detected.
     PairExpected,
     /// Supplied JSON doesn't have an object or array as a root object.
@@ -52,6 +66,12 @@ pub enum LexerErrorDetails {
     InvalidEscapeSequence(String),
     /// An invalid unicode escape sequence (\uXXX) has been found within the input.
     InvalidUnicodeEscapeSequence(String),
+    /// The underlying character source failed to decode the input.
+    Decode(DecoderErrorCode),
+    /// The lexer attempted to return to a point in the input which is no longer available.
+    InvalidMark,
+    /// A JSON5 block comment wasn't closed before the end of the input.
+    UnterminatedComment,
 }
 
 impl Display for LexerErrorDetails {
@@ -86,6 +106,9 @@ impl Display for LexerErrorDetails {
             LexerErrorDetails::InvalidUnicodeEscapeSequence(seq) => {
                 write!(f, "invalid unicode escape sequence: \"{}\"", seq)
             }
+            LexerErrorDetails::Decode(code) => write!(f, "failed to decode input: {}", code),
+            LexerErrorDetails::InvalidMark => write!(f, "invalid mark"),
+            LexerErrorDetails::UnterminatedComment => write!(f, "unterminated comment"),
         }
     }
 }
@@ -101,15 +124,29 @@ pub struct LexerError {
 
 impl Display for LexerError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.coords.is_some() {
-            write!(
-                f,
-                "details: {}, coords: {}",
-                self.details,
-                self.coords.unwrap()
-            )
-        } else {
-            write!(f, "details: {}", self.details)
+        match self.coords {
+            Some(coords) => write!(f, "details: {}, coords: {}", self.details, coords),
+            None => write!(f, "details: {}", self.details),
+        }
+    }
+}
+
+/// Allows conversion from errors arising within the scanning stage of lexing
+impl From<ScannerError> for LexerError {
+    fn from(value: ScannerError) -> Self {
+        LexerError {
+            details: match value.details {
+                ScannerErrorDetails::EndOfInput => LexerErrorDetails::EndOfInput,
+                ScannerErrorDetails::Decode(code) => LexerErrorDetails::Decode(code),
+                ScannerErrorDetails::InvalidMark => LexerErrorDetails::InvalidMark,
+                ScannerErrorDetails::MatchFailed(expected, found) => {
+                    LexerErrorDetails::MatchFailed(expected, found)
+                }
+                ScannerErrorDetails::UnexpectedCharacter(ch) => {
+                    LexerErrorDetails::InvalidCharacter(ch)
+                }
+            },
+            coords: value.coords,
         }
     }
 }
@@ -146,13 +183,6 @@ macro_rules! lexer_error {
     };
 }
 
-/// Pattern to match for null
-const NULL_ASCII: [u8; 4] = [0x6e, 0x75, 0x6c, 0x6c];
-/// Pattern to match for true
-const TRUE_ASCII: [u8; 4] = [0x74, 0x72, 0x75, 0x65];
-/// Pattern to match for false
-const FALSE_ASCII: [u8; 5] = [0x66, 0x61, 0x6c, 0x73, 0x65];
-
 macro_rules! packed_token {
     ($t:expr, $s:expr, $e:expr) => {
         Ok(($t, Span { start: $s, end: $e }))
@@ -190,13 +220,6 @@ macro_rules! match_digit {
     };
 }
 
-/// Pattern matching macro
-macro_rules! match_non_zero_digit {
-    () => {
-        '1'..='9'
-    };
-}
-
 /// Pattern matching macro
 macro_rules! match_exponent {
     () => {
@@ -225,13 +248,6 @@ macro_rules! match_escape {
     };
 }
 
-/// Pattern matching macro
-macro_rules! match_escape_non_unicode_suffix {
-    () => {
-        'n' | 't' | 'r' | '\\' | '/' | 'b' | 'f' | '\"'
-    };
-}
-
 /// Pattern matching macro
 macro_rules! match_escape_unicode_suffix {
     () => {
@@ -246,44 +262,60 @@ macro_rules! match_quote {
     };
 }
 
-/// Pattern matching macro
-macro_rules! match_newline {
-    () => {
-        '\n'
-    };
+/// The JSON lexer, which pulls characters through a scanner in order to produce [Token]s. Usually,
+/// a [Scanner] is used to read from a [CharSource], but any implementation of [Scan] may be used.
+/// The lifetime `'a` is that of the tokens produced: when working over a [SliceScanner], string
+/// tokens are borrowed directly from the input, whereas a [Scanner] always produces owned tokens
+pub struct Lexer<'a, I: Scan<'a>> {
+    /// Input coordinate state
+    input: I,
+    /// The result of any call to [Lexer::peek] which has yet to be consumed
+    peeked: Option<LexerResult<PackedToken<'a>>>,
+    /// Set once iteration has reached either the end of the input or an error
+    finished: bool,
+    /// The dialect of JSON being lexed
+    dialect: Dialect,
 }
 
-/// Given a source and target to compare, adjust a starting [Coord] so that it points to
-/// the exact location where they fail to match
-#[inline]
-fn adjusted_error_coords(start_coords: &Coords, source: &[u8], target: &[u8]) -> Coords {
-    let mut err_coords = Coords::from_coords(start_coords);
-    for i in 0..=target.len() {
-        if !source[i].is_ascii_whitespace() {
-            if source[i] != target[i] {
-                break;
-            } else {
-                err_coords.increment();
-            }
-        } else {
-            err_coords.increment();
-        }
+/// A [Lexer] which reads from a type-erased [CharSource], for use when the decoder is selected at
+/// runtime
+pub type DynLexer<'c> = Lexer<'static, DynScanner<'c>>;
+
+impl<S: CharSource> Lexer<'static, Scanner<S>> {
+    pub fn new(chars: S) -> Self {
+        Self::with_scanner(Scanner::new(chars))
+    }
+
+    /// New instance, which calculates token positions according to a given [PositionModel]
+    pub fn with_position_model(chars: S, model: PositionModel) -> Self {
+        Self::with_scanner(Scanner::with_position_model(chars, model))
     }
-    err_coords
 }
 
-pub struct Lexer<'a> {
-    /// Input coordinate state
-    input: Scanner<'a>,
+impl<'a> Lexer<'a, SliceScanner<'a>> {
+    /// New instance, which works directly over a string slice without any copying
+    pub fn from_slice(input: &'a str) -> Self {
+        Self::with_scanner(SliceScanner::new(input))
+    }
 }
 
-impl<'a> Lexer<'a> {
-    pub fn new(chars: &'a mut impl Iterator<Item = char>) -> Self {
+impl<'a, I: Scan<'a>> Lexer<'a, I> {
+    /// New instance, based on any implementation of [Scan]
+    pub fn with_scanner(input: I) -> Self {
         Lexer {
-            input: Scanner::new(chars),
+            input,
+            peeked: None,
+            finished: false,
+            dialect: Dialect::Json,
         }
     }
 
+    /// Set the [Dialect] of JSON to be lexed, which defaults to strict JSON
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
     /// Get the front of the input
     fn front(&self) -> Option<CharWithCoords> {
         self.input.front()
@@ -296,26 +328,32 @@ impl<'a> Lexer<'a> {
 
     /// Grab the front character
     #[inline]
-    fn front_char(&self) -> char {
-        self.input.front().unwrap().ch
+    fn front_char(&self) -> Option<char> {
+        self.input.front().map(|cwc| cwc.ch)
     }
 
     /// Grab the back character
     #[inline]
-    fn back_char(&self) -> char {
-        self.input.back().unwrap().ch
+    fn back_char(&self) -> Option<char> {
+        self.input.back().map(|cwc| cwc.ch)
     }
 
-    /// Grab the front input coordinates
+    /// Grab the front input coordinates, falling back to the current position if the buffer is
+    /// empty
     #[inline]
     fn front_coords(&self) -> Coords {
-        self.input.front().unwrap().coords
+        self.input
+            .front()
+            .map_or(self.absolute_position(), |cwc| cwc.coords)
     }
 
-    /// Grab the back input coordinates
+    /// Grab the back input coordinates, falling back to the current position if the buffer is
+    /// empty
     #[inline]
     fn back_coords(&self) -> Coords {
-        self.input.back().unwrap().coords
+        self.input
+            .back()
+            .map_or(self.absolute_position(), |cwc| cwc.coords)
     }
 
     /// Grab the current absolute input coordinates
@@ -329,38 +367,95 @@ impl<'a> Lexer<'a> {
     fn advance(&mut self, skip_whitespace: bool) -> LexerResult<()> {
         self.input
             .advance(skip_whitespace)
-            .map_err(|e| lexer_error!(LexerErrorDetails::EndOfInput))
+            .map_err(LexerError::from)
     }
 
     /// Advance the input by n
     fn advance_n(&mut self, n: usize, skip_whitespace: bool) -> LexerResult<()> {
         self.input
             .advance_n(n, skip_whitespace)
-            .map_err(|e| lexer_error!(LexerErrorDetails::EndOfInput))
+            .map_err(LexerError::from)
+    }
+
+    /// Advance the input over the next character (without skipping whitespace), returning it
+    #[inline]
+    fn advance_char(&mut self) -> LexerResult<CharWithCoords> {
+        self.input
+            .advance_char()
+            .map_err(|err| self.scanner_error(err))
+    }
+
+    /// Convert a [ScannerError] into a [LexerError], falling back to the current input position
+    /// if the scanner didn't supply any coordinates
+    fn scanner_error(&self, err: ScannerError) -> LexerError {
+        let coords = err.coords.unwrap_or(self.absolute_position());
+        lexer_error!(LexerError::from(err).details, coords)
     }
 
     /// Grab the current input string
     #[inline]
     fn current_string(&mut self) -> String {
-        self.input.buffer_as_string_with_span().str
+        self.input.buffer_as_str().into_owned()
     }
 
-    /// Grab the current input character array
+    /// Grab the current input bytes
     #[inline]
-    fn current_chars(&mut self) -> Vec<char> {
-        self.input.buffer_as_char_array()
+    fn current_bytes(&mut self) -> Cow<'a, [u8]> {
+        self.input.buffer_as_bytes()
     }
 
-    /// Grab the current input byte array
-    #[inline]
-    fn current_bytes(&mut self) -> Vec<u8> {
-        self.input.buffer_as_byte_array()
+    /// Consume the next [Token] from the input
+    pub fn consume(&mut self) -> LexerResult<PackedToken<'a>> {
+        match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.next_token(),
+        }
     }
 
-    /// Consume the next [Token] from the input and add it to the internal buffer
-    pub fn consume(&mut self) -> LexerResult<PackedToken> {
+    /// Take a look at the next [Token] in the input, without consuming it
+    pub fn peek(&mut self) -> LexerResult<&PackedToken<'a>> {
+        let peeked = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.next_token(),
+        };
+        match self.peeked.insert(peeked) {
+            Ok(packed) => Ok(packed),
+            Err(err) => Err(err.clone()),
+        }
+    }
+
+    /// Take a look at the next item which will be returned when iterating over the lexer, without
+    /// consuming it. In the same way as [std::iter::Peekable::peek], [None] is returned once the
+    /// end of the input (or an error) has been reached
+    pub fn peek_next(&mut self) -> Option<&LexerResult<PackedToken<'a>>> {
+        if self.finished {
+            return None;
+        }
+        if self.peeked.is_none() {
+            self.peeked = Some(self.next_token());
+        }
+        match &self.peeked {
+            Some(Ok((Token::EndOfInput, _))) => None,
+            peeked => peeked.as_ref(),
+        }
+    }
+
+    /// The source text of the token most recently lexed, exactly as it appears in the input. For
+    /// string tokens this includes the enclosing quotes, along with any escape sequences which
+    /// have been decoded in the [Token::Str] value. Note that after a call to [Lexer::peek], this
+    /// will be the text of the peeked token
+    pub fn raw_text(&mut self) -> Cow<'a, str> {
+        self.input.buffer_as_str()
+    }
+
+    /// Pull the next [Token] through from the input
+    fn next_token(&mut self) -> LexerResult<PackedToken<'a>> {
         self.input.clear();
-        match self.advance(true) {
+        let advanced = match self.dialect {
+            Dialect::Json => self.advance(true),
+            Dialect::Json5 => self.advance_over_trivia(),
+        };
+        match advanced {
             Ok(_) => match self.input.front() {
                 Some(CharWithCoords { ch: '{', coords }) => {
                     packed_token!(Token::StartObject, coords)
@@ -372,16 +467,16 @@ impl<'a> Lexer<'a> {
                 Some(CharWithCoords { ch: ']', coords }) => packed_token!(Token::EndArray, coords),
                 Some(CharWithCoords { ch: ':', coords }) => packed_token!(Token::Colon, coords),
                 Some(CharWithCoords { ch: ',', coords }) => packed_token!(Token::Comma, coords),
+                Some(cwc) if self.dialect == Dialect::Json5 => self.match_json5_value(cwc),
                 Some(CharWithCoords { ch: '\"', coords }) => self.match_string(),
                 Some(CharWithCoords { ch: 'n', coords }) => self.match_null(),
                 Some(CharWithCoords { ch: 't', coords }) => self.match_true(),
                 Some(CharWithCoords { ch: 'f', coords }) => self.match_false(),
                 Some(CharWithCoords { ch: '-', coords }) => self.match_number(),
                 Some(CharWithCoords { ch: d, coords }) if d.is_ascii_digit() => self.match_number(),
-                Some(CharWithCoords { ch, coords }) => wrapped_lexer_error!(
-                    LexerErrorDetails::InvalidCharacter(ch.clone()),
-                    coords.clone()
-                ),
+                Some(CharWithCoords { ch, coords }) => {
+                    wrapped_lexer_error!(LexerErrorDetails::InvalidCharacter(ch), coords)
+                }
                 None => {
                     wrapped_lexer_error!(LexerErrorDetails::EndOfInput, self.absolute_position())
                 }
@@ -398,361 +493,613 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    /// Match on a valid Json string.
+    /// Match on a valid Json string, decoding any escape sequences found within it. If there
+    /// aren't any, then the value of the string is borrowed from the input where possible. Control
+    /// characters (U+0000 through U+001F) must be escaped, and are rejected if found unescaped
+    #[cfg(not(feature = "lazy-strings"))]
     #[inline]
-    fn match_string(&mut self) -> LexerResult<PackedToken> {
+    fn match_string(&mut self) -> LexerResult<PackedToken<'a>> {
+        let mut decoded: Option<String> = None;
         loop {
-            match self.advance(false) {
-                Ok(_) => match self.front_char() {
-                    match_escape!() => match self.input.advance(false) {
-                        Ok(_) => match self.front_char() {
-                            match_escape_non_unicode_suffix!() => (),
-                            match_escape_unicode_suffix!() => self.check_unicode_sequence()?,
-                            _ => {
-                                return wrapped_lexer_error!(
-                                    LexerErrorDetails::InvalidEscapeSequence(self.current_string()),
-                                    self.back_coords()
-                                );
-                            }
-                        },
-                        Err(err) => {
-                            return wrapped_lexer_error!(
-                                LexerErrorDetails::EndOfInput,
-                                err.coords.unwrap()
-                            );
-                        }
-                    },
-                    match_quote!() => {
-                        return packed_token!(
-                            Token::Str(self.current_string()),
-                            self.back_coords(),
-                            self.front_coords()
-                        );
-                    }
-                    _ => (),
-                },
-                Err(err) => {
-                    return match err.coords {
-                        Some(_) => {
-                            wrapped_lexer_error!(err.details, err.coords.unwrap())
-                        }
-                        None => wrapped_lexer_error!(err.details, self.absolute_position()),
+            let cwc = self.advance_char()?;
+            match cwc.ch {
+                match_escape!() => {
+                    let decoded = match decoded {
+                        Some(ref mut decoded) => decoded,
+                        None => decoded.insert(self.string_contents(1).into_owned()),
+                    };
+                    decoded.push(self.match_escape_sequence(cwc.coords)?);
+                }
+                match_quote!() => {
+                    let value = match decoded {
+                        Some(decoded) => Cow::Owned(decoded),
+                        None => self.string_contents(1),
+                    };
+                    return packed_token!(Token::Str(value), self.back_coords(), cwc.coords);
+                }
+                ch if ch < '\u{20}' => {
+                    return wrapped_lexer_error!(
+                        LexerErrorDetails::InvalidCharacter(ch),
+                        cwc.coords
+                    )
+                }
+                ch => {
+                    if let Some(decoded) = &mut decoded {
+                        decoded.push(ch);
                     }
                 }
             }
         }
     }
 
-    /// Check for a valid unicode escape sequence of the form '\uXXXX'
+    /// Match on a valid Json string in the same way as above, except that escape sequences are
+    /// only validated here, and are decoded later on if the value of the [LazyString] is read
+    #[cfg(feature = "lazy-strings")]
     #[inline]
-    fn check_unicode_sequence(&mut self) -> LexerResult<()> {
-        let start_position = self.absolute_position();
-        for i in 1..=4 {
-            match self.advance(false) {
-                Ok(_) => {
-                    if !self.front_char().is_ascii_hexdigit() {
-                        return wrapped_lexer_error!(
-                            LexerErrorDetails::InvalidUnicodeEscapeSequence(self.current_string()),
-                            start_position
-                        );
-                    }
+    fn match_string(&mut self) -> LexerResult<PackedToken<'a>> {
+        let mut escaped = false;
+        loop {
+            let cwc = self.advance_char()?;
+            match cwc.ch {
+                match_escape!() => {
+                    escaped = true;
+                    self.match_escape_sequence(cwc.coords)?;
+                }
+                match_quote!() => {
+                    let value = LazyString::new(self.string_contents(1), escaped);
+                    return packed_token!(Token::LazyString(value), self.back_coords(), cwc.coords);
                 }
-                Err(e) => {
+                ch if ch < '\u{20}' => {
                     return wrapped_lexer_error!(
-                        LexerErrorDetails::EndOfInput,
-                        self.absolute_position()
-                    );
+                        LexerErrorDetails::InvalidCharacter(ch),
+                        cwc.coords
+                    )
                 }
+                _ => (),
             }
         }
-        Ok(())
     }
 
-    /// Match on a valid Json number representation, taking into account valid prefixes allowed
-    /// within Json but discarding anything that may be allowed by a more general representations.
-    ///
-    /// Few rules are applied here, leading to different error conditions:
-    /// - All representations must have a valid prefix
-    /// - Only a single exponent can be specified
-    /// - Only a single decimal point can be specified
-    /// - Exponents must be well-formed
-    /// - An non-exponent alphabetic found in the representation will result in an error
-    /// - Numbers can be terminated by commas, brackets and whitespace only (end of pair, end of array)
+    /// The contents of the buffer, less the opening quote of a string and the final `trailing`
+    /// bytes
     #[inline]
-    fn match_number(&mut self) -> LexerResult<PackedToken> {
-        let mut have_exponent = false;
-        let mut have_decimal = false;
+    fn string_contents(&mut self, trailing: usize) -> Cow<'a, str> {
+        match self.input.buffer_as_str() {
+            Cow::Borrowed(str) => Cow::Borrowed(&str[1..str.len() - trailing]),
+            Cow::Owned(mut str) => {
+                str.truncate(str.len() - trailing);
+                str.remove(0);
+                Cow::Owned(str)
+            }
+        }
+    }
 
-        match self.match_valid_number_prefix() {
-            Ok(integral) => {
-                have_decimal = !integral;
-                loop {
-                    match self.advance(false) {
-                        Ok(_) => match self.front_char() {
-                            match_digit!() => (),
-                            match_exponent!() => {
-                                if !have_exponent {
-                                    self.check_following_exponent()?;
-                                    have_exponent = true;
-                                } else {
-                                    return wrapped_lexer_error!(
-                                        LexerErrorDetails::InvalidNumericRepresentation(
-                                            self.current_string()
-                                        ),
-                                        self.back_coords()
-                                    );
-                                }
-                            }
-                            match_period!() => {
-                                if !have_decimal {
-                                    have_decimal = true;
-                                } else {
-                                    return wrapped_lexer_error!(
-                                        LexerErrorDetails::InvalidNumericRepresentation(
-                                            self.current_string()
-                                        ),
-                                        self.back_coords()
-                                    );
-                                }
-                            }
-                            match_numeric_terminator!() => {
-                                self.input.pushback();
-                                break;
-                            }
-                            ch if ch.is_ascii_whitespace() => {
-                                self.input.pushback();
-                                break;
-                            }
-                            ch if ch.is_alphabetic() => {
-                                return wrapped_lexer_error!(
-                                    LexerErrorDetails::InvalidNumericRepresentation(
-                                        self.current_string()
-                                    ),
-                                    self.back_coords()
-                                );
-                            }
-                            _ => {
-                                return wrapped_lexer_error!(
-                                    LexerErrorDetails::InvalidNumericRepresentation(
-                                        self.current_string()
-                                    ),
-                                    self.back_coords()
-                                );
-                            }
-                        },
-                        Err(err) => {
-                            return match err.coords {
-                                Some(coords) => wrapped_lexer_error!(err.details, coords),
-                                None => wrapped_lexer_error!(err.details),
-                            };
-                        }
-                    }
+    /// Match on the remainder of an escape sequence, the leading backslash of which is found at
+    /// the given [Coords], returning the [char] it represents
+    #[inline]
+    fn match_escape_sequence(&mut self, start: Coords) -> LexerResult<char> {
+        match self.advance_char()?.ch {
+            match_escape_unicode_suffix!() => self.match_unicode_sequence(start),
+            ch => match unescape(ch) {
+                Some(ch) => Ok(ch),
+                None => self.invalid_escape_sequence(start),
+            },
+        }
+    }
+
+    /// Match on a unicode escape sequence of the form '\uXXXX'. A high surrogate must be followed
+    /// immediately by a second sequence holding a low surrogate, and the two are then combined
+    /// into a single [char]. Unpaired surrogates are rejected
+    #[inline]
+    fn match_unicode_sequence(&mut self, start: Coords) -> LexerResult<char> {
+        let high = self.match_code_unit(start)?;
+        let scalar = match high {
+            0xd800..=0xdbff => {
+                if self.advance_char()?.ch != '\\' || self.advance_char()?.ch != 'u' {
+                    return self.invalid_unicode_sequence(start);
+                }
+                match self.match_code_unit(start)? {
+                    low @ 0xdc00..=0xdfff => 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00),
+                    _ => return self.invalid_unicode_sequence(start),
                 }
             }
-            Err(err) => {
-                return match err.coords {
-                    Some(coords) => wrapped_lexer_error!(err.details, coords),
-                    None => wrapped_lexer_error!(err.details),
-                };
+            0xdc00..=0xdfff => return self.invalid_unicode_sequence(start),
+            _ => high,
+        };
+        match char::from_u32(scalar) {
+            Some(ch) => Ok(ch),
+            None => self.invalid_unicode_sequence(start),
+        }
+    }
+
+    /// Match on the four hex digits which make up a single UTF-16 code unit within a unicode
+    /// escape sequence
+    #[inline]
+    fn match_code_unit(&mut self, start: Coords) -> LexerResult<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.advance_char()?.ch.to_digit(16) {
+                Some(digit) => code = code * 16 + digit,
+                None => return self.invalid_unicode_sequence(start),
             }
         }
+        Ok(code)
+    }
 
-        self.parse_numeric(!have_decimal)
+    /// Report an invalid unicode escape sequence, starting at the given [Coords]
+    fn invalid_unicode_sequence<T>(&mut self, start: Coords) -> LexerResult<T> {
+        wrapped_lexer_error!(
+            LexerErrorDetails::InvalidUnicodeEscapeSequence(self.current_string()),
+            start
+        )
     }
 
+    /// Match on a valid Json number representation, following the grammar given in RFC 8259:
+    ///
+    /// `number = [ minus ] int [ frac ] [ exp ]`
+    ///
+    /// - `int` is either a single zero, or a non-zero digit followed by any number of digits
+    /// - `frac` is a decimal point followed by at least one digit
+    /// - `exp` is an `e` or `E`, followed by an optional sign and at least one digit
+    ///
+    /// Numbers can be terminated by commas, closing brackets, whitespace or the end of the input
+    /// only. Errors are reported at the start of the representation
     #[inline]
-    fn check_following_exponent(&mut self) -> LexerResult<()> {
-        self.advance(false).and_then(|_| {
-            return match self.front_char() {
-                match_plus_minus!() => Ok(()),
-                _ => wrapped_lexer_error!(
-                    LexerErrorDetails::InvalidNumericRepresentation(self.current_string()),
-                    self.absolute_position()
-                ),
-            };
-        })
+    fn match_number(&mut self) -> LexerResult<PackedToken<'a>> {
+        if self.back_char() == Some(match_minus!()) {
+            self.match_required_digit()?;
+        }
+        if self.front_char() != Some(match_zero!()) {
+            self.advance_over_digits()?;
+        }
+        let mut integral = true;
+        if matches!(self.peek_char(), Some(match_period!())) {
+            self.advance(false)?;
+            self.match_required_digit()?;
+            self.advance_over_digits()?;
+            integral = false;
+        }
+        if matches!(self.peek_char(), Some(match_exponent!())) {
+            self.advance(false)?;
+            if matches!(self.peek_char(), Some(match_plus_minus!())) {
+                self.advance(false)?;
+            }
+            self.match_required_digit()?;
+            self.advance_over_digits()?;
+            integral = false;
+        }
+        match self.peek_char() {
+            Some(match_numeric_terminator!()) | None => {}
+            Some(ch) if is_whitespace(ch) => {}
+            Some(_) => {
+                self.advance(false)?;
+                return self.invalid_number();
+            }
+        }
+        let bytes = self.current_bytes();
+        self.parse_numeric(&bytes, integral)
+    }
+
+    /// Take a look at the next character in the input, without consuming it
+    #[inline]
+    fn peek_char(&mut self) -> Option<char> {
+        self.input.peek_n(0).map(|cwc| cwc.ch)
     }
 
-    /// We detect whether we have an integral or
-    /// floating point value, and parse differently (and emit different tokens) for each
+    /// Advance over a single digit, which must be present at this point within a number
+    #[inline]
+    fn match_required_digit(&mut self) -> LexerResult<()> {
+        match self.advance_char() {
+            Ok(CharWithCoords {
+                ch: match_digit!(), ..
+            }) => Ok(()),
+            Ok(_)
+            | Err(LexerError {
+                details: LexerErrorDetails::EndOfInput,
+                ..
+            }) => self.invalid_number(),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Advance over any digits at the front of the input
+    #[inline]
+    fn advance_over_digits(&mut self) -> LexerResult<usize> {
+        self.input
+            .advance_while(|ch| ch.is_ascii_digit())
+            .map_err(|err| self.scanner_error(err))
+    }
+
+    /// Report an invalid number, positioned at the start of its representation
+    fn invalid_number<T>(&mut self) -> LexerResult<T> {
+        wrapped_lexer_error!(
+            LexerErrorDetails::InvalidNumericRepresentation(self.current_string()),
+            self.back_coords()
+        )
+    }
+
+    /// We detect whether we have an integral or floating point value, and parse differently (and
+    /// emit different tokens) for each. Integers which are too large to fit into an [i64] are
+    /// emitted as lazy numerics, so that their exact value isn't lost
     #[cfg(not(feature = "lazy-numerics"))]
     #[inline]
-    fn parse_numeric(&mut self, integral: bool) -> LexerResult<PackedToken> {
+    fn parse_numeric(&mut self, bytes: &[u8], integral: bool) -> LexerResult<PackedToken<'a>> {
         if integral {
-            packed_token!(
-                Token::Integer(lexical::parse(self.input.buffer_as_byte_array()).unwrap()),
-                self.back_coords(),
-                self.front_coords()
-            )
-        } else {
-            packed_token!(
-                Token::Float(fast_float::parse(self.input.buffer_as_byte_array()).unwrap()),
-                self.back_coords(),
-                self.front_coords()
-            )
+            let token = match lexical::parse(bytes) {
+                Ok(value) => Token::Integer(value),
+                Err(_) => Token::LazyNumeric(LazyNumeric::new(bytes)),
+            };
+            return packed_token!(token, self.back_coords(), self.front_coords());
+        }
+        match fast_float::parse(bytes) {
+            Ok(value) => {
+                packed_token!(Token::Float(value), self.back_coords(), self.front_coords())
+            }
+            Err(_) => self.invalid_number(),
         }
     }
 
     #[cfg(feature = "lazy-numerics")]
     #[inline]
-    fn parse_numeric(&mut self, integral: bool) -> LexerResult<PackedToken> {
+    fn parse_numeric(&mut self, bytes: &[u8], integral: bool) -> LexerResult<PackedToken<'a>> {
         packed_token!(
-            Token::LazyNumeric(LazyNumeric::new(
-                self.input.buffer_as_byte_array().as_slice()
-            )),
+            Token::LazyNumeric(LazyNumeric::new(bytes)),
             self.back_coords(),
             self.front_coords()
         )
     }
 
-    /// Check that a numeric representation is prefixed correctly.
-    ///
-    /// A few rules here:
-    /// - A leading minus must be followed by a digit
-    /// - A leading minus must be followed by at most one zero before a period
-    /// - Any number > zero can't have a leading zero in the representation
+    /// Match on a null token
     #[inline]
-    fn match_valid_number_prefix(&mut self) -> LexerResult<bool> {
-        let ch = self.back_char();
-        assert!(ch.is_ascii_digit() || ch == '-');
-        match ch {
-            match_minus!() => self
-                .input
-                .advance(false)
-                .map_err(|e| lexer_error!(LexerErrorDetails::EndOfInput))
-                .and_then(|_| self.check_following_minus()),
-            match_zero!() => self
-                .input
-                .advance(false)
-                .map_err(|e| lexer_error!(LexerErrorDetails::EndOfInput))
-                .and_then(|_| self.check_following_zero()),
-            _ => Ok(true),
-        }
-    }
-
-    /// Check for valid characters following a zero
+    fn match_null(&mut self) -> LexerResult<PackedToken<'a>> {
+        self.match_literal("null", Token::Null)
+    }
+
+    /// Match on a true token
     #[inline]
-    fn check_following_zero(&mut self) -> LexerResult<bool> {
-        match self.front_char() {
-            match_period!() => Ok(false),
-            match_digit!() => wrapped_lexer_error!(
-                LexerErrorDetails::InvalidNumericRepresentation(self.current_string()),
-                self.back_coords()
+    fn match_true(&mut self) -> LexerResult<PackedToken<'a>> {
+        self.match_literal("true", Token::Boolean(true))
+    }
+
+    /// Match on a false token
+    #[inline]
+    fn match_false(&mut self) -> LexerResult<PackedToken<'a>> {
+        self.match_literal("false", Token::Boolean(false))
+    }
+
+    /// Match on the remainder of a literal token, the first character of which has already been
+    /// consumed
+    #[inline]
+    fn match_literal(&mut self, literal: &str, token: Token<'a>) -> LexerResult<PackedToken<'a>> {
+        match self.input.match_literal(&literal[1..]) {
+            Ok(_) => packed_token!(token, self.back_coords(), self.front_coords()),
+            Err(ScannerError {
+                details: ScannerErrorDetails::MatchFailed(..),
+                coords: Some(coords),
+            }) => wrapped_lexer_error!(
+                LexerErrorDetails::MatchFailed(String::from(literal), self.current_string()),
+                coords
             ),
-            match_newline!() => {
-                self.input.pushback();
-                Ok(true)
-            }
-            _ => {
-                self.input.pushback();
-                Ok(true)
+            Err(err) => Err(self.scanner_error(err)),
+        }
+    }
+
+    /// Advance to the start of the next JSON5 token, skipping over whitespace and comments. Single
+    /// line comments run up to the next line terminator, and block comments must be closed before
+    /// the end of the input
+    fn advance_over_trivia(&mut self) -> LexerResult<()> {
+        loop {
+            self.advance(true)?;
+            match self.front() {
+                Some(CharWithCoords { ch, .. }) if is_json5_whitespace(ch) => (),
+                Some(CharWithCoords { ch: '/', coords }) => match self.peek_char() {
+                    Some('/') => {
+                        self.input
+                            .advance_while(|ch| !is_line_terminator(ch))
+                            .map_err(|err| self.scanner_error(err))?;
+                    }
+                    Some('*') => self.advance_over_block_comment(coords)?,
+                    _ => return Ok(()),
+                },
+                _ => return Ok(()),
             }
+            self.input.clear();
         }
     }
 
-    /// Check for valid characters following a minus character
-    #[inline]
-    fn check_following_minus(&mut self) -> LexerResult<bool> {
-        match self.front_char() {
-            match_non_zero_digit!() => Ok(true),
-            match_zero!() => self.advance(false).and_then(|_| {
-                if self.front_char() != '.' {
-                    return wrapped_lexer_error!(
-                        LexerErrorDetails::InvalidNumericRepresentation(self.current_string()),
-                        self.back_coords()
+    /// Advance over the remainder of a block comment, the opening slash of which is found at the
+    /// given [Coords]
+    fn advance_over_block_comment(&mut self, start: Coords) -> LexerResult<()> {
+        self.advance(false)?;
+        match self.input.take_until("*/") {
+            Ok(_) => self.advance_n(2, false),
+            Err(ScannerError {
+                details: ScannerErrorDetails::EndOfInput,
+                ..
+            }) => wrapped_lexer_error!(LexerErrorDetails::UnterminatedComment, start),
+            Err(err) => Err(self.scanner_error(err)),
+        }
+    }
+
+    /// Match on any of the values which may only appear within JSON5, or which are lexed
+    /// differently from JSON
+    fn match_json5_value(&mut self, cwc: CharWithCoords) -> LexerResult<PackedToken<'a>> {
+        match cwc.ch {
+            quote @ ('\'' | '\"') => self.match_json5_string(quote),
+            match_plus_minus!() | match_period!() | match_digit!() => self.match_json5_number(),
+            ch if ch == '\\' || is_identifier_start(ch) => self.match_identifier(),
+            ch => wrapped_lexer_error!(LexerErrorDetails::InvalidCharacter(ch), cwc.coords),
+        }
+    }
+
+    /// Match on a JSON5 string, which may be enclosed in either single or double quotes. Escape
+    /// sequences are validated here, and then decoded afterwards. Line terminators must be
+    /// escaped (forming a line continuation), but other control characters needn't be
+    fn match_json5_string(&mut self, quote: char) -> LexerResult<PackedToken<'a>> {
+        let mut escaped = false;
+        loop {
+            let cwc = self.advance_char()?;
+            match cwc.ch {
+                match_escape!() => {
+                    escaped = true;
+                    self.match_json5_escape_sequence(cwc.coords)?;
+                }
+                ch if ch == quote => {
+                    let value = LazyString::new(self.string_contents(1), escaped);
+                    return packed_token!(
+                        Self::json5_string_token(value),
+                        self.back_coords(),
+                        cwc.coords
                     );
                 }
-                Ok(false)
-            }),
-            match_newline!() => {
-                self.input.pushback();
-                Ok(true)
+                ch @ ('\n' | '\r') => {
+                    return wrapped_lexer_error!(
+                        LexerErrorDetails::InvalidCharacter(ch),
+                        cwc.coords
+                    )
+                }
+                _ => (),
             }
-            _ => wrapped_lexer_error!(
-                LexerErrorDetails::InvalidNumericRepresentation(self.current_string()),
-                self.back_coords()
-            ),
         }
     }
 
-    /// Match on a null token
+    /// Strings are decoded straight away, unless lazy strings are enabled
+    #[cfg(not(feature = "lazy-strings"))]
     #[inline]
-    fn match_null(&mut self) -> LexerResult<PackedToken> {
-        self.input
-            .advance_n(3, false)
-            .map_err(|e| lexer_error!(LexerErrorDetails::EndOfInput, self.absolute_position()))
-            .and_then(|_| {
-                if self.current_bytes() == NULL_ASCII {
-                    packed_token!(Token::Null, self.back_coords(), self.front_coords())
-                } else {
-                    wrapped_lexer_error!(
-                        LexerErrorDetails::MatchFailed(String::from("null"), self.current_string()),
-                        adjusted_error_coords(
-                            &self.back_coords(),
-                            &self.current_bytes().as_slice(),
-                            &NULL_ASCII
-                        )
-                    )
-                }
-            })
+    fn json5_string_token(value: LazyString<'a>) -> Token<'a> {
+        Token::Str(value.into_str())
     }
 
-    /// Match on a true token
+    #[cfg(feature = "lazy-strings")]
     #[inline]
-    fn match_true(&mut self) -> LexerResult<PackedToken> {
-        self.advance_n(3, false)
-            .map_err(|e| lexer_error!(LexerErrorDetails::EndOfInput, self.absolute_position()))
-            .and_then(|_| {
-                if self.current_bytes() == TRUE_ASCII {
-                    packed_token!(
-                        Token::Boolean(true),
-                        self.back_coords(),
-                        self.front_coords()
-                    )
-                } else {
-                    wrapped_lexer_error!(
-                        LexerErrorDetails::MatchFailed(String::from("true"), self.current_string()),
-                        adjusted_error_coords(
-                            &self.back_coords(),
-                            &self.current_bytes().as_slice(),
-                            &TRUE_ASCII
-                        )
-                    )
-                }
-            })
+    fn json5_string_token(value: LazyString<'a>) -> Token<'a> {
+        Token::LazyString(value)
     }
 
-    /// Match on a false token
+    /// Validate the remainder of a JSON5 escape sequence, the leading backslash of which is found
+    /// at the given [Coords]. Any character other than a decimal digit, `x` or `u` may be escaped
     #[inline]
-    fn match_false(&mut self) -> LexerResult<PackedToken> {
-        self.advance_n(4, false)
-            .map_err(|e| lexer_error!(LexerErrorDetails::EndOfInput, self.absolute_position()))
-            .and_then(|_| {
-                if self.current_bytes() == FALSE_ASCII {
-                    packed_token!(
-                        Token::Boolean(false),
-                        self.back_coords(),
-                        self.front_coords()
-                    )
-                } else {
-                    wrapped_lexer_error!(
-                        LexerErrorDetails::MatchFailed(
-                            String::from("false"),
-                            self.current_string()
-                        ),
-                        adjusted_error_coords(
-                            &self.back_coords(),
-                            &self.current_bytes().as_slice(),
-                            &FALSE_ASCII
-                        )
-                    )
+    fn match_json5_escape_sequence(&mut self, start: Coords) -> LexerResult<()> {
+        match self.advance_char()?.ch {
+            match_escape_unicode_suffix!() => self.match_unicode_sequence(start).map(|_| ()),
+            'x' => {
+                for _ in 0..2 {
+                    if !self.advance_char()?.ch.is_ascii_hexdigit() {
+                        return self.invalid_escape_sequence(start);
+                    }
+                }
+                Ok(())
+            }
+            '0' if matches!(self.peek_char(), Some(match_digit!())) => {
+                self.invalid_escape_sequence(start)
+            }
+            '1'..='9' => self.invalid_escape_sequence(start),
+            '\r' if self.peek_char() == Some('\n') => self.advance(false),
+            _ => Ok(()),
+        }
+    }
+
+    /// Report an invalid escape sequence, starting at the given [Coords]
+    fn invalid_escape_sequence<T>(&mut self, start: Coords) -> LexerResult<T> {
+        wrapped_lexer_error!(
+            LexerErrorDetails::InvalidEscapeSequence(self.current_string()),
+            start
+        )
+    }
+
+    /// Match on an unquoted JSON5 identifier, which may contain unicode escape sequences. The
+    /// literals `null`, `true` and `false` are produced as the usual tokens
+    fn match_identifier(&mut self) -> LexerResult<PackedToken<'a>> {
+        let mut escaped = false;
+        let mut cwc = self
+            .front()
+            .ok_or(lexer_error!(LexerErrorDetails::EndOfInput))?;
+        let mut start = true;
+        loop {
+            if cwc.ch == match_escape!() {
+                escaped = true;
+                let ch = match self.advance_char()?.ch {
+                    match_escape_unicode_suffix!() => self.match_unicode_sequence(cwc.coords)?,
+                    _ => return self.invalid_escape_sequence(cwc.coords),
+                };
+                if !(is_identifier_part(ch) && (!start || is_identifier_start(ch))) {
+                    return wrapped_lexer_error!(
+                        LexerErrorDetails::InvalidCharacter(ch),
+                        cwc.coords
+                    );
+                }
+            }
+            match self.peek_char() {
+                Some(ch) if ch == match_escape!() || is_identifier_part(ch) => {
+                    cwc = self.advance_char()?;
+                    start = false;
                 }
-            })
+                _ => break,
+            }
+        }
+        let name = LazyString::new(self.input.buffer_as_str(), escaped).into_str();
+        let token = match &*name {
+            "null" => Token::Null,
+            "true" => Token::Boolean(true),
+            "false" => Token::Boolean(false),
+            _ => Token::Identifier(name),
+        };
+        packed_token!(token, self.back_coords(), self.front_coords())
+    }
+
+    /// Match on a JSON5 number, which extends the JSON grammar with an optional plus sign,
+    /// hexadecimal integers, signed `Infinity` and `NaN`, and decimal points which needn't have
+    /// digits on both sides. Errors are reported at the start of the representation
+    fn match_json5_number(&mut self) -> LexerResult<PackedToken<'a>> {
+        let negative = self.front_char() == Some(match_minus!());
+        if matches!(self.front_char(), Some(match_plus_minus!())) {
+            match self.peek_char() {
+                Some('I') if negative => {
+                    return self.match_non_finite("Infinity", f64::NEG_INFINITY)
+                }
+                Some('I') => return self.match_non_finite("Infinity", f64::INFINITY),
+                Some('N') => return self.match_non_finite("NaN", f64::NAN),
+                Some(match_digit!() | match_period!()) => self.advance(false)?,
+                _ => {
+                    return self
+                        .match_required_digit()
+                        .and_then(|_| self.invalid_number())
+                }
+            }
+        }
+        let mut integral = true;
+        match self.front_char() {
+            Some(match_period!()) => {
+                self.match_required_digit()?;
+                self.advance_over_digits()?;
+                integral = false;
+            }
+            Some(match_zero!()) if matches!(self.peek_char(), Some('x' | 'X')) => {
+                self.advance(false)?;
+                let digits = self
+                    .input
+                    .advance_while(|ch| ch.is_ascii_hexdigit())
+                    .map_err(|err| self.scanner_error(err))?;
+                if digits == 0 {
+                    return self.invalid_number();
+                }
+                self.match_json5_terminator()?;
+                return self.parse_hex_numeric(negative);
+            }
+            _ => {
+                if self.front_char() != Some(match_zero!()) {
+                    self.advance_over_digits()?;
+                }
+                if matches!(self.peek_char(), Some(match_period!())) {
+                    self.advance(false)?;
+                    self.advance_over_digits()?;
+                    integral = false;
+                }
+            }
+        }
+        if matches!(self.peek_char(), Some(match_exponent!())) {
+            self.advance(false)?;
+            if matches!(self.peek_char(), Some(match_plus_minus!())) {
+                self.advance(false)?;
+            }
+            self.match_required_digit()?;
+            self.advance_over_digits()?;
+            integral = false;
+        }
+        self.match_json5_terminator()?;
+        let normalised = normalise_number(&self.current_string());
+        self.parse_numeric(normalised.as_bytes(), integral)
+    }
+
+    /// Match on a signed `Infinity` or `NaN`, the sign of which has already been consumed
+    fn match_non_finite(&mut self, literal: &str, value: f64) -> LexerResult<PackedToken<'a>> {
+        self.advance(false)?;
+        let packed = self.match_literal(literal, Token::Float(value))?;
+        self.match_json5_terminator()?;
+        Ok(packed)
+    }
+
+    /// JSON5 numbers may also be terminated by comments, and by any JSON5 whitespace
+    fn match_json5_terminator(&mut self) -> LexerResult<()> {
+        match self.peek_char() {
+            Some(match_numeric_terminator!() | '/') | None => Ok(()),
+            Some(ch) if is_json5_whitespace(ch) => Ok(()),
+            Some(_) => {
+                self.advance(false)?;
+                self.invalid_number()
+            }
+        }
+    }
+
+    /// Hexadecimal integers are rewritten in decimal, and then parsed in the same way as any other
+    /// number.  Integers which are too large to fit into an [i128] are rewritten as floating point
+    /// values
+    fn parse_hex_numeric(&mut self, negative: bool) -> LexerResult<PackedToken<'a>> {
+        let raw = self.current_string();
+        let digits = raw.trim_start_matches(|ch| ch != 'x' && ch != 'X')[1..].chars();
+        let sign = if negative { "-" } else { "" };
+        let magnitude = digits.clone().try_fold(0u128, |value, digit| {
+            value
+                .checked_mul(16)?
+                .checked_add(digit.to_digit(16)? as u128)
+        });
+        match magnitude {
+            Some(magnitude) => {
+                let decimal = format!("{}{}", sign, magnitude);
+                self.parse_numeric(decimal.as_bytes(), true)
+            }
+            None => {
+                let value = digits.fold(0f64, |value, digit| {
+                    value * 16.0 + digit.to_digit(16).unwrap_or(0) as f64
+                });
+                let decimal = format!("{}{:e}", sign, value);
+                self.parse_numeric(decimal.as_bytes(), false)
+            }
+        }
+    }
+}
+
+/// Iterates over the tokens in the input, stopping at the end of the input (without producing a
+/// [Token::EndOfInput]) or after the first error
+impl<'a, I: Scan<'a>> Iterator for Lexer<'a, I> {
+    type Item = LexerResult<PackedToken<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.consume() {
+            Ok((Token::EndOfInput, _)) => {
+                self.finished = true;
+                None
+            }
+            Ok(packed) => Some(Ok(packed)),
+            Err(err) => {
+                self.finished = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<'a, I: Scan<'a>> FusedIterator for Lexer<'a, I> {}
+
+impl<'a, I: Scan<'a>> Lex for Lexer<'a, I> {
+    type Token = Token<'a>;
+    type Error = LexerError;
+
+    fn consume(&mut self) -> LexerResult<PackedToken<'a>> {
+        Lexer::consume(self)
+    }
+
+    fn peek(&mut self) -> LexerResult<&PackedToken<'a>> {
+        Lexer::peek(self)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
     use std::env;
     use std::fs::File;
     use std::io::{BufRead, BufReader};
@@ -762,8 +1109,10 @@ mod tests {
     use chisel_common::{lines_from_relative_file, reader_from_bytes};
     use chisel_decoders::utf8::Utf8Decoder;
 
-    use crate::json::lexer::{Lexer, LexerError, LexerResult};
+    use crate::json::dialect::Dialect;
+    use crate::json::lexer::{Lexer, LexerError, LexerErrorDetails, LexerResult};
     use crate::json::tokens::{PackedToken, Token};
+    use proptest::prelude::*;
 
     #[test]
     fn should_report_position_of_eoi() {
@@ -777,7 +1126,7 @@ mod tests {
                 assert!(err.coords.is_some());
                 assert_eq!(err.coords.unwrap().column, input.len())
             }
-            _ => assert!(false),
+            _ => panic!(),
         }
     }
 
@@ -807,6 +1156,123 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_report_byte_ranges_for_tokens() {
+        let input = "[\"caf\u{e9}\", \n  \"\u{1f600}\", true,-1.5e+3, null]";
+        let mut reader = reader_from_bytes!(input);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut lexer = Lexer::new(&mut decoder);
+        let mut slices = vec![];
+        loop {
+            let (token, span) = lexer.consume().unwrap();
+            if token == Token::EndOfInput {
+                break;
+            }
+            slices.push(&input[span.byte_range()]);
+        }
+        assert_eq!(
+            slices,
+            [
+                "[",
+                "\"caf\u{e9}\"",
+                ",",
+                "\"\u{1f600}\"",
+                ",",
+                "true",
+                ",",
+                "-1.5e+3",
+                ",",
+                "null",
+                "]"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_borrow_string_tokens_from_slices() {
+        let input = "{\"caf\u{e9}\": [1.5, \"\u{1f600}\"],\r\n \"b\": false}";
+        let mut reader = reader_from_bytes!(input);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut streaming = Lexer::new(&mut decoder);
+        let mut slice = Lexer::from_slice(input);
+        loop {
+            let expected = streaming.consume().unwrap();
+            let (token, span) = slice.consume().unwrap();
+            assert_eq!((&token, span), (&expected.0, expected.1));
+            match token {
+                Token::Str(str) => assert!(matches!(str, Cow::Borrowed(_))),
+                Token::LazyString(str) => assert!(matches!(str.into_str(), Cow::Borrowed(_))),
+                Token::EndOfInput => break,
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn should_peek_at_tokens() {
+        let mut lexer = Lexer::from_slice("[true, nul]");
+        assert_eq!(lexer.peek().unwrap().0, Token::StartArray);
+        assert_eq!(lexer.peek().unwrap().0, Token::StartArray);
+        assert_eq!(lexer.consume().unwrap().0, Token::StartArray);
+        let (token, span) = lexer.peek().unwrap().clone();
+        assert_eq!((token, span.start.column), (Token::Boolean(true), 2));
+        assert_eq!(lexer.consume().unwrap(), (Token::Boolean(true), span));
+        assert_eq!(lexer.consume().unwrap().0, Token::Comma);
+
+        // errors are reported by both peek and consume
+        let err = lexer.peek().err().unwrap();
+        let consumed = lexer.consume().err().unwrap();
+        assert_eq!(
+            (consumed.details, consumed.coords),
+            (err.details, err.coords)
+        );
+        assert_eq!(err.coords.unwrap().column, 11);
+    }
+
+    #[test]
+    fn should_iterate_over_tokens() {
+        let input = "{\"a\": [1, 2, 3], \"b\": [4]}";
+        let commas = Lexer::from_slice(input)
+            .filter(|result| matches!(result, Ok((Token::Comma, _))))
+            .count();
+        assert_eq!(commas, 3);
+        let numeric = if cfg!(feature = "lazy-numerics") {
+            "Lazy("
+        } else {
+            "Integer("
+        };
+        let numbers: Vec<String> = Lexer::from_slice(input)
+            .filter_map(|result| result.ok().map(|(token, _)| token.to_string()))
+            .filter(|token| token.starts_with(numeric))
+            .collect();
+        assert_eq!(numbers, [1, 2, 3, 4].map(|n| format!("{}{})", numeric, n)));
+
+        let mut reader = reader_from_bytes!(input);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let tokens: LexerResult<Vec<PackedToken>> = Lexer::new(&mut decoder).collect();
+        assert_eq!(tokens.unwrap().len(), 17);
+    }
+
+    #[test]
+    fn should_fuse_after_the_first_error() {
+        let mut lexer = Lexer::from_slice("[1, ?, 2]");
+        assert_eq!(
+            lexer.peek_next().unwrap().as_ref().unwrap().0,
+            Token::StartArray
+        );
+        assert_eq!(lexer.next().unwrap().unwrap().0, Token::StartArray);
+        assert_eq!(lexer.by_ref().take(2).filter(Result::is_ok).count(), 2);
+        assert!(lexer.peek_next().unwrap().is_err());
+        assert!(lexer.next().unwrap().is_err());
+        assert!(lexer.peek_next().is_none());
+        assert!(lexer.next().is_none());
+
+        let mut lexer = Lexer::from_slice("[]");
+        assert_eq!(lexer.by_ref().count(), 2);
+        assert!(lexer.peek_next().is_none());
+        assert!(lexer.next().is_none());
+    }
+
     #[test]
     fn should_parse_null_and_booleans() {
         let mut reader = reader_from_bytes!("null true    falsetruefalse");
@@ -841,14 +1307,348 @@ mod tests {
                 let mut decoder = Utf8Decoder::new(&mut reader);
                 let mut lexer = Lexer::new(&mut decoder);
                 let token = lexer.consume().unwrap();
-                match token.0 {
-                    Token::Str(str) => {
-                        assert_eq!(str, l)
+                match string_value(token.0) {
+                    Some(str) => {
+                        assert_eq!(lexer.raw_text(), l);
+                        assert!(!str.contains('\\'));
+                        assert_eq!(str.chars().count() + 2, l.chars().count() - escapes(&l));
                     }
-                    _ => panic!(),
+                    None => panic!(),
+                }
+            }
+        }
+    }
+
+    /// The decoded value of a string token, whether or not lazy strings are enabled
+    fn string_value(token: Token) -> Option<Cow<str>> {
+        match token {
+            Token::Str(str) => Some(str),
+            Token::LazyString(str) => Some(str.into_str()),
+            _ => None,
+        }
+    }
+
+    /// The number of characters taken up by escape sequences, less those they represent
+    fn escapes(str: &str) -> usize {
+        let mut count = 0;
+        let mut chars = str.chars();
+        while let Some(ch) = chars.next() {
+            if ch == '\\' {
+                if let Some('u') = chars.next() {
+                    chars.nth(3);
+                    count += 4;
                 }
+                count += 1;
             }
         }
+        count
+    }
+
+    #[test]
+    fn should_decode_escape_sequences() {
+        let input = r#"["a\"b\\c\/d\b\f\n\r\t", "\u00e9\u55fe", "\uD83D\uDE00!", "plain"]"#;
+        let mut reader = reader_from_bytes!(input);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let streaming: Vec<Token> = Lexer::new(&mut decoder).map(|r| r.unwrap().0).collect();
+        let slice: Vec<Token> = Lexer::from_slice(input).map(|r| r.unwrap().0).collect();
+        assert_eq!(streaming, slice);
+        let strings: Vec<Cow<str>> = slice.into_iter().filter_map(string_value).collect();
+        assert_eq!(
+            strings,
+            vec![
+                "a\"b\\c/d\u{8}\u{c}\n\r\t",
+                "\u{e9}\u{55fe}",
+                "\u{1f600}!",
+                "plain"
+            ]
+        );
+        assert!(matches!(strings[0], Cow::Owned(_)));
+        assert!(matches!(strings[3], Cow::Borrowed(_)));
+
+        let mut lexer = Lexer::from_slice(r#"  "a\u0041\u00""#);
+        lexer.consume().unwrap_err();
+        let mut lexer = Lexer::from_slice(r#""\n\u0041""#);
+        assert_eq!(
+            string_value(lexer.consume().unwrap().0),
+            Some(Cow::Borrowed("\nA"))
+        );
+        assert_eq!(lexer.raw_text(), r#""\n\u0041""#);
+    }
+
+    #[cfg(feature = "lazy-strings")]
+    #[test]
+    fn should_defer_decoding_of_lazy_strings() {
+        let tokens: LexerResult<Vec<PackedToken>> =
+            Lexer::from_slice(r#"["plain", "\u00e9\n"]"#).collect();
+        let tokens = tokens.unwrap();
+        match (&tokens[1].0, &tokens[3].0) {
+            (Token::LazyString(plain), Token::LazyString(escaped)) => {
+                assert!(!plain.has_escapes());
+                assert!(matches!(plain.to_str(), Cow::Borrowed("plain")));
+                assert!(escaped.has_escapes());
+                assert_eq!(escaped.raw(), r#"\u00e9\n"#);
+                assert_eq!(escaped.to_str(), "\u{e9}\n");
+            }
+            _ => panic!("expected lazy strings"),
+        }
+        let err = Lexer::from_slice(r#"["\q"]"#)
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(err.coords.unwrap().column, 3);
+    }
+
+    #[test]
+    fn should_reject_unpaired_surrogates() {
+        for (input, column) in [
+            (r#"["\uD83D"]"#, 3),
+            (r#"["ab\uD83Dx"]"#, 5),
+            (r#"["\uD83D\u0041"]"#, 3),
+            (r#"["\uDE00\uD83D"]"#, 3),
+            (r#"["\uD83D\uD83D"]"#, 3),
+        ] {
+            let err = Lexer::from_slice(input).find_map(Result::err).unwrap();
+            assert!(
+                matches!(
+                    err.details,
+                    LexerErrorDetails::InvalidUnicodeEscapeSequence(_)
+                ),
+                "{}",
+                input
+            );
+            assert_eq!(err.coords.unwrap().column, column, "{}", input);
+        }
+        let err = Lexer::from_slice(r#""\q""#).consume().unwrap_err();
+        assert!(matches!(
+            err.details,
+            LexerErrorDetails::InvalidEscapeSequence(_)
+        ));
+        assert_eq!(err.coords.unwrap().column, 2);
+    }
+
+    #[test]
+    fn should_reject_unescaped_control_characters() {
+        for (input, ch, column) in [
+            ("[\"a\tb\"]", '\t', 4),
+            ("[\"\u{0}\"]", '\u{0}', 3),
+            ("[\"\\n\n\"]", '\n', 5),
+        ] {
+            let err = Lexer::from_slice(input).find_map(Result::err).unwrap();
+            assert_eq!(
+                err.details,
+                LexerErrorDetails::InvalidCharacter(ch),
+                "{:?}",
+                input
+            );
+            assert_eq!(err.coords.unwrap().column, column, "{:?}", input);
+        }
+        let tokens: LexerResult<Vec<PackedToken>> = Lexer::from_slice("[\"\u{7f}\"]").collect();
+        assert_eq!(
+            string_value(tokens.unwrap().remove(1).0),
+            Some(Cow::Borrowed("\u{7f}"))
+        );
+    }
+
+    #[test]
+    fn should_only_skip_json_whitespace() {
+        let tokens: LexerResult<Vec<PackedToken>> = Lexer::from_slice(" \t[\r\n1 ]\n").collect();
+        assert_eq!(tokens.unwrap().len(), 3);
+        for input in ["[\u{a0}1]", "[\u{c}1]", "[1\u{2028}]"] {
+            let err = Lexer::from_slice(input).find_map(Result::err).unwrap();
+            assert!(
+                matches!(
+                    err.details,
+                    LexerErrorDetails::InvalidCharacter(_)
+                        | LexerErrorDetails::InvalidNumericRepresentation(_)
+                ),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    /// A description of a token which doesn't depend on whether lazy numerics or strings are enabled
+    fn describe(token: Token) -> String {
+        match token {
+            Token::Integer(value) => format!("Number({})", value),
+            Token::Float(value) => format!("Number({})", value),
+            Token::LazyNumeric(lazy) => format!("Number({})", lazy.to_float().unwrap()),
+            token => match string_value(token.clone()) {
+                Some(str) => format!("String({})", str),
+                None => token.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn should_lex_json5() {
+        let input = "// leading comment\n{unquoted: 'it\\'s', $key_2: [0x1F, -0XfF, +1, .5, 5., \
+            -Infinity, +NaN, Infinity,], /* block\n comment */ 'line\\\n continued': \
+            \"tab\there\", caf\u{e9}: null, \\u0061b: true,}\u{a0}\u{feff}// trailing";
+        let expected = [
+            "StartObject",
+            "Identifier(unquoted)",
+            "Colon",
+            "String(it's)",
+            "Comma",
+            "Identifier($key_2)",
+            "Colon",
+            "StartArray",
+            "Number(31)",
+            "Comma",
+            "Number(-255)",
+            "Comma",
+            "Number(1)",
+            "Comma",
+            "Number(0.5)",
+            "Comma",
+            "Number(5)",
+            "Comma",
+            "Number(-inf)",
+            "Comma",
+            "Number(NaN)",
+            "Comma",
+            "Identifier(Infinity)",
+            "Comma",
+            "EndArray",
+            "Comma",
+            "String(line continued)",
+            "Colon",
+            "String(tab\there)",
+            "Comma",
+            "Identifier(caf\u{e9})",
+            "Colon",
+            "Null",
+            "Comma",
+            "Identifier(ab)",
+            "Colon",
+            "Boolean(true)",
+            "Comma",
+            "EndObject",
+        ];
+        let mut reader = reader_from_bytes!(input);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let streaming: Vec<String> = Lexer::new(&mut decoder)
+            .with_dialect(Dialect::Json5)
+            .map(|r| describe(r.unwrap().0))
+            .collect();
+        let slice: Vec<String> = Lexer::from_slice(input)
+            .with_dialect(Dialect::Json5)
+            .map(|r| describe(r.unwrap().0))
+            .collect();
+        assert_eq!(streaming, expected);
+        assert_eq!(slice, expected);
+
+        // none of the extensions are allowed in strict JSON
+        for input in [
+            "[1] // comment",
+            "['a']",
+            "{a: 1}",
+            "[+1]",
+            "[.5]",
+            "[0x1]",
+            "[NaN]",
+        ] {
+            assert!(Lexer::from_slice(input).any(|r| r.is_err()), "{}", input);
+        }
+    }
+
+    #[test]
+    fn should_lex_json5_identifiers_by_unicode_category() {
+        for key in [
+            "a\u{301}b",
+            "a\u{203f}b",
+            "\u{2160}x",
+            "$\u{663}",
+            "a\u{200d}",
+        ] {
+            let input = format!("{{{}: 1}}", key);
+            let tokens: Vec<String> = Lexer::from_slice(&input)
+                .with_dialect(Dialect::Json5)
+                .map(|r| describe(r.unwrap().0))
+                .collect();
+            assert_eq!(tokens[1], format!("Identifier({})", key));
+        }
+        for input in ["{a\u{b2}: 1}", "{\u{301}a: 1}", "{\u{663}: 1}"] {
+            assert!(
+                Lexer::from_slice(input)
+                    .with_dialect(Dialect::Json5)
+                    .any(|r| r.is_err()),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn should_report_json5_errors_precisely() {
+        for (input, column) in [
+            ("[1, /* open", 5),
+            ("[1, / 2]", 5),
+            ("['a\\1']", 4),
+            ("['a\\x4']", 4),
+            ("['a\nb']", 4),
+            ("[0x]", 2),
+            ("[+]", 2),
+            ("[01]", 2),
+            ("[.e1]", 2),
+            ("[1.5.]", 2),
+            ("[+Infinit]", 10),
+            ("{a\\u0020b: 1}", 3),
+            ("[#]", 2),
+        ] {
+            let err = Lexer::from_slice(input)
+                .with_dialect(Dialect::Json5)
+                .find_map(Result::err)
+                .unwrap();
+            assert_eq!(err.coords.unwrap().column, column, "{}", input);
+        }
+        let err = Lexer::from_slice("\n  /* open")
+            .with_dialect(Dialect::Json5)
+            .consume()
+            .unwrap_err();
+        assert_eq!(err.details, LexerErrorDetails::UnterminatedComment);
+        assert_eq!(
+            (err.coords.unwrap().line, err.coords.unwrap().column),
+            (2, 3)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn should_never_panic_on_arbitrary_input(bytes in proptest::collection::vec(any::<u8>(), 0..256)) {
+            let mut reader = BufReader::new(bytes.as_slice());
+            let mut decoder = Utf8Decoder::new(&mut reader);
+            Lexer::new(&mut decoder).for_each(drop);
+            Lexer::from_slice(&String::from_utf8_lossy(&bytes)).for_each(drop);
+        }
+
+        #[test]
+        fn should_never_panic_on_json_like_input(input in "[\\[\\]{}:,\"\\\\u0-9a-fA-F.+\\-etrns \t\n\u{0}\u{e9}]{0,128}") {
+            let mut reader = BufReader::new(input.as_bytes());
+            let mut decoder = Utf8Decoder::new(&mut reader);
+            let streamed: Vec<String> = Lexer::new(&mut decoder)
+                .map(|result| format!("{:?}", result.map(|packed| packed.0)))
+                .collect();
+            let sliced: Vec<String> = Lexer::from_slice(&input)
+                .map(|result| format!("{:?}", result.map(|packed| packed.0)))
+                .collect();
+            prop_assert_eq!(streamed, sliced);
+        }
+
+        #[test]
+        fn should_never_panic_on_json5_like_input(input in "[\\[\\]{}:,\"'\\\\/*u0-9a-fA-FxX.+\\-IfinityNa$_ \t\r\n\u{0}\u{e9}\u{a0}\u{2028}]{0,128}") {
+            let mut reader = BufReader::new(input.as_bytes());
+            let mut decoder = Utf8Decoder::new(&mut reader);
+            let streamed: Vec<String> = Lexer::new(&mut decoder)
+                .with_dialect(Dialect::Json5)
+                .map(|result| format!("{:?}", result.map(|packed| packed.0)))
+                .collect();
+            let sliced: Vec<String> = Lexer::from_slice(&input)
+                .with_dialect(Dialect::Json5)
+                .map(|result| format!("{:?}", result.map(|packed| packed.0)))
+                .collect();
+            prop_assert_eq!(streamed, sliced);
+        }
     }
 
     #[test]
@@ -892,7 +1692,7 @@ mod tests {
                         );
                     }
                     Token::LazyNumeric(lazy) => {
-                        let value: f64 = lazy.into();
+                        let value = lazy.to_float().unwrap();
                         assert_eq!(
                             Token::Float(value),
                             Token::Float(fast_float::parse(l.replace(',', "")).unwrap())
@@ -953,7 +1753,7 @@ mod tests {
         let mut reader = reader_from_bytes!("true farse");
         let mut decoder = Utf8Decoder::new(&mut reader);
         let mut lexer = Lexer::new(&mut decoder);
-        let mut results: Vec<LexerResult<PackedToken>> = vec![];
+        let mut results: Vec<LexerResult<PackedToken<'_>>> = vec![];
         for _ in 1..=2 {
             results.push(lexer.consume());
         }
@@ -963,13 +1763,8 @@ mod tests {
         assert!(results[1].is_err());
 
         // check that we've located a boolean in the correct position
-        if results[0].is_ok() {
-            match &results[0] {
-                Ok(packed) => {
-                    assert_eq!((*packed).1.start.column, 1)
-                }
-                Err(_) => {}
-            }
+        if let Ok(packed) = &results[0] {
+            assert_eq!(packed.1.start.column, 1)
         }
 
         // check that the dodgy boolean has been picked up at the correct location
@@ -977,7 +1772,11 @@ mod tests {
             match &results[1] {
                 Ok(_) => {}
                 Err(err) => {
-                    assert_eq!(err.coords.unwrap().column, 8)
+                    assert_eq!(err.coords.unwrap().column, 8);
+                    assert_eq!(
+                        err.details,
+                        LexerErrorDetails::MatchFailed("false".into(), "far".into())
+                    );
                 }
             }
         }
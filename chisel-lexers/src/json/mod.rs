@@ -1,3 +1,5 @@
+pub mod dialect;
 pub mod lexer;
 pub mod numerics;
+pub mod strings;
 pub mod tokens;
@@ -1,6 +1,113 @@
+use std::fmt::{Display, Formatter};
+use std::iter;
+
+#[cfg(feature = "big-decimal")]
+pub use bigdecimal::BigDecimal;
+
+/// Enumeration of the errors which may occur when converting a [LazyNumeric]
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumericError {
+    /// The raw bytes don't hold a valid numeric representation
+    InvalidRepresentation(String),
+    /// The value is valid, but lies outside the range of the target type
+    OutOfRange(String),
+    /// The value lies within the range of the target type, but can't be represented exactly by it
+    PrecisionLoss(String),
+}
+
+impl Display for NumericError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumericError::InvalidRepresentation(repr) => {
+                write!(f, "invalid number representation: \"{}\"", repr)
+            }
+            NumericError::OutOfRange(repr) => {
+                write!(f, "number out of range for the target type: \"{}\"", repr)
+            }
+            NumericError::PrecisionLoss(repr) => {
+                write!(
+                    f,
+                    "number can't be converted without loss of precision: \"{}\"",
+                    repr
+                )
+            }
+        }
+    }
+}
+
+/// The exact value of a numeric representation, normalised so that two representations of the same
+/// value (e.g. `1.50` and `15e-1`) compare as equal. The value is given by `digits * 10^exponent`
+#[derive(Debug, PartialEq)]
+struct Decimal {
+    /// Whether or not there was a leading minus sign
+    negative: bool,
+    /// The significant decimal digits, without leading or trailing zeros. Empty for zero
+    digits: Vec<u8>,
+    /// The power of ten to scale the digits by
+    exponent: i64,
+}
+
+impl Decimal {
+    /// Parse the JSON representation of a number, without any rounding. Returns [None] if the
+    /// bytes don't follow the JSON number grammar
+    fn parse(raw: &[u8]) -> Option<Self> {
+        let mut bytes = raw.iter().copied().peekable();
+        let negative = bytes.next_if_eq(&b'-').is_some();
+        let mut digits = vec![];
+        while let Some(b) = bytes.next_if(u8::is_ascii_digit) {
+            digits.push(b - b'0');
+        }
+        if digits.is_empty() {
+            return None;
+        }
+        let mut exponent: i64 = 0;
+        if bytes.next_if_eq(&b'.').is_some() {
+            let integral = digits.len();
+            while let Some(b) = bytes.next_if(u8::is_ascii_digit) {
+                digits.push(b - b'0');
+            }
+            if digits.len() == integral {
+                return None;
+            }
+            exponent = integral as i64 - digits.len() as i64;
+        }
+        if bytes.next_if(|b| *b == b'e' || *b == b'E').is_some() {
+            let sign: i64 = match bytes.next_if(|b| *b == b'+' || *b == b'-') {
+                Some(b'-') => -1,
+                _ => 1,
+            };
+            let mut explicit: Option<i64> = None;
+            while let Some(b) = bytes.next_if(u8::is_ascii_digit) {
+                let value = explicit.unwrap_or(0);
+                explicit = Some(value.saturating_mul(10).saturating_add((b - b'0') as i64));
+            }
+            exponent = exponent.saturating_add(sign * explicit?);
+        }
+        if bytes.next().is_some() {
+            return None;
+        }
+        let leading = digits.iter().take_while(|d| **d == 0).count();
+        digits.drain(..leading);
+        let trailing = digits.iter().rev().take_while(|d| **d == 0).count();
+        digits.truncate(digits.len() - trailing);
+        if digits.is_empty() {
+            exponent = 0;
+        } else {
+            exponent = exponent.saturating_add(trailing as i64);
+        }
+        Some(Decimal {
+            negative,
+            digits,
+            exponent,
+        })
+    }
+}
+
 /// A lazy numeric type which allows for raw bytes associated with a floating point value
 /// to be stashed away, and then only parsed when needed.  Structs of this type may be returned
-/// by the lexer if the associated feature is enabled
+/// by the lexer if the associated feature is enabled, in which case the exact representation found
+/// in the input is preserved, and may be converted into a number of different types through the
+/// checked conversions below
 #[derive(Debug, Clone, PartialEq)]
 pub struct LazyNumeric {
     /// Raw bytes associated with the value
@@ -15,20 +122,200 @@ impl LazyNumeric {
         }
     }
 
-    /// Convenience method for conversion into an [f64]
-    pub fn to_float(&self) -> f64 {
-        fast_float::parse(self.raw.as_slice()).unwrap()
+    /// Convenience method for conversion into an [f64]. Fails if the raw bytes aren't a valid
+    /// numeric representation, which can only happen if the value wasn't produced by the lexer.
+    /// The result is rounded to the nearest [f64] where required, see [LazyNumeric::as_f64_lossless]
+    /// for a conversion which won't round
+    pub fn to_float(&self) -> Result<f64, NumericError> {
+        fast_float::parse(self.raw.as_slice()).map_err(|_| self.invalid())
+    }
+
+    /// Convert into a [u64], failing if the value has a fractional part or is out of range
+    pub fn as_u64(&self) -> Result<u64, NumericError> {
+        u64::try_from(self.as_i128()?).map_err(|_| NumericError::OutOfRange(self.to_string()))
+    }
+
+    /// Convert into an [i128], failing if the value has a fractional part or is out of range.
+    /// Representations such as `1.0` or `25e-1` are accepted, as long as the value is integral
+    pub fn as_i128(&self) -> Result<i128, NumericError> {
+        let decimal = self.decimal()?;
+        if decimal.exponent < 0 {
+            return Err(NumericError::PrecisionLoss(self.to_string()));
+        }
+        if (decimal.digits.len() as i64).saturating_add(decimal.exponent) > 39 {
+            return Err(NumericError::OutOfRange(self.to_string()));
+        }
+        let zeros = iter::repeat(0).take(decimal.exponent as usize);
+        decimal
+            .digits
+            .iter()
+            .copied()
+            .chain(zeros)
+            .try_fold(0i128, |value, digit| {
+                let value = value.checked_mul(10)?;
+                if decimal.negative {
+                    value.checked_sub(digit as i128)
+                } else {
+                    value.checked_add(digit as i128)
+                }
+            })
+            .ok_or_else(|| NumericError::OutOfRange(self.to_string()))
+    }
+
+    /// Convert into an [f64], failing if the value can't be represented exactly, rather than
+    /// rounding it to the nearest [f64] in the way that [LazyNumeric::to_float] does
+    pub fn as_f64_lossless(&self) -> Result<f64, NumericError> {
+        let decimal = self.decimal()?;
+        let value = self.to_float()?;
+        if value.is_infinite() {
+            return Err(NumericError::OutOfRange(self.to_string()));
+        }
+        // every finite f64 has an exact decimal expansion of at most 767 significant digits
+        match Decimal::parse(format!("{:.767e}", value).as_bytes()) {
+            Some(exact) if exact == decimal => Ok(value),
+            _ => Err(NumericError::PrecisionLoss(self.to_string())),
+        }
+    }
+
+    /// Convert into a [BigDecimal], which holds the exact value regardless of its magnitude or
+    /// precision. Only fails for exponents which are too large to be represented at all
+    #[cfg(feature = "big-decimal")]
+    pub fn as_big_decimal(&self) -> Result<BigDecimal, NumericError> {
+        self.decimal()?;
+        std::str::from_utf8(&self.raw)
+            .ok()
+            .and_then(|s| s.parse::<BigDecimal>().ok())
+            .ok_or_else(|| NumericError::OutOfRange(self.to_string()))
+    }
+
+    /// The exact value of the raw representation
+    fn decimal(&self) -> Result<Decimal, NumericError> {
+        Decimal::parse(&self.raw).ok_or_else(|| self.invalid())
+    }
+
+    fn invalid(&self) -> NumericError {
+        NumericError::InvalidRepresentation(self.to_string())
+    }
+}
+/// The raw representation is displayed, exactly as it was found in the input
+impl Display for LazyNumeric {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.raw))
     }
 }
 
-impl From<&LazyNumeric> for f64 {
-    fn from(value: &LazyNumeric) -> Self {
-        fast_float::parse(value.raw.as_slice()).unwrap()
+impl TryFrom<&LazyNumeric> for f64 {
+    type Error = NumericError;
+
+    fn try_from(value: &LazyNumeric) -> Result<Self, Self::Error> {
+        value.to_float()
+    }
+}
+
+impl TryFrom<LazyNumeric> for f64 {
+    type Error = NumericError;
+
+    fn try_from(value: LazyNumeric) -> Result<Self, Self::Error> {
+        value.to_float()
     }
 }
 
-impl From<LazyNumeric> for f64 {
-    fn from(value: LazyNumeric) -> Self {
-        fast_float::parse(value.raw.as_slice()).unwrap()
+#[cfg(test)]
+mod tests {
+    use crate::json::numerics::{LazyNumeric, NumericError};
+
+    #[test]
+    fn should_report_invalid_representations() {
+        let lazy = LazyNumeric::new(b"-12.5e1");
+        assert_eq!(lazy.to_float(), Ok(-125.0));
+        assert_eq!(f64::try_from(&lazy), Ok(-125.0));
+        assert_eq!(lazy.to_string(), "-12.5e1");
+        assert_eq!(
+            LazyNumeric::new(b"1x").to_float(),
+            Err(NumericError::InvalidRepresentation("1x".into()))
+        );
+        assert!(f64::try_from(LazyNumeric::new(b"\xff")).is_err());
+    }
+
+    #[test]
+    fn should_convert_integers_exactly() {
+        let lazy = |raw: &str| LazyNumeric::new(raw.as_bytes());
+        assert_eq!(lazy("18446744073709551615").as_u64(), Ok(u64::MAX));
+        assert_eq!(lazy("-0").as_u64(), Ok(0));
+        assert_eq!(lazy("1.50e2").as_u64(), Ok(150));
+        assert_eq!(
+            lazy("-170141183460469231731687303715884105728").as_i128(),
+            Ok(i128::MIN)
+        );
+        assert_eq!(lazy("0.000e99999999999999999999").as_i128(), Ok(0));
+        assert_eq!(
+            lazy("18446744073709551616").as_u64(),
+            Err(NumericError::OutOfRange("18446744073709551616".into()))
+        );
+        assert_eq!(
+            lazy("-1").as_u64(),
+            Err(NumericError::OutOfRange("-1".into()))
+        );
+        assert_eq!(
+            lazy("1e39").as_i128(),
+            Err(NumericError::OutOfRange("1e39".into()))
+        );
+        assert_eq!(
+            lazy("12.5").as_i128(),
+            Err(NumericError::PrecisionLoss("12.5".into()))
+        );
+        assert_eq!(
+            lazy("1e-99999999999999999999").as_u64(),
+            Err(NumericError::PrecisionLoss(
+                "1e-99999999999999999999".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn should_report_precision_loss_for_floats() {
+        let lazy = |raw: &str| LazyNumeric::new(raw.as_bytes());
+        assert_eq!(lazy("0.5").as_f64_lossless(), Ok(0.5));
+        assert_eq!(lazy("-1.25E+2").as_f64_lossless(), Ok(-125.0));
+        assert_eq!(
+            lazy("9007199254740992").as_f64_lossless(),
+            Ok(9007199254740992.0)
+        );
+        assert_eq!(
+            lazy("5e-324").as_f64_lossless(),
+            Err(NumericError::PrecisionLoss("5e-324".into()))
+        );
+        assert_eq!(
+            lazy("0.1").as_f64_lossless(),
+            Err(NumericError::PrecisionLoss("0.1".into()))
+        );
+        assert_eq!(
+            lazy("9007199254740993").as_f64_lossless(),
+            Err(NumericError::PrecisionLoss("9007199254740993".into()))
+        );
+        assert_eq!(
+            lazy("1e400").as_f64_lossless(),
+            Err(NumericError::OutOfRange("1e400".into()))
+        );
+        assert_eq!(lazy("0.1").to_float(), Ok(0.1));
+    }
+
+    #[cfg(feature = "big-decimal")]
+    #[test]
+    fn should_convert_into_big_decimals() {
+        use crate::json::numerics::BigDecimal;
+        use std::str::FromStr;
+        let lazy = LazyNumeric::new(b"-123456789012345678901234567890.000000000000000000001");
+        assert_eq!(
+            lazy.as_big_decimal(),
+            Ok(
+                BigDecimal::from_str("-123456789012345678901234567890.000000000000000000001")
+                    .unwrap()
+            )
+        );
+        assert!(LazyNumeric::new(b"1e99999999999999999999")
+            .as_big_decimal()
+            .is_err());
+        assert!(LazyNumeric::new(b"1.").as_big_decimal().is_err());
     }
 }
@@ -0,0 +1,190 @@
+use std::borrow::Cow;
+use std::char::REPLACEMENT_CHARACTER;
+use std::fmt::{Display, Formatter};
+
+use crate::json::dialect::is_line_terminator;
+
+/// A lazy string type which holds the raw contents of a string (less the enclosing quotes), along
+/// with a flag indicating whether any escape sequences were found within it.  Escape sequences are
+/// only decoded when the value is actually read, and strings without any escapes are served
+/// directly from the raw contents. Structs of this type may be returned by the lexer if the
+/// associated feature is enabled
+#[derive(Debug, Clone, PartialEq)]
+pub struct LazyString<'a> {
+    /// The raw contents of the string, with any escape sequences left intact
+    raw: Cow<'a, str>,
+    /// Whether or not the raw contents contain any escape sequences
+    escaped: bool,
+}
+
+impl<'a> LazyString<'a> {
+    /// Create a new struct given the raw contents of a string, and whether or not those contents
+    /// contain any escape sequences
+    pub fn new(raw: Cow<'a, str>, escaped: bool) -> Self {
+        LazyString { raw, escaped }
+    }
+
+    /// The raw contents of the string, exactly as they were found in the input
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Whether or not the raw contents contain any escape sequences which need decoding
+    pub fn has_escapes(&self) -> bool {
+        self.escaped
+    }
+
+    /// The decoded value of the string, which is borrowed from the raw contents if there aren't
+    /// any escape sequences to decode. Both JSON and JSON5 escape sequences are understood.
+    /// Malformed escape sequences (which can only be present if the value wasn't produced by the
+    /// lexer) are decoded as [REPLACEMENT_CHARACTER]
+    pub fn to_str(&self) -> Cow<'_, str> {
+        if self.escaped {
+            Cow::Owned(decode(&self.raw))
+        } else {
+            Cow::Borrowed(&self.raw)
+        }
+    }
+
+    /// Convert into the decoded value of the string. If there aren't any escape sequences to
+    /// decode, then a string borrowed from the input stays borrowed
+    pub fn into_str(self) -> Cow<'a, str> {
+        if self.escaped {
+            Cow::Owned(decode(&self.raw))
+        } else {
+            self.raw
+        }
+    }
+
+    /// A copy of the string which borrows the raw contents from this one
+    pub fn as_borrowed(&self) -> LazyString<'_> {
+        LazyString::new(Cow::Borrowed(&self.raw), self.escaped)
+    }
+
+    /// Convert into a string which doesn't borrow from the underlying input
+    pub fn into_owned(self) -> LazyString<'static> {
+        LazyString::new(Cow::Owned(self.raw.into_owned()), self.escaped)
+    }
+}
+
+/// The decoded value is displayed
+impl<'a> Display for LazyString<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+/// Map the character following a backslash onto the [char] it represents, for all escape
+/// sequences other than unicode escape sequences
+#[inline]
+pub(crate) fn unescape(ch: char) -> Option<char> {
+    match ch {
+        '\"' => Some('\"'),
+        '\\' => Some('\\'),
+        '/' => Some('/'),
+        'b' => Some('\u{8}'),
+        'f' => Some('\u{c}'),
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        _ => None,
+    }
+}
+
+/// Decode all the escape sequences found within some raw string contents. As well as those defined
+/// by JSON, this covers the additional escape sequences and line continuations allowed by JSON5
+fn decode(raw: &str) -> String {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            decoded.push(ch);
+            continue;
+        }
+        let ch = match chars.next() {
+            Some('u') => decode_unicode_sequence(&mut chars),
+            Some('x') => decode_hex_digits(&mut chars, 2).and_then(char::from_u32),
+            Some('\r') => {
+                chars.next_if_eq(&'\n');
+                continue;
+            }
+            Some(ch) if is_line_terminator(ch) => continue,
+            Some(ch) => unescape(ch).or_else(|| unescape_json5(ch)),
+            None => None,
+        };
+        decoded.push(ch.unwrap_or(REPLACEMENT_CHARACTER));
+    }
+    decoded
+}
+
+/// Map the character following a backslash onto the [char] it represents, for the single
+/// character escape sequences which are only allowed by JSON5. Any character which doesn't have a
+/// special meaning represents itself
+fn unescape_json5(ch: char) -> Option<char> {
+    match ch {
+        '\'' => Some('\''),
+        'v' => Some('\u{b}'),
+        '0' => Some('\0'),
+        '1'..='9' | 'x' | 'u' => None,
+        ch => Some(ch),
+    }
+}
+
+/// Decode the remainder of a unicode escape sequence, combining surrogate pairs
+fn decode_unicode_sequence(chars: &mut impl Iterator<Item = char>) -> Option<char> {
+    let high = decode_hex_digits(chars, 4)?;
+    if (0xd800..=0xdbff).contains(&high) {
+        if chars.next()? != '\\' || chars.next()? != 'u' {
+            return None;
+        }
+        return match decode_hex_digits(chars, 4)? {
+            low @ 0xdc00..=0xdfff => {
+                char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+            }
+            _ => None,
+        };
+    }
+    char::from_u32(high)
+}
+
+/// Decode a fixed number of hex digits, such as the four which make up a single UTF-16 code unit
+fn decode_hex_digits(chars: &mut impl Iterator<Item = char>, count: usize) -> Option<u32> {
+    let mut code = 0;
+    for _ in 0..count {
+        code = code * 16 + chars.next()?.to_digit(16)?;
+    }
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::json::strings::LazyString;
+
+    #[test]
+    fn should_decode_only_when_escaped() {
+        let plain = LazyString::new(Cow::Borrowed("plain \u{e9}"), false);
+        assert!(matches!(plain.to_str(), Cow::Borrowed("plain \u{e9}")));
+        assert!(matches!(plain.into_str(), Cow::Borrowed("plain \u{e9}")));
+
+        let escaped = LazyString::new(Cow::Borrowed(r#"a\"b\u00e9\n\ud83d\ude00"#), true);
+        assert_eq!(escaped.raw(), r#"a\"b\u00e9\n\ud83d\ude00"#);
+        assert_eq!(escaped.to_str(), "a\"b\u{e9}\n\u{1f600}");
+        assert_eq!(escaped.to_string(), "a\"b\u{e9}\n\u{1f600}");
+        assert!(matches!(escaped.into_str(), Cow::Owned(_)));
+    }
+
+    #[test]
+    fn should_replace_malformed_escape_sequences() {
+        let malformed = LazyString::new(Cow::Borrowed(r#"\1\ud800x\u12"#), true);
+        assert_eq!(malformed.to_str(), "\u{fffd}\u{fffd}\u{fffd}");
+    }
+
+    #[test]
+    fn should_decode_json5_escape_sequences() {
+        let raw = "\\'\\v\\x41\\q\\0a\\\r\nb\\\u{2028}c";
+        let escaped = LazyString::new(Cow::Borrowed(raw), true);
+        assert_eq!(escaped.to_str(), "'\u{b}Aq\0abc");
+    }
+}
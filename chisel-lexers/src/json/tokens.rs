@@ -1,10 +1,13 @@
 use crate::json::numerics::LazyNumeric;
+use crate::json::strings::LazyString;
 use chisel_common::char::span::Span;
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 
-/// Enumeration of generated JSON tokens
+/// Enumeration of generated JSON tokens. The lifetime is that of the underlying input, which string
+/// values may be borrowed from
 #[derive(Debug, Clone, PartialEq)]
-pub enum Token {
+pub enum Token<'a> {
     /// Start of a JSON object
     StartObject,
     /// End of a JSON object
@@ -17,13 +20,20 @@ pub enum Token {
     Colon,
     /// A lowly comma
     Comma,
-    /// A string value
-    Str(String),
+    /// A string value, borrowed from the input where possible
+    Str(Cow<'a, str>),
+    /// A lazy string value, the escape sequences of which are decoded on demand. Produced in place
+    /// of [Token::Str] if the `lazy-strings` feature is enabled
+    LazyString(LazyString<'a>),
+    /// An unquoted identifier, which is only produced when lexing JSON5. Identifiers are used as
+    /// object keys, and so `Infinity` and `NaN` are produced as identifiers unless they're signed
+    Identifier(Cow<'a, str>),
     /// A float value
     Float(f64),
     /// An integral value
     Integer(i64),
-    /// A lazy numeric value
+    /// A lazy numeric value. Every number is produced as one of these if the `lazy-numerics`
+    /// feature is enabled, otherwise only integers which don't fit into an [i64] are
     LazyNumeric(LazyNumeric),
     /// A null value
     Null,
@@ -33,7 +43,30 @@ pub enum Token {
     EndOfInput,
 }
 
-impl Display for Token {
+impl<'a> Token<'a> {
+    /// Convert into a token which doesn't borrow from the underlying input
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Token::StartObject => Token::StartObject,
+            Token::EndObject => Token::EndObject,
+            Token::StartArray => Token::StartArray,
+            Token::EndArray => Token::EndArray,
+            Token::Colon => Token::Colon,
+            Token::Comma => Token::Comma,
+            Token::Str(str) => Token::Str(Cow::Owned(str.into_owned())),
+            Token::LazyString(str) => Token::LazyString(str.into_owned()),
+            Token::Identifier(name) => Token::Identifier(Cow::Owned(name.into_owned())),
+            Token::Float(num) => Token::Float(num),
+            Token::Integer(num) => Token::Integer(num),
+            Token::LazyNumeric(lazy) => Token::LazyNumeric(lazy),
+            Token::Null => Token::Null,
+            Token::Boolean(bool) => Token::Boolean(bool),
+            Token::EndOfInput => Token::EndOfInput,
+        }
+    }
+}
+
+impl<'a> Display for Token<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Token::StartObject => write!(f, "StartObject"),
@@ -43,12 +76,11 @@ impl Display for Token {
             Token::Colon => write!(f, "Colon"),
             Token::Comma => write!(f, "Comma"),
             Token::Str(str) => write!(f, "String(\"{}\")", str),
+            Token::LazyString(str) => write!(f, "LazyString(\"{}\")", str),
+            Token::Identifier(name) => write!(f, "Identifier({})", name),
             Token::Float(num) => write!(f, "Float({})", num),
             Token::Integer(num) => write!(f, "Integer({})", num),
-            Token::LazyNumeric(lazy) => {
-                let value: f64 = lazy.into();
-                write!(f, "Lazy({})", value)
-            }
+            Token::LazyNumeric(lazy) => write!(f, "Lazy({})", lazy),
             Token::Null => write!(f, "Null"),
             Token::Boolean(bool) => write!(f, "Boolean({})", bool),
             Token::EndOfInput => write!(f, "EndOfInput"),
@@ -57,4 +89,4 @@ impl Display for Token {
 }
 
 /// A packed token consists of a [Token] and the [Span] associated with it
-pub type PackedToken<'a> = (Token, Span);
+pub type PackedToken<'a> = (Token<'a>, Span);
@@ -0,0 +1,24 @@
+//! The operations common to all lexers.
+//!
+//! Parsers only ever need to pull tokens (along with their positions) from a lexer, and so are
+//! written in terms of the [Lex] trait rather than any particular lexer.  This means that a parser
+//! can be fed by an alternative lexer for the same format, or even by a stream of tokens which has
+//! been recorded earlier and is being replayed.
+use chisel_common::char::span::Span;
+
+/// A source of tokens, each of which is paired with the [Span] it occupies within the input
+pub trait Lex {
+    /// The type of the tokens produced
+    type Token;
+
+    /// The type of any errors which occur whilst producing tokens
+    type Error;
+
+    /// Consume the next token from the input, along with its [Span]
+    fn consume(&mut self) -> Result<(Self::Token, Span), Self::Error>;
+
+    /// Take a look at the next token in the input (along with its [Span]) without consuming it.
+    /// If producing the token fails, then the same error will also be returned by the next call to
+    /// [Lex::consume]
+    fn peek(&mut self) -> Result<&(Self::Token, Span), Self::Error>;
+}
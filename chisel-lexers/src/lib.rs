@@ -13,7 +13,8 @@
 //!
 //! A lexer defines and is capable of producing its own set of distinct tokens
 //! specific to the parsing task in hand.  (For example, the JSON lexer produces JSON-specific
-//! tokens only).
+//! tokens only). Every lexer implements the [lex::Lex] trait, which is all that a parser relies
+//! upon.
 //!
 //! ## Scanning the input
 //! The scanner operates through maintaining a simple internal state:
@@ -22,6 +23,14 @@
 //! - An input buffer used to control pushbacks and lookaheads
 //! - An accumulation buffer for gathering up characters
 //!
+//! The way in which lines and columns are calculated for each character can be tailored through a
+//! [position::PositionModel], which controls the recognised line terminators, the width of tabs
+//! and the units in which columns are counted.
+//!
+//! On top of the basic scanner operations, [combinators::Combinators] provides some higher level
+//! building blocks (such as matching a literal, or advancing whilst a predicate holds) from which
+//! new lexers can be composed.
+//!
 //! A lexer simply pulls characters through the scanner (which adds positional information to each
 //! one) and gathers them up within the accumulation buffer until it sees something that triggers
 //! the parse of a valid token.
@@ -35,7 +44,7 @@
 //!  use std::io::BufReader;
 //!  use chisel_common::reader_from_bytes;
 //!  use chisel_decoders::utf8::Utf8Decoder;
-//!  use chisel_lexers::scanner::Scanner;
+//!  use chisel_lexers::scanner::{Scan, Scanner};
 //!
 //!  // construct a new scanner instance, based on a decoded byte source
 //!  let buffer: &[u8] = "let goodly sin and sunshine in".as_bytes();
@@ -92,5 +101,9 @@
 
 //!
 
+pub mod combinators;
 pub mod json;
+pub mod lex;
+pub mod position;
 pub mod scanner;
+pub mod slice;
@@ -0,0 +1,316 @@
+//! Configuration of the way in which a [crate::scanner::Scanner] calculates the [Coords] of each
+//! character it reads.
+//!
+//! A [PositionModel] determines which characters terminate a line, how wide a tab character is,
+//! and the units in which columns are counted.  For example, an editor integration speaking the
+//! language server protocol would typically count columns in UTF-16 code units:
+//!
+//! ```rust
+//!  use chisel_common::reader_from_bytes;
+//!  use chisel_decoders::utf8::Utf8Decoder;
+//!  use chisel_lexers::position::{ColumnUnit, PositionModel};
+//!  use chisel_lexers::scanner::{Scan, Scanner};
+//!  use std::io::BufReader;
+//!
+//!  let mut reader = reader_from_bytes!("\u{1f600}x");
+//!  let mut decoder = Utf8Decoder::new(&mut reader);
+//!  let model = PositionModel {
+//!     column_unit: ColumnUnit::Utf16,
+//!     ..Default::default()
+//!  };
+//!  let mut scanner = Scanner::with_position_model(&mut decoder, model);
+//!  scanner.advance_n(2, false).unwrap();
+//!  assert_eq!(scanner.front().unwrap().coords.column, 3);
+//! ```
+use chisel_common::char::coords::Coords;
+
+/// The line separator character
+const LINE_SEPARATOR: char = '\u{2028}';
+
+/// The paragraph separator character
+const PARAGRAPH_SEPARATOR: char = '\u{2029}';
+
+/// Enumeration of the different sets of characters which may be recognised as terminating a line.
+/// A terminating character is always considered to be the last character on the line it ends
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LineTerminators {
+    /// Only `'\n'` terminates a line
+    Lf,
+    /// `'\n'`, `"\r\n"` and a lone `'\r'` each terminate a line
+    Ascii,
+    /// As for [LineTerminators::Ascii], along with U+2028 (line separator) and U+2029 (paragraph
+    /// separator)
+    Unicode,
+}
+
+impl Default for LineTerminators {
+    fn default() -> Self {
+        LineTerminators::Ascii
+    }
+}
+
+/// Enumeration of the different units in which columns may be counted
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ColumnUnit {
+    /// Each character occupies a single column
+    Chars,
+    /// Each character occupies as many columns as the number of bytes it takes up in the
+    /// underlying input
+    Bytes,
+    /// Each character occupies as many columns as the number of UTF-16 code units required to
+    /// encode it
+    Utf16,
+}
+
+impl Default for ColumnUnit {
+    fn default() -> Self {
+        ColumnUnit::Chars
+    }
+}
+
+/// The model used to calculate the line and column of each character read by a scanner
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PositionModel {
+    /// The characters which terminate a line
+    pub line_terminators: LineTerminators,
+    /// The width of a tab character. Tabs advance the column to the next tab stop, so that with a
+    /// width of 4, a tab in column 1 or 2 will be followed by a character in column 5. A width of
+    /// 0 or 1 means a tab is treated the same as any other character
+    pub tab_width: usize,
+    /// The units in which columns are counted
+    pub column_unit: ColumnUnit,
+}
+
+impl Default for PositionModel {
+    /// By default, all ASCII line terminators are recognised and columns are counted in chars
+    fn default() -> Self {
+        PositionModel {
+            line_terminators: LineTerminators::Ascii,
+            tab_width: 1,
+            column_unit: ColumnUnit::Chars,
+        }
+    }
+}
+
+/// Tracks the position of successive characters read from a source, according to a
+/// [PositionModel]
+#[derive(Debug, Clone)]
+pub(crate) struct PositionTracker {
+    /// The model used to calculate positions
+    model: PositionModel,
+    /// The [Coords] of the next character, less the byte offset and length
+    next: Coords,
+    /// Whether the last character read was a carriage return, which may yet be followed by a line
+    /// feed
+    pending_cr: bool,
+}
+
+impl PositionTracker {
+    /// Create a new tracker, positioned at the start of the first line
+    pub fn new(model: PositionModel) -> Self {
+        PositionTracker {
+            model,
+            next: Coords {
+                absolute: 1,
+                line: 1,
+                column: 1,
+                byte_offset: 0,
+                byte_len: 0,
+            },
+            pending_cr: false,
+        }
+    }
+
+    /// Create a new tracker using the same [PositionModel], which will position the next character
+    /// it reads at a given set of [Coords]
+    pub fn restart_at(&self, coords: Coords) -> Self {
+        PositionTracker {
+            model: self.model,
+            next: coords,
+            pending_cr: false,
+        }
+    }
+
+    /// The [Coords] at which the next character will be positioned.  Useful for reporting
+    /// failures which occur in between characters
+    pub fn peek(&self) -> Coords {
+        let mut coords = self.next;
+        if self.pending_cr {
+            coords.line += 1;
+            coords.column = 1;
+        }
+        coords
+    }
+
+    /// Calculate the [Coords] for the next character read from the source, given the offset and
+    /// length of its encoded form
+    pub fn next(&mut self, ch: char, byte_offset: usize, byte_len: usize) -> Coords {
+        if self.pending_cr {
+            self.pending_cr = false;
+            if ch != '\n' {
+                self.break_line();
+            }
+        }
+        let coords = self.next.with_bytes(byte_offset, byte_len);
+        self.next.absolute += 1;
+        self.next.byte_offset = byte_offset + byte_len;
+
+        match (ch, self.model.line_terminators) {
+            ('\n', _) => self.break_line(),
+            ('\r', LineTerminators::Ascii | LineTerminators::Unicode) => {
+                self.next.column += 1;
+                self.pending_cr = true;
+            }
+            (LINE_SEPARATOR | PARAGRAPH_SEPARATOR, LineTerminators::Unicode) => self.break_line(),
+            ('\t', _) if self.model.tab_width > 1 => {
+                let width = self.model.tab_width;
+                self.next.column = ((self.next.column - 1) / width + 1) * width + 1;
+            }
+            _ => self.next.column += self.width(ch, byte_len),
+        }
+        coords
+    }
+
+    /// Move on to the start of the next line
+    #[inline]
+    fn break_line(&mut self) {
+        self.next.line += 1;
+        self.next.column = 1;
+    }
+
+    /// The number of columns occupied by a character
+    #[inline]
+    fn width(&self, ch: char, byte_len: usize) -> usize {
+        match self.model.column_unit {
+            ColumnUnit::Chars => 1,
+            ColumnUnit::Bytes => byte_len,
+            ColumnUnit::Utf16 => ch.len_utf16(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::position::{ColumnUnit, LineTerminators, PositionModel, PositionTracker};
+
+    /// Run some input through a tracker, returning the (line, column) of each character
+    fn positions(input: &str, model: PositionModel) -> Vec<(usize, usize)> {
+        let mut tracker = PositionTracker::new(model);
+        input
+            .char_indices()
+            .map(|(offset, ch)| {
+                let coords = tracker.next(ch, offset, ch.len_utf8());
+                (coords.line, coords.column)
+            })
+            .collect()
+    }
+
+    fn terminators(line_terminators: LineTerminators) -> PositionModel {
+        PositionModel {
+            line_terminators,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_break_lines_on_consecutive_line_feeds() {
+        assert_eq!(
+            positions("a\n\n\nb", PositionModel::default()),
+            vec![(1, 1), (1, 2), (2, 1), (3, 1), (4, 1)]
+        );
+    }
+
+    #[test]
+    fn should_treat_crlf_as_a_single_terminator() {
+        assert_eq!(
+            positions("a\r\nb\r\n\r\nc", PositionModel::default()),
+            vec![
+                (1, 1),
+                (1, 2),
+                (1, 3),
+                (2, 1),
+                (2, 2),
+                (2, 3),
+                (3, 1),
+                (3, 2),
+                (4, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_break_lines_on_lone_carriage_returns() {
+        assert_eq!(
+            positions("a\rb\r\rc", PositionModel::default()),
+            vec![(1, 1), (1, 2), (2, 1), (2, 2), (3, 1), (4, 1)]
+        );
+        assert_eq!(
+            positions("a\rb", terminators(LineTerminators::Lf)),
+            vec![(1, 1), (1, 2), (1, 3)]
+        );
+    }
+
+    #[test]
+    fn should_only_recognise_unicode_separators_when_asked() {
+        let input = "a\u{2028}b\u{2029}c";
+        assert_eq!(
+            positions(input, PositionModel::default()),
+            vec![(1, 1), (1, 2), (1, 3), (1, 4), (1, 5)]
+        );
+        assert_eq!(
+            positions(input, terminators(LineTerminators::Unicode)),
+            vec![(1, 1), (1, 2), (2, 1), (2, 2), (3, 1)]
+        );
+    }
+
+    #[test]
+    fn should_expand_tabs_to_the_next_tab_stop() {
+        let model = PositionModel {
+            tab_width: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            positions("\ta\tbcd\te", model),
+            vec![
+                (1, 1),
+                (1, 5),
+                (1, 6),
+                (1, 9),
+                (1, 10),
+                (1, 11),
+                (1, 12),
+                (1, 13)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_count_columns_in_different_units() {
+        let input = "\u{e9}\u{1f600}x";
+        let columns = |column_unit| {
+            positions(
+                input,
+                PositionModel {
+                    column_unit,
+                    ..Default::default()
+                },
+            )
+            .into_iter()
+            .map(|(_, column)| column)
+            .collect::<Vec<usize>>()
+        };
+        assert_eq!(columns(ColumnUnit::Chars), vec![1, 2, 3]);
+        assert_eq!(columns(ColumnUnit::Bytes), vec![1, 3, 7]);
+        assert_eq!(columns(ColumnUnit::Utf16), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn should_peek_at_the_next_position() {
+        let mut tracker = PositionTracker::new(PositionModel::default());
+        assert_eq!(tracker.peek().column, 1);
+        tracker.next('a', 0, 1);
+        tracker.next('\r', 1, 1);
+        let next = tracker.peek();
+        assert_eq!((next.line, next.column, next.byte_offset), (2, 1, 2));
+    }
+}
@@ -4,15 +4,22 @@
 //!
 //! Usage of the scanner is pretty straightforward. Construct an instance based on a supplied
 //! decoder (which is responsible for decoding byte streams into streams of UTF8 characters),
-//! and then use the [Scanner::advance] and [Scanner::advance_n] functions to move through the
+//! and then use the [Scan::advance] and [Scan::advance_n] functions to move through the
 //! underlying input and populate the internal scanner buffer.
 //!
-//! To look into the scanner buffer, the [Scanner::front] and [Scanner::back] functions allow
+//! To look into the scanner buffer, the [Scan::front] and [Scan::back] functions allow
 //! access to the first and last elements.  To grab the entire contents of the buffer, functions
-//! such as [Scanner::buffer_as_char_array] may be used.
+//! such as [Scan::buffer_as_str] may be used.
 //!
 //! Once a chunk of input has been processed, the scanner state (i.e. the buffer) can be reset
-//! with a call to [Scanner::clear].
+//! with a call to [Scan::clear].
+//!
+//! The operations provided by a scanner are defined by the [Scan] trait, which is also implemented
+//! by [crate::slice::SliceScanner] for input that is already held in memory as a string slice.
+//!
+//! A [Scanner] is generic over its [CharSource], so that calls through to the decoder can be
+//! inlined.  Where the decoder can only be chosen at runtime (for example, when using
+//! [chisel_decoders::new_decoder]), a [DynScanner] may be used instead.
 //!
 //! # Examples
 //!
@@ -20,7 +27,7 @@
 //!  use std::io::BufReader;
 //!  use chisel_common::reader_from_bytes;
 //!  use chisel_decoders::utf8::Utf8Decoder;
-//!  use chisel_lexers::scanner::Scanner;
+//!  use chisel_lexers::scanner::{Scan, Scanner};
 //!
 //!  // construct a new scanner instance, based on a decoded byte source
 //!  let buffer: &[u8] = "let goodly sin and sunshine in".as_bytes();
@@ -41,15 +48,36 @@
 #![allow(dead_code)]
 use chisel_common::char::coords::Coords;
 use chisel_common::char::span::Span;
+use chisel_decoders::common::{CharSource, DecoderErrorCode};
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 
+use crate::position::{PositionModel, PositionTracker};
+
+/// Check whether a [char] is one of the whitespace characters skipped by [Scan::advance]. This is
+/// deliberately narrower than [char::is_whitespace], and matches the definition given in RFC 8259
+#[inline]
+pub fn is_whitespace(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\n' | '\r')
+}
+
 /// Result type for the scanner
 pub type ScannerResult<T> = Result<T, ScannerError>;
 
 /// An enumeration of possible faults
 #[derive(Debug, Clone, PartialEq)]
 pub enum ScannerErrorDetails {
+    /// The end of the underlying input has been reached
     EndOfInput,
+    /// The underlying source failed to decode a character
+    Decode(DecoderErrorCode),
+    /// An attempt was made to reset to a mark which is no longer valid
+    InvalidMark,
+    /// The input didn't match an expected literal. Holds the literal, and the characters which
+    /// were actually found up to (and including) the first mismatch
+    MatchFailed(String, String),
+    /// A character was found which wasn't one of those expected
+    UnexpectedCharacter(char),
 }
 
 /// Convert specific fault codes into human-readable strings
@@ -57,6 +85,14 @@ impl Display for ScannerErrorDetails {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ScannerErrorDetails::EndOfInput => write!(f, "end of input reached"),
+            ScannerErrorDetails::Decode(code) => write!(f, "failed to decode input: {}", code),
+            ScannerErrorDetails::InvalidMark => write!(f, "invalid mark"),
+            ScannerErrorDetails::MatchFailed(expected, found) => {
+                write!(f, "expected \"{}\", found \"{}\"", expected, found)
+            }
+            ScannerErrorDetails::UnexpectedCharacter(ch) => {
+                write!(f, "unexpected character: \'{}\'", ch)
+            }
         }
     }
 }
@@ -95,7 +131,8 @@ macro_rules! scanner_error {
     };
 }
 
-/// A [char] and a [Coord] providing positional information
+/// A [char] and a [Coords] providing positional information
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct CharWithCoords {
     pub ch: char,
     pub coords: Coords,
@@ -107,16 +144,6 @@ pub struct StringWithSpan {
     pub span: Span,
 }
 
-/// Just clone a [CharWithCoords] structure
-macro_rules! clone_char_with_coords {
-    ($src : expr) => {
-        CharWithCoords {
-            ch: $src.ch,
-            coords: $src.coords.clone(),
-        }
-    };
-}
-
 /// Shorthand for the creation of a [CharWithCoords]
 macro_rules! char_with_coords {
     ($ch : expr, $coords : expr) => {
@@ -127,92 +154,270 @@ macro_rules! char_with_coords {
     };
 }
 
-/// Simple scanner which wraps itself around a source of [char]s and converts raw characters
+/// The operations provided by a scanner, and relied upon by the lexers. Implemented by both
+/// [Scanner], which pulls characters from a [CharSource], and [crate::slice::SliceScanner], which
+/// works directly over a string slice held in memory.  The lifetime `'a` is that of the underlying
+/// input, so that a scanner which is able to do so can hand out borrowed slices of it
+pub trait Scan<'a> {
+    /// A checkpoint within the input, which may later be returned to with [Scan::reset_to]
+    type Mark: Clone;
+
+    /// Reset the accumulated state of the scanner, without resetting the underlying input
+    fn clear(&mut self);
+
+    /// Push the last accumulated character (and it's coords) back onto the input. Noop if nothing
+    /// has currently been accumulated
+    fn pushback(&mut self);
+
+    /// Get the absolute position in the underlying input
+    fn position(&self) -> Coords;
+
+    /// Get the optional [char] at the front of the scanner buffer (the one most recently read)
+    fn front(&self) -> Option<CharWithCoords>;
+
+    /// Get the optional [char] at the back of the scanner buffer (the first one accumulated)
+    fn back(&self) -> Option<CharWithCoords>;
+
+    /// Advance the scanner to the next available character, optionally skipping whitespace. Only
+    /// the four whitespace characters defined by RFC 8259 (space, tab, line feed and carriage
+    /// return) are skipped, see [is_whitespace]
+    fn advance(&mut self, skip_whitespace: bool) -> ScannerResult<()>;
+
+    /// Advance the scanner over n available characters, returning a [ScannerError] if it's not
+    /// possible to do so. After calling this method the input state should be read using the
+    /// other associated functions available for this type
+    fn advance_n(&mut self, n: usize, skip_whitespace: bool) -> ScannerResult<()> {
+        for _ in 0..n {
+            self.advance(skip_whitespace)?;
+        }
+        Ok(())
+    }
+
+    /// Try and look ahead one [char] in the input. If the underlying input fails, then [None] is
+    /// returned and the failure will be reported by the next call to [Scan::advance]
+    fn try_lookahead(&mut self) -> Option<&CharWithCoords>;
+
+    /// Look ahead `n` characters beyond the next one in the input, without consuming anything, so
+    /// that `peek_n(0)` is the character which would be read by the next call to [Scan::advance].
+    /// Whitespace is never skipped. Returns [None] if the input ends (or fails) before then
+    fn peek_n(&mut self, n: usize) -> Option<CharWithCoords>;
+
+    /// Take a checkpoint of the current state of the scanner, including the contents of the
+    /// buffer
+    fn mark(&self) -> Self::Mark;
+
+    /// Rewind the scanner to a checkpoint previously taken with [Scan::mark], so that any
+    /// characters read since will be read again (with the same [Coords]).  The contents of the
+    /// buffer are restored to what they were when the mark was taken.  Returns a
+    /// [ScannerErrorDetails::InvalidMark] error if the scanner is no longer able to return to the
+    /// mark
+    fn reset_to(&mut self, mark: &Self::Mark) -> ScannerResult<()>;
+
+    /// Extract the scanner buffer as a string, which is borrowed from the underlying input where
+    /// possible. Will return an empty string if there's nothing in the buffer
+    fn buffer_as_str(&mut self) -> Cow<'a, str>;
+
+    /// Extract the scanner buffer as a sequence of bytes, which is borrowed from the underlying
+    /// input where possible.  Only meaningful when the buffer holds ASCII characters
+    fn buffer_as_bytes(&mut self) -> Cow<'a, [u8]>;
+
+    /// The [Span] covered by the scanner buffer. If the buffer is empty, then the span is located
+    /// at the current position
+    fn buffer_span(&self) -> Span {
+        match (self.back(), self.front()) {
+            (Some(back), Some(front)) => Span {
+                start: back.coords,
+                end: front.coords,
+            },
+            _ => Span {
+                start: self.position(),
+                end: self.position(),
+            },
+        }
+    }
+
+    /// Extract the scanner buffer as a [StringWithSpan]. Will return an empty string if there's
+    /// nothing in the buffer
+    fn buffer_as_string_with_span(&mut self) -> StringWithSpan {
+        StringWithSpan {
+            str: self.buffer_as_str().into_owned(),
+            span: self.buffer_span(),
+        }
+    }
+}
+
+/// A [Scanner] which reads from a type-erased [CharSource], for use when the decoder is selected at
+/// runtime.  Each character read will incur a dynamic call through to the decoder
+pub type DynScanner<'a> = Scanner<&'a mut dyn CharSource>;
+
+/// Simple scanner which wraps itself around a [CharSource] and converts raw characters
 /// into [CharWithCoords] structures. Provides a running buffer which can be used to accumulate
 /// input characters, prior to extracting them for further downstream processing.
-#[derive()]
-pub struct Scanner<'a> {
+pub struct Scanner<S: CharSource> {
     /// The underlying source of characters
-    source: &'a mut dyn Iterator<Item = char>,
+    source: S,
 
     /// Accumulation buffer
     accumulator: Vec<CharWithCoords>,
 
-    /// Input buffer
-    buffer: Vec<CharWithCoords>,
+    /// All the characters read from the underlying source since the buffer was last cleared, so
+    /// that the scanner is able to look ahead, push back and return to a mark.  Like the
+    /// accumulation buffer, this will keep on growing if the buffer is never cleared
+    history: Vec<CharWithCoords>,
+
+    /// The index within the history of the next character to be read
+    cursor: usize,
+
+    /// The number of characters which have been discarded from the front of the history
+    discarded: usize,
 
     /// Overall position
     position: Coords,
 
-    /// Newline flag in order ensure correct position reporting
-    newline: bool,
+    /// Tracks the position of each character read from the underlying source
+    tracker: PositionTracker,
+
+    /// Any failure reported by the underlying source. Once set, the failure is reported on every
+    /// subsequent read
+    fault: Option<ScannerError>,
 }
 
 /// An input adapter used by the lexer. A [Scanner] is responsible for managing input
 /// state to to provide access to segments (or individual characters) from within the source input.
-impl<'a> Scanner<'a> {
-    /// New instance, based on an [Iterator] of [char]
-    pub fn new(chars: &'a mut dyn Iterator<Item = char>) -> Self {
+impl<S: CharSource> Scanner<S> {
+    /// New instance, based on a [CharSource]
+    pub fn new(chars: S) -> Self {
+        Self::with_position_model(chars, PositionModel::default())
+    }
+
+    /// New instance, based on a [CharSource], which calculates the [Coords] of each character
+    /// according to a given [PositionModel]
+    pub fn with_position_model(chars: S, model: PositionModel) -> Self {
         Scanner {
             source: chars,
             accumulator: vec![],
-            buffer: vec![],
+            history: vec![],
+            cursor: 0,
+            discarded: 0,
             position: Coords {
                 column: 0,
                 line: 1,
                 absolute: 0,
+                byte_offset: 0,
+                byte_len: 0,
             },
-            newline: false,
+            tracker: PositionTracker::new(model),
+            fault: None,
         }
     }
 
-    /// Reset the internal state of the scanner, without resetting the state of the underlying char iterator
-    pub fn clear(&mut self) {
-        self.accumulator = vec![];
+    /// Grab the next available character, either from the history or the underlying input
+    #[inline]
+    fn next(&mut self) -> Option<ScannerResult<CharWithCoords>> {
+        // early return from the history if possible
+        if let Some(cwc) = self.history.get(self.cursor) {
+            self.cursor += 1;
+            return Some(Ok(*cwc));
+        }
+        let read = self.read();
+        if let Some(Ok(_)) = read {
+            self.cursor += 1;
+        }
+        read
     }
 
-    /// Push the last read character (and it's coords) onto the pushback buffer. Noop if there's
-    /// currently nothing in the accumulator
-    pub fn pushback(&mut self) {
-        if !self.accumulator.is_empty() {
-            self.buffer.push(self.accumulator.pop().unwrap())
+    /// Read a new character from the underlying input and calculate its position, picking up the
+    /// byte offset and length of the character from the source. The character is appended to the
+    /// history
+    #[inline]
+    fn read(&mut self) -> Option<ScannerResult<CharWithCoords>> {
+        if let Some(fault) = &self.fault {
+            return Some(Err(fault.clone()));
+        }
+        match self.source.next_char() {
+            Some(Ok(decoded)) => {
+                let coords = self.tracker.next(decoded.ch, decoded.offset, decoded.len);
+                let cwc = char_with_coords!(decoded.ch, coords);
+                self.history.push(cwc);
+                Some(Ok(cwc))
+            }
+            Some(Err(err)) => {
+                let fault = ScannerError {
+                    details: ScannerErrorDetails::Decode(err.code),
+                    coords: Some(self.tracker.peek()),
+                };
+                self.fault = Some(fault.clone());
+                Some(Err(fault))
+            }
+            None => None,
         }
     }
 
-    /// Get the absolute position in the underlying input
-    pub fn position(&self) -> Coords {
+    /// Extract the scanner buffer as a [char] slice
+    pub fn buffer_as_char_array(&mut self) -> Vec<char> {
+        self.accumulator.iter().map(|cwc| cwc.ch).collect()
+    }
+
+    /// Extract the scanner buffer as a byte buffer.  You just get an empty vec if the buffer is
+    /// currently empty
+    pub fn buffer_as_byte_array(&self) -> Vec<u8> {
+        self.accumulator.iter().map(|cwc| cwc.ch as u8).collect()
+    }
+}
+
+/// A checkpoint taken by a [Scanner]
+#[derive(Debug, Clone)]
+pub struct ScannerMark {
+    /// The number of characters read from the underlying source prior to the mark
+    index: usize,
+    /// The contents of the buffer at the mark
+    accumulator: Vec<CharWithCoords>,
+    /// The overall position at the mark
+    position: Coords,
+}
+
+impl<'a, S: CharSource> Scan<'a> for Scanner<S> {
+    type Mark = ScannerMark;
+
+    /// Clearing the buffer also discards the history of characters read so far, which means that
+    /// any marks taken beforehand become invalid
+    fn clear(&mut self) {
+        self.accumulator.clear();
+        self.history.drain(..self.cursor);
+        self.discarded += self.cursor;
+        self.cursor = 0;
+    }
+
+    /// Any whitespace skipped after the character was originally read will be read again
+    fn pushback(&mut self) {
+        if let Some(cwc) = self.accumulator.pop() {
+            self.cursor = cwc.coords.absolute - 1 - self.discarded;
+        }
+    }
+
+    fn position(&self) -> Coords {
         self.position
     }
 
-    /// Get the optional [char] at the front of the scanner buffer
-    pub fn front(&self) -> Option<CharWithCoords> {
-        return if !self.accumulator.is_empty() {
-            Some(clone_char_with_coords!(self.accumulator.last().unwrap()))
-        } else {
-            None
-        };
+    fn front(&self) -> Option<CharWithCoords> {
+        self.accumulator.last().copied()
     }
 
-    /// Get the optional [char] at the back of the scanner buffer
-    pub fn back(&self) -> Option<CharWithCoords> {
-        return if !self.accumulator.is_empty() {
-            Some(clone_char_with_coords!(self.accumulator.first().unwrap()))
-        } else {
-            None
-        };
+    fn back(&self) -> Option<CharWithCoords> {
+        self.accumulator.first().copied()
     }
 
-    /// Advance the scanner to the next available character, optionally skipping whitespace.
-    pub fn advance(&mut self, skip_whitespace: bool) -> ScannerResult<()> {
+    fn advance(&mut self, skip_whitespace: bool) -> ScannerResult<()> {
         loop {
             match self.next() {
-                Some(cwc) => {
+                Some(Err(err)) => return Err(err),
+                Some(Ok(cwc)) => {
                     // update overall position
                     self.position.copy_from(&cwc.coords);
 
                     // check for whitespace
                     if skip_whitespace {
-                        if !cwc.ch.is_whitespace() {
+                        if !is_whitespace(cwc.ch) {
                             self.accumulator.push(cwc);
                             return Ok(());
                         }
@@ -226,113 +431,65 @@ impl<'a> Scanner<'a> {
         }
     }
 
-    /// Try and look ahead one [char] in the input stream
-    pub fn try_lookahead(&mut self) -> Option<&CharWithCoords> {
-        return if !self.buffer.is_empty() {
-            self.buffer.last()
-        } else {
-            match self.next() {
-                Some(cwc) => {
-                    self.buffer.push(cwc);
-                    self.buffer.last()
-                }
-                None => None,
-            }
-        };
+    fn try_lookahead(&mut self) -> Option<&CharWithCoords> {
+        if self.cursor == self.history.len() {
+            self.read()?.ok()?;
+        }
+        self.history.get(self.cursor)
     }
 
-    /// Grab the next available character and update the current position if we retrieve a new
-    /// character from the underlying input
-    fn next(&mut self) -> Option<CharWithCoords> {
-        // early return from the buffer if possible
-        return if !self.buffer.is_empty() {
-            Some(self.buffer.pop().unwrap())
-        } else {
-            // check next character and adjust position taking into account line endings
-            match self.source.next() {
-                Some(ch) => match ch {
-                    '\n' => {
-                        self.newline = true;
-                        Some(char_with_coords!(ch, self.position.copy_increment()))
-                    }
-                    _ => {
-                        if self.newline {
-                            self.newline = false;
-                            Some(char_with_coords!(
-                                ch,
-                                self.position.copy_increment_newline()
-                            ))
-                        } else {
-                            Some(char_with_coords!(ch, self.position.copy_increment()))
-                        }
-                    }
-                },
-                None => None,
-            }
-        };
+    fn peek_n(&mut self, n: usize) -> Option<CharWithCoords> {
+        while self.history.len() <= self.cursor + n {
+            self.read()?.ok()?;
+        }
+        self.history.get(self.cursor + n).copied()
     }
 
-    /// Advance the scanner over n available characters, returning a [ScannerError] if it's not
-    /// possible to do so. After calling this method the input state should be read using the
-    /// other associated functions available for this type
-    pub fn advance_n(&mut self, n: usize, skip_whitespace: bool) -> ScannerResult<()> {
-        for _ in 0..n {
-            self.advance(skip_whitespace)?;
+    /// The mark holds a copy of the buffer, so it's cheapest to take marks whilst the buffer is
+    /// small
+    fn mark(&self) -> ScannerMark {
+        ScannerMark {
+            index: self.discarded + self.cursor,
+            accumulator: self.accumulator.clone(),
+            position: self.position,
         }
-        Ok(())
     }
 
-    /// Extract the scanner buffer as a [StringWithSpan]. Will return an empty string if there's
-    /// nothing in the buffer
-    pub fn buffer_as_string_with_span(&mut self) -> StringWithSpan {
-        return if !self.accumulator.is_empty() {
-            let mut s = String::with_capacity(self.accumulator.len());
-            self.accumulator.iter().for_each(|cwc| s.push(cwc.ch));
-            StringWithSpan {
-                str: s,
-                span: Span {
-                    start: self.back().unwrap().coords,
-                    end: self.front().unwrap().coords,
-                },
-            }
-        } else {
-            StringWithSpan {
-                str: String::new(),
-                span: Span {
-                    start: self.position,
-                    end: self.position,
-                },
-            }
+    /// A mark remains valid until the next call to [Scan::clear]
+    fn reset_to(&mut self, mark: &ScannerMark) -> ScannerResult<()> {
+        // the mark is only valid if all the characters it refers to are still in the history
+        let earliest = match mark.accumulator.first() {
+            Some(cwc) => cwc.coords.absolute - 1,
+            None => mark.index,
         };
+        if earliest < self.discarded || mark.index > self.discarded + self.history.len() {
+            return scanner_error!(ScannerErrorDetails::InvalidMark, self.position);
+        }
+        self.cursor = mark.index - self.discarded;
+        self.accumulator.clone_from(&mark.accumulator);
+        self.position = mark.position;
+        Ok(())
     }
 
-    /// Extract the scanner buffer as a [char] slice
-    pub fn buffer_as_char_array(&mut self) -> Vec<char> {
-        return if !self.accumulator.is_empty() {
-            let mut arr: Vec<char> = vec![];
-            self.accumulator.iter().for_each(|cwc| arr.push(cwc.ch));
-            arr
-        } else {
-            vec![]
-        };
+    /// The characters are always copied out of the accumulator into a new [String], so the
+    /// result is always owned
+    fn buffer_as_str(&mut self) -> Cow<'a, str> {
+        Cow::Owned(self.accumulator.iter().map(|cwc| cwc.ch).collect())
     }
 
-    /// Extract the scanner buffer as a byte buffer.  You just get an empty vec if the buffer is
-    /// currently empty
-    pub fn buffer_as_byte_array(&self) -> Vec<u8> {
-        return if !self.accumulator.is_empty() {
-            self.accumulator.iter().map(|cwc| cwc.ch as u8).collect()
-        } else {
-            vec![]
-        };
+    fn buffer_as_bytes(&mut self) -> Cow<'a, [u8]> {
+        Cow::Owned(self.buffer_as_byte_array())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::scanner::Scanner;
+    use crate::scanner::{DynScanner, Scan, Scanner, ScannerErrorDetails};
     use chisel_common::reader_from_bytes;
+    use chisel_decoders::common::{ByteOrder, DecoderErrorCode};
+    use chisel_decoders::utf16::Utf16Decoder;
     use chisel_decoders::utf8::Utf8Decoder;
+    use chisel_decoders::{new_decoder, Encoding};
     use std::io::BufReader;
 
     #[test]
@@ -366,6 +523,17 @@ mod test {
         assert_eq!(input.front().unwrap().coords.column, 10)
     }
 
+    #[test]
+    fn should_only_skip_json_whitespace() {
+        let mut reader = reader_from_bytes!(" \t\r\n\u{a0}\u{c}x");
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut input = Scanner::new(&mut decoder);
+        input.advance(true).unwrap();
+        assert_eq!(input.front().unwrap().ch, '\u{a0}');
+        input.advance(true).unwrap();
+        assert_eq!(input.front().unwrap().ch, '\u{c}');
+    }
+
     #[test]
     fn should_handle_pushbacks_correctly() {
         // construct a new scanner instance, based on a decoded byte source
@@ -410,4 +578,135 @@ mod test {
         assert_eq!(scanner.front().unwrap().ch, 't');
         assert_eq!(scanner.front().unwrap().coords.column, 3);
     }
+
+    #[test]
+    fn should_report_decoder_failures() {
+        let buffer: &[u8] = &[0x61, 0x0a, 0x62, 0xff, 0x63];
+        let mut reader = BufReader::new(buffer);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut scanner = Scanner::new(&mut decoder);
+        assert!(scanner.advance_n(2, true).is_ok());
+        let err = scanner.advance(true).err().unwrap();
+        assert_eq!(
+            err.details,
+            ScannerErrorDetails::Decode(DecoderErrorCode::InvalidByteSequence)
+        );
+        assert_eq!(err.coords.unwrap().line, 2);
+        assert_eq!(err.coords.unwrap().column, 2);
+        assert_eq!(err.coords.unwrap().byte_offset, 3);
+
+        // the failure should be sticky
+        assert!(scanner.advance(true).is_err());
+    }
+
+    #[test]
+    fn should_track_byte_offsets_reported_by_the_decoder() {
+        let bytes: Vec<u8> = "a\u{20ac}\n\u{1f600}b"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let mut reader = BufReader::new(bytes.as_slice());
+        let mut decoder = Utf16Decoder::new(&mut reader, ByteOrder::LittleEndian);
+        let mut scanner = Scanner::new(&mut decoder);
+        let mut offsets = vec![];
+        while scanner.advance(false).is_ok() {
+            let coords = scanner.front().unwrap().coords;
+            offsets.push((coords.byte_offset, coords.byte_len));
+        }
+        assert_eq!(offsets, vec![(0, 2), (2, 2), (4, 2), (6, 4), (10, 2)]);
+        let span = scanner.buffer_as_string_with_span().span;
+        assert_eq!(span.byte_range(), 0..bytes.len());
+    }
+
+    #[test]
+    fn should_scan_sources_chosen_at_runtime() {
+        let input = "[\"\u{e9}\", 1]";
+        let utf16: Vec<u8> = input.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        for (bytes, encoding) in [
+            (input.as_bytes(), Encoding::Utf8),
+            (utf16.as_slice(), Encoding::Utf16Le),
+        ] {
+            let mut reader = BufReader::new(bytes);
+            let mut decoder = new_decoder(&mut reader, encoding);
+            let mut scanner: DynScanner = Scanner::new(&mut decoder);
+            while scanner.advance(false).is_ok() {}
+            assert_eq!(scanner.buffer_as_str(), input);
+        }
+
+        // a scanner may also take ownership of its source
+        let mut reader = reader_from_bytes!(input);
+        let mut scanner = Scanner::new(Utf8Decoder::new(&mut reader));
+        scanner.advance_n(3, false).unwrap();
+        assert_eq!(scanner.buffer_as_str(), "[\"\u{e9}");
+    }
+
+    #[test]
+    fn should_peek_without_consuming() {
+        let mut reader = reader_from_bytes!("nul\nl");
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut scanner = Scanner::new(&mut decoder);
+        let peeked: String = (0..5)
+            .filter_map(|n| scanner.peek_n(n))
+            .map(|cwc| cwc.ch)
+            .collect();
+        assert_eq!(peeked, "nul\nl");
+        assert_eq!(scanner.peek_n(4).unwrap().coords.line, 2);
+        assert!(scanner.peek_n(5).is_none());
+        assert_eq!(scanner.try_lookahead().unwrap().ch, 'n');
+        scanner.advance_n(2, false).unwrap();
+        assert_eq!(scanner.buffer_as_str(), "nu");
+        assert_eq!(scanner.peek_n(0).unwrap().ch, 'l');
+    }
+
+    #[test]
+    fn should_reset_to_marks() {
+        let mut reader = reader_from_bytes!("ab  c\nde");
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut scanner = Scanner::new(&mut decoder);
+        scanner.advance(false).unwrap();
+        let mark = scanner.mark();
+        scanner.advance_n(3, true).unwrap();
+        assert_eq!(scanner.buffer_as_str(), "abcd");
+        assert_eq!(scanner.front().unwrap().coords.line, 2);
+
+        // go back and try again, several times over
+        for _ in 0..2 {
+            scanner.reset_to(&mark).unwrap();
+            assert_eq!(scanner.buffer_as_str(), "a");
+            assert_eq!(scanner.position().column, 1);
+            scanner.advance_n(3, false).unwrap();
+            assert_eq!(scanner.buffer_as_str(), "ab  ");
+            assert_eq!(scanner.front().unwrap().coords.column, 4);
+        }
+
+        // pushing back below the mark is fine too
+        scanner.reset_to(&mark).unwrap();
+        scanner.pushback();
+        scanner.reset_to(&mark).unwrap();
+        assert_eq!(scanner.buffer_as_str(), "a");
+
+        // but clearing the buffer discards the history
+        scanner.advance(false).unwrap();
+        scanner.clear();
+        let err = scanner.reset_to(&mark).err().unwrap();
+        assert_eq!(err.details, ScannerErrorDetails::InvalidMark);
+        scanner.advance(true).unwrap();
+        assert_eq!(scanner.front().unwrap().ch, 'c');
+    }
+
+    #[test]
+    fn should_report_lines_for_each_terminator() {
+        let mut reader = reader_from_bytes!("a\n\nb\r\nc\rd");
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut scanner = Scanner::new(&mut decoder);
+        let mut lines = vec![];
+        while scanner.advance(true).is_ok() {
+            let cwc = scanner.front().unwrap();
+            lines.push((cwc.ch, cwc.coords.line, cwc.coords.column));
+        }
+        assert_eq!(
+            lines,
+            vec![('a', 1, 1), ('b', 3, 1), ('c', 4, 1), ('d', 5, 1)]
+        );
+    }
 }
@@ -0,0 +1,380 @@
+//! A scanner which works directly over a string slice held in memory.
+//!
+//! Rather than copying each character into an accumulation buffer, a [SliceScanner] just keeps
+//! track of the first and last characters accumulated so far.  Because the input is already in
+//! memory, the contents of the buffer can then be handed out as a slice borrowed from the input,
+//! without any copying or allocation.
+//!
+//! ```rust
+//!  use std::borrow::Cow;
+//!  use chisel_lexers::scanner::Scan;
+//!  use chisel_lexers::slice::SliceScanner;
+//!
+//!  let input = String::from("  borrowed");
+//!  let mut scanner = SliceScanner::new(&input);
+//!  scanner.advance(true).unwrap();
+//!  scanner.advance_n(7, false).unwrap();
+//!  assert!(matches!(scanner.buffer_as_str(), Cow::Borrowed("borrowed")));
+//!  assert_eq!(scanner.buffer_span().byte_range(), 2..10);
+//! ```
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use chisel_common::char::coords::Coords;
+
+use crate::position::{PositionModel, PositionTracker};
+use crate::scanner::{
+    is_whitespace, CharWithCoords, Scan, ScannerError, ScannerErrorDetails, ScannerResult,
+};
+
+/// A scanner over a `&'a str`, which hands out borrowed slices of its input
+pub struct SliceScanner<'a> {
+    /// The underlying input
+    input: &'a str,
+
+    /// The byte offset of the next character to be read from the input
+    offset: usize,
+
+    /// Input buffer, holding any characters which have been pushed back or looked ahead
+    buffer: VecDeque<CharWithCoords>,
+
+    /// The first character accumulated
+    start: Option<CharWithCoords>,
+
+    /// The last character accumulated
+    end: Option<CharWithCoords>,
+
+    /// Overall position
+    position: Coords,
+
+    /// Tracks the position of each character read from the input
+    tracker: PositionTracker,
+}
+
+impl<'a> SliceScanner<'a> {
+    /// New instance, based on a string slice
+    pub fn new(input: &'a str) -> Self {
+        Self::with_position_model(input, PositionModel::default())
+    }
+
+    /// New instance, based on a string slice, which calculates the [Coords] of each character
+    /// according to a given [PositionModel]
+    pub fn with_position_model(input: &'a str, model: PositionModel) -> Self {
+        SliceScanner {
+            input,
+            offset: 0,
+            buffer: VecDeque::new(),
+            start: None,
+            end: None,
+            position: Coords {
+                column: 0,
+                line: 1,
+                absolute: 0,
+                byte_offset: 0,
+                byte_len: 0,
+            },
+            tracker: PositionTracker::new(model),
+        }
+    }
+
+    /// Grab the next available character, either from the input buffer or the input itself
+    #[inline]
+    fn next(&mut self) -> Option<CharWithCoords> {
+        if let Some(cwc) = self.buffer.pop_front() {
+            return Some(cwc);
+        }
+        self.read()
+    }
+
+    /// Read the next character from the input itself
+    #[inline]
+    fn read(&mut self) -> Option<CharWithCoords> {
+        let remaining = &self.input.as_bytes()[self.offset..];
+        let ch = match remaining.first() {
+            None => return None,
+            Some(byte) if byte.is_ascii() => char::from(*byte),
+            Some(_) => self.input[self.offset..].chars().next()?,
+        };
+        let len = ch.len_utf8();
+        let coords = self.tracker.next(ch, self.offset, len);
+        self.offset += len;
+        Some(CharWithCoords { ch, coords })
+    }
+
+    /// Add a character to the (notional) accumulation buffer
+    #[inline]
+    fn accumulate(&mut self, cwc: CharWithCoords) {
+        if self.start.is_none() {
+            self.start = Some(cwc);
+        }
+        self.end = Some(cwc);
+    }
+
+    /// Find the accumulated character which immediately precedes a given one.  Only the first
+    /// and last characters are held on to, so this walks forward from the first character in
+    /// order to recalculate the [Coords] of the one that's wanted
+    fn accumulated_before(&self, cwc: &CharWithCoords) -> Option<CharWithCoords> {
+        let start = self.start?;
+        let mut tracker = self.tracker.restart_at(start.coords);
+        let from = start.coords.byte_offset;
+        self.input[from..cwc.coords.byte_offset]
+            .char_indices()
+            .map(|(i, ch)| CharWithCoords {
+                ch,
+                coords: tracker.next(ch, from + i, ch.len_utf8()),
+            })
+            .last()
+    }
+}
+
+/// A checkpoint taken by a [SliceScanner].  As the input is held in memory, it's always possible to
+/// return to one of these
+#[derive(Debug, Clone)]
+pub struct SliceMark {
+    /// The byte offset of the next character to be read from the input
+    offset: usize,
+    /// The contents of the input buffer
+    buffer: VecDeque<CharWithCoords>,
+    /// The first and last characters accumulated
+    accumulated: (Option<CharWithCoords>, Option<CharWithCoords>),
+    /// The overall position
+    position: Coords,
+    /// The state of the position tracker
+    tracker: PositionTracker,
+}
+
+impl<'a> Scan<'a> for SliceScanner<'a> {
+    type Mark = SliceMark;
+
+    fn clear(&mut self) {
+        self.start = None;
+        self.end = None;
+    }
+
+    /// Any whitespace skipped after the buffer started to accumulate is part of the buffer, so
+    /// will be pushed back a character at a time in the same way as anything else
+    fn pushback(&mut self) {
+        if let Some(end) = self.end.take() {
+            self.buffer.push_front(end);
+            self.end = self.accumulated_before(&end);
+            if self.end.is_none() {
+                self.start = None;
+            }
+        }
+    }
+
+    fn position(&self) -> Coords {
+        self.position
+    }
+
+    fn front(&self) -> Option<CharWithCoords> {
+        self.end
+    }
+
+    fn back(&self) -> Option<CharWithCoords> {
+        self.start
+    }
+
+    /// Any whitespace skipped after the buffer has started to accumulate is included in the
+    /// contents of the buffer
+    fn advance(&mut self, skip_whitespace: bool) -> ScannerResult<()> {
+        loop {
+            match self.next() {
+                Some(cwc) => {
+                    self.position.copy_from(&cwc.coords);
+                    if !skip_whitespace || !is_whitespace(cwc.ch) {
+                        self.accumulate(cwc);
+                        return Ok(());
+                    }
+                }
+                None => {
+                    return Err(ScannerError {
+                        details: ScannerErrorDetails::EndOfInput,
+                        coords: None,
+                    })
+                }
+            }
+        }
+    }
+
+    fn try_lookahead(&mut self) -> Option<&CharWithCoords> {
+        self.peek_n(0)?;
+        self.buffer.front()
+    }
+
+    fn peek_n(&mut self, n: usize) -> Option<CharWithCoords> {
+        while self.buffer.len() <= n {
+            let cwc = self.read()?;
+            self.buffer.push_back(cwc);
+        }
+        self.buffer.get(n).copied()
+    }
+
+    fn mark(&self) -> SliceMark {
+        SliceMark {
+            offset: self.offset,
+            buffer: self.buffer.clone(),
+            accumulated: (self.start, self.end),
+            position: self.position,
+            tracker: self.tracker.clone(),
+        }
+    }
+
+    fn reset_to(&mut self, mark: &SliceMark) -> ScannerResult<()> {
+        self.offset = mark.offset;
+        self.buffer.clone_from(&mark.buffer);
+        self.start = mark.accumulated.0;
+        self.end = mark.accumulated.1;
+        self.position = mark.position;
+        self.tracker = mark.tracker.clone();
+        Ok(())
+    }
+
+    /// The contents of the buffer are always borrowed from the input
+    fn buffer_as_str(&mut self) -> Cow<'a, str> {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => {
+                Cow::Borrowed(&self.input[start.coords.byte_offset..end.coords.byte_end()])
+            }
+            _ => Cow::Borrowed(""),
+        }
+    }
+
+    fn buffer_as_bytes(&mut self) -> Cow<'a, [u8]> {
+        match self.buffer_as_str() {
+            Cow::Borrowed(str) => Cow::Borrowed(str.as_bytes()),
+            Cow::Owned(string) => Cow::Owned(string.into_bytes()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::io::BufReader;
+
+    use chisel_common::reader_from_bytes;
+    use chisel_decoders::utf8::Utf8Decoder;
+
+    use crate::scanner::{Scan, Scanner, ScannerErrorDetails};
+    use crate::slice::SliceScanner;
+
+    #[test]
+    fn should_match_the_streaming_scanner() {
+        let input = "{\"a\u{e9}\":\r\n [1, \u{1f600}]\n}";
+        let mut reader = reader_from_bytes!(input);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut streaming = Scanner::new(&mut decoder);
+        let mut slice = SliceScanner::new(input);
+        loop {
+            let skip = slice.position().absolute % 3 == 0;
+            match (streaming.advance(skip), slice.advance(skip)) {
+                (Ok(_), Ok(_)) => {
+                    assert_eq!(streaming.front(), slice.front());
+                    assert_eq!(streaming.back(), slice.back());
+                    assert_eq!(streaming.position(), slice.position());
+                }
+                (Err(left), Err(right)) => {
+                    assert_eq!(left.details, ScannerErrorDetails::EndOfInput);
+                    assert_eq!(right.details, ScannerErrorDetails::EndOfInput);
+                    break;
+                }
+                _ => panic!("scanners disagree"),
+            }
+            if slice.position().absolute % 4 == 0 {
+                streaming.clear();
+                slice.clear();
+            }
+        }
+    }
+
+    #[test]
+    fn should_borrow_from_the_input() {
+        let input = String::from("\"caf\u{e9}\", 12");
+        let mut scanner = SliceScanner::new(&input);
+        scanner.advance_n(6, false).unwrap();
+        let str = scanner.buffer_as_str();
+        assert!(matches!(str, Cow::Borrowed(_)));
+        assert_eq!(str, "\"caf\u{e9}\"");
+        assert_eq!(scanner.buffer_span().byte_range(), 0..7);
+
+        scanner.clear();
+        assert_eq!(scanner.buffer_as_str(), "");
+        scanner.advance(false).unwrap();
+        scanner.clear();
+        scanner.advance_n(2, true).unwrap();
+        assert_eq!(scanner.buffer_as_bytes().as_ref(), b"12");
+    }
+
+    #[test]
+    fn should_handle_pushbacks_correctly() {
+        let mut scanner = SliceScanner::new("123,");
+        scanner.advance_n(4, false).unwrap();
+        scanner.pushback();
+        assert_eq!(scanner.buffer_as_str(), "123");
+        assert_eq!(scanner.front().unwrap().ch, '3');
+        assert_eq!(scanner.try_lookahead().unwrap().ch, ',');
+        scanner.clear();
+        scanner.advance(false).unwrap();
+        assert_eq!(scanner.front().unwrap().ch, ',');
+        assert_eq!(scanner.front().unwrap().coords.column, 4);
+        assert!(scanner.advance(false).is_err());
+    }
+
+    #[test]
+    fn should_push_back_like_the_streaming_scanner() {
+        let input = "ab\u{e9}\r\n\u{1f600}\tc";
+        let mut reader = reader_from_bytes!(input);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut streaming = Scanner::new(&mut decoder);
+        let mut slice = SliceScanner::new(input);
+        for (advances, pushbacks) in [(3, 2), (1, 0), (4, 3), (3, 6), (1, 8), (8, 1)] {
+            for _ in 0..advances {
+                assert_eq!(
+                    streaming.advance(false).is_ok(),
+                    slice.advance(false).is_ok()
+                );
+            }
+            for _ in 0..pushbacks {
+                streaming.pushback();
+                slice.pushback();
+                assert_eq!(slice.buffer_as_str(), streaming.buffer_as_str());
+                assert_eq!(slice.front(), streaming.front());
+                assert_eq!(slice.back(), streaming.back());
+            }
+            assert_eq!(slice.buffer_as_str(), streaming.buffer_as_str());
+        }
+        assert_eq!(slice.buffer_as_str(), "ab\u{e9}\r\n\u{1f600}\t");
+    }
+
+    #[test]
+    fn should_peek_and_reset_like_the_streaming_scanner() {
+        let input = "true\r\n fals\u{e9}";
+        let mut reader = reader_from_bytes!(input);
+        let mut decoder = Utf8Decoder::new(&mut reader);
+        let mut streaming = Scanner::new(&mut decoder);
+        let mut slice = SliceScanner::new(input);
+        for n in [3, 0, 8, 13, 14] {
+            assert_eq!(streaming.peek_n(n), slice.peek_n(n));
+        }
+        streaming.advance_n(4, false).unwrap();
+        slice.advance_n(4, false).unwrap();
+        let (streaming_mark, slice_mark) = (streaming.mark(), slice.mark());
+        streaming.advance_n(5, true).unwrap();
+        slice.advance_n(5, true).unwrap();
+        assert_eq!(slice.buffer_as_str(), "true\r\n fals\u{e9}");
+        assert_eq!(slice.front(), streaming.front());
+
+        streaming.reset_to(&streaming_mark).unwrap();
+        slice.reset_to(&slice_mark).unwrap();
+        assert_eq!(slice.buffer_as_str(), "true");
+        assert_eq!(slice.position(), streaming.position());
+        loop {
+            match (streaming.advance(false), slice.advance(false)) {
+                (Ok(_), Ok(_)) => assert_eq!(streaming.front(), slice.front()),
+                (Err(_), Err(_)) => break,
+                _ => panic!("scanners disagree"),
+            }
+        }
+    }
+}
@@ -2,92 +2,212 @@
 //!
 //!
 use std::borrow::Cow;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
 use std::path::Path;
 
-use chisel_decoders::{default_decoder, new_decoder, Encoding};
-use chisel_lexers::json::lexer::Lexer;
+use chisel_common::char::span::Span;
+use chisel_decoders::common::{CharSource, DecodePolicy};
+use chisel_decoders::Encoding;
+use chisel_lexers::json::dialect::Dialect;
+use chisel_lexers::json::lexer::LexerError;
 use chisel_lexers::json::tokens::Token;
+use chisel_lexers::lex::Lex;
+use chisel_lexers::position::PositionModel;
 
-use crate::json::{JsonKeyValue, JsonNumeric, JsonValue};
+use crate::json::{
+    non_finite, object_key, Frontend, JsonKeyValue, JsonNumeric, JsonValue, ParseRoot,
+    ParserOptions,
+};
 use crate::{parser_error, ParserError, ParserErrorDetails, ParserResult};
 
 /// Main JSON parser struct
 pub struct Parser {
-    /// The current encoding
-    encoding: Encoding,
+    /// The options in effect, along with the state of the last parse
+    frontend: Frontend,
 }
 
 impl Default for Parser {
-    /// The default encoding is Utf-8
+    /// The default encoding is Utf-8, and malformed input is rejected
     fn default() -> Self {
-        Self {
-            encoding: Default::default(),
-        }
+        Self::with_options(ParserOptions::default())
     }
 }
 
 impl Parser {
     /// Create a new instance of the parser using a specific [Encoding]
     pub fn with_encoding(encoding: Encoding) -> Self {
-        Self { encoding }
+        Self::with_options(ParserOptions::default().with_encoding(encoding))
     }
 
-    ///
-    pub fn parse_file<PathLike: AsRef<Path>>(&self, path: PathLike) -> ParserResult<JsonValue> {
-        match File::open(&path) {
-            Ok(f) => {
-                let mut reader = BufReader::new(f);
-                let mut chars = new_decoder(&mut reader, self.encoding);
-                self.parse(&mut chars)
-            }
-            Err(_) => {
-                parser_error!(ParserErrorDetails::InvalidFile)
-            }
+    /// Create a new instance of the parser using a given set of [ParserOptions]
+    pub fn with_options(options: ParserOptions) -> Self {
+        Self {
+            frontend: Frontend::new(options),
         }
     }
 
-    pub fn parse_bytes(&self, bytes: &[u8]) -> ParserResult<JsonValue> {
-        let mut reader = BufReader::new(bytes);
-        let mut chars = default_decoder(&mut reader);
-        self.parse(&mut chars)
+    /// Set the [DecodePolicy] used to deal with malformed input
+    pub fn with_decode_policy(mut self, policy: DecodePolicy) -> Self {
+        self.frontend.options = self.frontend.options.with_decode_policy(policy);
+        self
+    }
+
+    /// Set the [PositionModel] used to calculate the positions reported in spans and errors
+    pub fn with_position_model(mut self, model: PositionModel) -> Self {
+        self.frontend.options = self.frontend.options.with_position_model(model);
+        self
+    }
+
+    /// Set the maximum depth to which objects and arrays may be nested, which defaults to
+    /// [DEFAULT_MAX_DEPTH](crate::json::DEFAULT_MAX_DEPTH). Anything nested more deeply results in a
+    /// [ParserErrorDetails::MaximumDepthExceeded] error
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.frontend.options = self.frontend.options.with_max_depth(depth);
+        self
+    }
+
+    /// Set the [Dialect] of JSON to be parsed, which defaults to strict JSON. Parsing JSON5 allows
+    /// for comments, trailing commas, unquoted keys and the other extensions listed against
+    /// [Dialect::Json5]
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.frontend.options = self.frontend.options.with_dialect(dialect);
+        self
+    }
+
+    /// The [ParserOptions] in effect for each parse
+    pub fn options(&self) -> &ParserOptions {
+        &self.frontend.options
+    }
+
+    /// The number of malformed sequences which were replaced or skipped during the last parse, as
+    /// a result of a lossy [DecodePolicy]
+    pub fn replacements(&self) -> usize {
+        self.frontend.replacements()
+    }
+
+    /// The [Encoding] which the input was decoded from during the last parse. When parsing with
+    /// [Encoding::Auto], this is the encoding which was detected. [None] if the last parse didn't
+    /// involve any decoding, because the input was supplied as [char]s or [Token]s
+    pub fn detected_encoding(&self) -> Option<Encoding> {
+        self.frontend.detected_encoding()
+    }
+
+    /// Parse the contents of a file
+    pub fn parse_file<PathLike: AsRef<Path>>(
+        &self,
+        path: PathLike,
+    ) -> ParserResult<JsonValue<'static>> {
+        self.frontend.parse_file(path, self)
+    }
+
+    /// Parse the contents of a file, which is mapped into memory rather than read through a buffer.
+    /// This is generally the better option for very large files. The file must not be modified
+    /// whilst it is being parsed
+    #[cfg(feature = "mmap")]
+    pub fn parse_mmap<PathLike: AsRef<Path>>(
+        &self,
+        path: PathLike,
+    ) -> ParserResult<JsonValue<'static>> {
+        self.frontend.parse_mmap(path, self)
+    }
+
+    /// Parse the contents of a byte slice, decoded using the selected [Encoding]. If the bytes are
+    /// valid UTF-8 (and the selected [Encoding] allows for it), then they are parsed in place and
+    /// any strings within the result are borrowed from the input
+    pub fn parse_bytes<'a>(&self, bytes: &'a [u8]) -> ParserResult<JsonValue<'a>> {
+        self.frontend.parse_bytes(bytes, self)
     }
 
-    pub fn parse_str(&self, str: &str) -> ParserResult<JsonValue> {
-        let mut reader = BufReader::new(str.as_bytes());
-        let mut chars = default_decoder(&mut reader);
-        self.parse(&mut chars)
+    /// Parse the contents of a string slice. Rust strings are always UTF-8, so the selected
+    /// [Encoding] is ignored.  The input is parsed in place, and any strings within the result are
+    /// borrowed from it. Input which starts with a byte order mark goes through the UTF-8 decoder
+    /// instead, so that the BOM is skipped
+    pub fn parse_str<'a>(&self, str: &'a str) -> ParserResult<JsonValue<'a>> {
+        self.frontend.parse_str(str, self)
     }
 
-    /// Parse the contents of a buffer (e.g. implementation of [BufRead])
-    pub fn parse_buffer(&self, buffer: &mut impl BufRead) -> ParserResult<JsonValue> {
-        let mut chars = default_decoder(buffer);
-        self.parse(&mut chars)
+    /// Parse the contents of a buffer (e.g. implementation of [BufRead]), decoded using the
+    /// selected [Encoding]
+    pub fn parse_buffer(&self, buffer: &mut impl BufRead) -> ParserResult<JsonValue<'static>> {
+        self.frontend.parse_buffer(buffer, self)
     }
 
-    pub fn parse(&self, chars: &mut impl Iterator<Item = char>) -> ParserResult<JsonValue> {
-        let mut lexer = Lexer::new(chars);
+    /// Parse the contents of an [Iterator] over already decoded [char]s
+    pub fn parse(
+        &self,
+        chars: &mut impl Iterator<Item = char>,
+    ) -> ParserResult<JsonValue<'static>> {
+        self.frontend.parse(chars, self)
+    }
+
+    /// Parse the contents of a [CharSource], such as one of the decoders. Any failure to decode
+    /// the input is reported as a [ParserErrorDetails::Decode] error
+    pub fn parse_source(&self, chars: &mut impl CharSource) -> ParserResult<JsonValue<'static>> {
+        self.frontend.parse_source(chars, self)
+    }
+
+    /// Parse a stream of [Token]s pulled from an implementation of [Lex]. This allows for the use
+    /// of an alternative lexer, or for tokens which have been recorded earlier to be replayed. The
+    /// stream must be terminated by a [Token::EndOfInput]
+    pub fn parse_tokens<'a, L: Lex<Token = Token<'a>, Error = LexerError>>(
+        &self,
+        lexer: &mut L,
+    ) -> ParserResult<JsonValue<'a>> {
+        self.frontend.parse_tokens(lexer, self)
+    }
+
+    /// A JSON text is a single value of any kind, which must be followed by the end of the input
+    #[inline]
+    fn parse_root<'a, L: Lex<Token = Token<'a>, Error = LexerError>>(
+        &self,
+        lexer: &mut L,
+    ) -> ParserResult<JsonValue<'a>> {
+        if let (
+            Token::EndOfInput | Token::EndObject | Token::EndArray | Token::Colon | Token::Comma,
+            span,
+        ) = lexer.peek()?
+        {
+            return parser_error!(ParserErrorDetails::InvalidRootObject, span.start);
+        }
+        let value = self.parse_value(lexer, 0)?;
         match lexer.consume()? {
-            (Token::StartObject, _) => self.parse_object(&mut lexer),
-            (Token::StartArray, _) => self.parse_array(&mut lexer),
-            (_, span) => {
-                parser_error!(ParserErrorDetails::InvalidRootObject, span.start)
+            (Token::EndOfInput, _) => Ok(value),
+            (token, span) => {
+                parser_error!(
+                    ParserErrorDetails::UnexpectedToken(token.to_string()),
+                    span.start
+                )
             }
         }
     }
 
+    /// Parse a value, found within the given number of enclosing objects and arrays
     #[inline]
-    fn parse_value(&self, lexer: &mut Lexer) -> ParserResult<JsonValue> {
+    fn parse_value<'a, L: Lex<Token = Token<'a>, Error = LexerError>>(
+        &self,
+        lexer: &mut L,
+        depth: usize,
+    ) -> ParserResult<JsonValue<'a>> {
         match lexer.consume()? {
-            (Token::StartObject, _) => self.parse_object(lexer),
-            (Token::StartArray, _) => self.parse_array(lexer),
-            (Token::Str(str), _) => Ok(JsonValue::String(Cow::Owned(str))),
+            (Token::StartObject, span) => self.parse_object(lexer, self.nested(depth, span)?),
+            (Token::StartArray, span) => self.parse_array(lexer, self.nested(depth, span)?),
+            (Token::Str(str), _) => Ok(JsonValue::String(str)),
+            (Token::LazyString(str), _) => Ok(JsonValue::LazyString(str)),
             (Token::LazyNumeric(value), _) => Ok(JsonValue::Number(JsonNumeric::Lazy(value))),
             (Token::Float(value), _) => Ok(JsonValue::Number(JsonNumeric::Float(value))),
             (Token::Integer(value), _) => Ok(JsonValue::Number(JsonNumeric::Integer(value))),
             (Token::Boolean(value), _) => Ok(JsonValue::Boolean(value)),
             (Token::Null, _) => Ok(JsonValue::Null),
+            (Token::Identifier(name), span) => {
+                match non_finite(&name, self.frontend.options.dialect) {
+                    Some(value) => Ok(JsonValue::Number(JsonNumeric::Float(value))),
+                    None => parser_error!(
+                        ParserErrorDetails::UnexpectedToken(Token::Identifier(name).to_string()),
+                        span.start
+                    ),
+                }
+            }
+            (Token::Comma, span) => parser_error!(ParserErrorDetails::ValueExpected, span.start),
             (token, span) => {
                 parser_error!(
                     ParserErrorDetails::UnexpectedToken(token.to_string()),
@@ -97,27 +217,46 @@ impl Parser {
         }
     }
 
-    /// An object is just a list of comma separated KV pairs
-    fn parse_object(&self, lexer: &mut Lexer) -> ParserResult<JsonValue> {
+    /// The depth of an object or array starting at the given [Span], which is found within the
+    /// given number of enclosing objects and arrays
+    #[inline]
+    fn nested(&self, depth: usize, span: Span) -> ParserResult<usize> {
+        if depth < self.frontend.options.max_depth {
+            Ok(depth + 1)
+        } else {
+            parser_error!(
+                ParserErrorDetails::MaximumDepthExceeded(self.frontend.options.max_depth),
+                span.start
+            )
+        }
+    }
+
+    /// An object is just a list of comma separated KV pairs, which may be empty. Trailing commas
+    /// are only allowed in JSON5
+    fn parse_object<'a, L: Lex<Token = Token<'a>, Error = LexerError>>(
+        &self,
+        lexer: &mut L,
+        depth: usize,
+    ) -> ParserResult<JsonValue<'a>> {
         let mut pairs = vec![];
+        match lexer.consume()? {
+            (Token::EndObject, _) => return Ok(JsonValue::Object(pairs)),
+            (token, span) => match object_key(token, self.frontend.options.dialect) {
+                Some(key) => pairs.push(self.parse_pair(key, lexer, depth)?),
+                None => return parser_error!(ParserErrorDetails::InvalidObject, span.start),
+            },
+        }
         loop {
             match lexer.consume()? {
-                (Token::Str(str), _) => {
-                    let should_be_colon = lexer.consume()?;
-                    match should_be_colon {
-                        (Token::Colon, _) => pairs.push(JsonKeyValue {
-                            key: str,
-                            value: self.parse_value(lexer)?,
-                        }),
-                        (_, _) => {
-                            return parser_error!(
-                                ParserErrorDetails::PairExpected,
-                                should_be_colon.1.start
-                            )
-                        }
+                (Token::Comma, _) => match lexer.consume()? {
+                    (Token::EndObject, _) if self.frontend.options.dialect == Dialect::Json5 => {
+                        return Ok(JsonValue::Object(pairs))
                     }
-                }
-                (Token::Comma, _) => (),
+                    (token, span) => match object_key(token, self.frontend.options.dialect) {
+                        Some(key) => pairs.push(self.parse_pair(key, lexer, depth)?),
+                        None => return parser_error!(ParserErrorDetails::PairExpected, span.start),
+                    },
+                },
                 (Token::EndObject, _) => return Ok(JsonValue::Object(pairs)),
                 (_token, span) => {
                     return parser_error!(ParserErrorDetails::InvalidObject, span.start);
@@ -126,58 +265,79 @@ impl Parser {
         }
     }
 
-    /// An array is just a list of comma separated values, but we need to do additional checking
-    /// to make sure that we don't have consecutive commas, we do allow for empty arrays etc...
-    fn parse_array(&self, lexer: &mut Lexer) -> ParserResult<JsonValue> {
+    /// Parse the remainder of a KV pair, the key of which has already been consumed
+    #[inline]
+    fn parse_pair<'a, L: Lex<Token = Token<'a>, Error = LexerError>>(
+        &self,
+        key: Cow<'a, str>,
+        lexer: &mut L,
+        depth: usize,
+    ) -> ParserResult<JsonKeyValue<'a>> {
+        match lexer.consume()? {
+            (Token::Colon, _) => Ok(JsonKeyValue {
+                key,
+                value: self.parse_value(lexer, depth)?,
+            }),
+            (_token, span) => parser_error!(ParserErrorDetails::PairExpected, span.start),
+        }
+    }
+
+    /// An array is just a list of comma separated values, which may be empty. Trailing commas
+    /// are only allowed in JSON5
+    fn parse_array<'a, L: Lex<Token = Token<'a>, Error = LexerError>>(
+        &self,
+        lexer: &mut L,
+        depth: usize,
+    ) -> ParserResult<JsonValue<'a>> {
         let mut values: Vec<JsonValue> = vec![];
-        let mut expect_value: bool = true;
         loop {
-            match lexer.consume()? {
-                (Token::StartArray, _) => {
-                    values.push(self.parse_array(lexer)?);
-                }
-                (Token::EndArray, span) => {
-                    return if !expect_value || values.is_empty() {
-                        Ok(JsonValue::Array(values))
-                    } else {
-                        parser_error!(ParserErrorDetails::ValueExpected, span.start)
-                    }
-                }
-                (Token::StartObject, _) => values.push(self.parse_object(lexer)?),
-                (Token::Str(str), _) => values.push(JsonValue::String(Cow::Owned(str))),
-                (Token::LazyNumeric(value), _) => {
-                    values.push(JsonValue::Number(JsonNumeric::Lazy(value)))
-                }
-                (Token::Float(value), _) => {
-                    values.push(JsonValue::Number(JsonNumeric::Float(value)))
-                }
-                (Token::Integer(value), _) => {
-                    values.push(JsonValue::Number(JsonNumeric::Integer(value)))
-                }
-                (Token::Boolean(value), _) => values.push(JsonValue::Boolean(value)),
-                (Token::Null, _) => values.push(JsonValue::Null),
-                (Token::Comma, span) => {
-                    if expect_value {
-                        return parser_error!(ParserErrorDetails::ValueExpected, span.start);
-                    }
+            if values.is_empty() || self.frontend.options.dialect == Dialect::Json5 {
+                if let (Token::EndArray, _) = lexer.peek()? {
+                    lexer.consume()?;
+                    return Ok(JsonValue::Array(values));
                 }
+            }
+            values.push(self.parse_value(lexer, depth)?);
+            match lexer.consume()? {
+                (Token::Comma, _) => (),
+                (Token::EndArray, _) => return Ok(JsonValue::Array(values)),
                 (_token, span) => {
                     return parser_error!(ParserErrorDetails::InvalidArray, span.start);
                 }
             }
-            expect_value = !expect_value
         }
     }
 }
 
+impl<'a> ParseRoot<'a> for &Parser {
+    type Output = JsonValue<'a>;
+
+    fn parse_root<L: Lex<Token = Token<'a>, Error = LexerError>>(
+        &mut self,
+        lexer: &mut L,
+    ) -> ParserResult<JsonValue<'a>> {
+        Parser::parse_root(self, lexer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(unused_macros)]
     use crate::json::dom::Parser;
     use crate::json::specs;
+    use crate::json::{JsonNumeric, JsonValue};
+    use crate::ParserErrorDetails;
     use bytesize::ByteSize;
     use chisel_common::char::coords::Coords;
     use chisel_common::relative_file;
+    use chisel_decoders::common::{DecodePolicy, DecoderErrorCode};
+    use chisel_decoders::Encoding;
+    use chisel_lexers::json::dialect::Dialect;
+    use chisel_lexers::json::lexer::{Lexer, LexerError, LexerErrorDetails, LexerResult};
+    use chisel_lexers::json::tokens::{PackedToken, Token};
+    use chisel_lexers::lex::Lex;
+    use chisel_lexers::position::{ColumnUnit, LineTerminators, PositionModel};
+    use std::borrow::Cow;
     use std::path::PathBuf;
     use std::time::Instant;
     use std::{env, fs};
@@ -196,6 +356,423 @@ mod tests {
         assert!(parsed.is_ok())
     }
 
+    #[test]
+    fn should_report_decoding_failures() {
+        let parser = Parser::default();
+        let parsed = parser.parse_bytes(b"{\"a\": \"b\xffc\"}");
+        let err = parsed.err().unwrap();
+        assert_eq!(
+            err.details,
+            ParserErrorDetails::Decode(DecoderErrorCode::InvalidByteSequence)
+        );
+        assert_eq!(err.coords.unwrap().line, 1);
+        assert_eq!(err.coords.unwrap().column, 9);
+    }
+
+    #[test]
+    fn should_parse_alternative_encodings() {
+        let source = "{\"emoji\": \"\u{1f600}\", \"values\": [1, 2.5, true]}";
+        let utf16le: Vec<u8> = source.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let utf16be: Vec<u8> = source.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let utf32le: Vec<u8> = source
+            .chars()
+            .flat_map(|c| (c as u32).to_le_bytes())
+            .collect();
+        let utf32be: Vec<u8> = source
+            .chars()
+            .flat_map(|c| (c as u32).to_be_bytes())
+            .collect();
+        let expected = format!("{:?}", Parser::default().parse_str(source).unwrap());
+        for (bytes, encoding) in [
+            (utf16le, Encoding::Utf16Le),
+            (utf16be, Encoding::Utf16Be),
+            (utf32le, Encoding::Utf32Le),
+            (utf32be, Encoding::Utf32Be),
+        ] {
+            let parser = Parser::with_encoding(encoding);
+            let parsed = parser.parse_bytes(&bytes).unwrap();
+            assert_eq!(format!("{:?}", parsed), expected);
+        }
+    }
+
+    #[test]
+    fn should_detect_encodings_automatically() {
+        let source = "\u{feff}{\"a\": [1, \"\u{1f600}\"]}";
+        let expected = format!("{:?}", Parser::default().parse_str(&source[3..]).unwrap());
+        let utf8 = source.as_bytes().to_vec();
+        let utf16le: Vec<u8> = source.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let utf32be: Vec<u8> = source
+            .chars()
+            .flat_map(|c| (c as u32).to_be_bytes())
+            .collect();
+        let parser = Parser::with_encoding(Encoding::Auto);
+        for (bytes, encoding) in [
+            (utf8, Encoding::Utf8),
+            (utf16le, Encoding::Utf16Le),
+            (utf32be, Encoding::Utf32Be),
+        ] {
+            let parsed = parser.parse_bytes(&bytes).unwrap();
+            assert_eq!(format!("{:?}", parsed), expected);
+            assert_eq!(parser.detected_encoding(), Some(encoding));
+        }
+    }
+
+    #[test]
+    fn should_skip_a_utf8_bom_by_default() {
+        let source = "\u{feff}{\"a\": [1, \"\u{1f600}\"]}";
+        let parser = Parser::default();
+        let expected = format!("{:?}", parser.parse_str(&source[3..]).unwrap());
+        let parsed = parser.parse_bytes(source.as_bytes()).unwrap();
+        assert_eq!(format!("{:?}", parsed), expected);
+        assert_eq!(parser.detected_encoding(), Some(Encoding::Utf8));
+        let parsed = parser.parse_str(source).unwrap();
+        assert_eq!(format!("{:?}", parsed), expected);
+        assert!(parser.parse_str("{}\u{feff}").is_err());
+    }
+
+    #[test]
+    fn should_report_unpaired_surrogates() {
+        let bytes: Vec<u8> = [0x5b_u16, 0xd800, 0x5d]
+            .iter()
+            .flat_map(|u| u.to_le_bytes())
+            .collect();
+        let parser = Parser::with_encoding(Encoding::Utf16Le);
+        let err = parser.parse_bytes(&bytes).err().unwrap();
+        assert_eq!(
+            err.details,
+            ParserErrorDetails::Decode(DecoderErrorCode::UnpairedSurrogate)
+        );
+    }
+
+    #[test]
+    fn should_parse_single_byte_encodings() {
+        let input = b"{\"caf\xe9\": \"\x93quoted\x94\"}";
+        let parser = Parser::with_encoding(Encoding::Latin1);
+        let debug = format!("{:?}", parser.parse_bytes(input).unwrap());
+        assert!(debug.contains("caf\u{e9}"));
+        assert!(debug.contains("\\u{93}quoted\\u{94}"));
+
+        let parser = Parser::with_encoding(Encoding::Windows1252);
+        let debug = format!("{:?}", parser.parse_bytes(input).unwrap());
+        assert!(debug.contains("caf\u{e9}"));
+        assert!(debug.contains("\u{201c}quoted\u{201d}"));
+    }
+
+    #[test]
+    fn should_apply_position_models() {
+        let error_position = |input: &str, model: PositionModel| {
+            let coords = Parser::default()
+                .with_position_model(model)
+                .parse_str(input)
+                .err()
+                .unwrap()
+                .coords
+                .unwrap();
+            (coords.line, coords.column)
+        };
+        let lone_crs = "[1,\r\r  tru]";
+        assert_eq!(error_position(lone_crs, PositionModel::default()), (3, 6));
+        let lf_only = PositionModel {
+            line_terminators: LineTerminators::Lf,
+            ..Default::default()
+        };
+        assert_eq!(error_position(lone_crs, lf_only), (1, 11));
+
+        let wide = "[\"\u{1f600}\", tru]";
+        for (column_unit, column) in [
+            (ColumnUnit::Chars, 10),
+            (ColumnUnit::Bytes, 13),
+            (ColumnUnit::Utf16, 11),
+        ] {
+            let model = PositionModel {
+                column_unit,
+                ..Default::default()
+            };
+            assert_eq!(error_position(wide, model), (1, column));
+        }
+    }
+
+    #[test]
+    fn should_borrow_strings_from_in_memory_input() {
+        let input = "{\"caf\u{e9}\": [\"\u{1f600}\", 1], \"b\": {\"c\": \"d\"}}";
+        let parser = Parser::default();
+        let borrowed = parser.parse_str(input).unwrap();
+        let expected = format!("{:?}", parser.parse(&mut input.chars()).unwrap());
+        assert_eq!(format!("{:?}", borrowed), expected);
+        assert_eq!(
+            format!("{:?}", parser.parse_bytes(input.as_bytes()).unwrap()),
+            expected
+        );
+        match borrowed {
+            JsonValue::Object(pairs) => {
+                assert!(pairs.iter().all(|kv| matches!(kv.key, Cow::Borrowed(_))));
+                match &pairs[0].value {
+                    JsonValue::Array(values) => {
+                        assert!(matches!(
+                            string_value(values[0].clone()),
+                            Some(Cow::Borrowed("\u{1f600}"))
+                        ))
+                    }
+                    _ => panic!("expected an array"),
+                }
+            }
+            _ => panic!("expected an object"),
+        }
+
+        // input which needs decoding can't be borrowed
+        let utf16: Vec<u8> = "[\"a\"]"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let parsed = Parser::with_encoding(Encoding::Utf16Le)
+            .parse_bytes(&utf16)
+            .unwrap();
+        assert!(
+            matches!(parsed, JsonValue::Array(values) if matches!(string_value(values[0].clone()), Some(Cow::Owned(_))))
+        );
+    }
+
+    /// The decoded value of a string, whether or not lazy strings are enabled
+    fn string_value(value: JsonValue) -> Option<Cow<str>> {
+        match value {
+            JsonValue::String(str) => Some(str),
+            JsonValue::LazyString(str) => Some(str.into_str()),
+            _ => None,
+        }
+    }
+
+    /// A stream of tokens which were recorded earlier
+    struct Replay<'a> {
+        tokens: std::vec::IntoIter<PackedToken<'a>>,
+        peeked: Option<PackedToken<'a>>,
+    }
+
+    impl<'a> Lex for Replay<'a> {
+        type Token = Token<'a>;
+        type Error = LexerError;
+
+        fn consume(&mut self) -> LexerResult<PackedToken<'a>> {
+            self.peek()?;
+            Ok(self.peeked.take().unwrap())
+        }
+
+        fn peek(&mut self) -> LexerResult<&PackedToken<'a>> {
+            if self.peeked.is_none() {
+                self.peeked = self.tokens.next();
+            }
+            self.peeked.as_ref().ok_or(LexerError {
+                details: LexerErrorDetails::EndOfInput,
+                coords: None,
+            })
+        }
+    }
+
+    #[test]
+    fn should_parse_replayed_tokens() {
+        let input = "{\"a\": [1, 2.5, {\"b\": null}], \"c\": true}";
+        let mut lexer = Lexer::from_slice(input);
+        let mut tokens = vec![];
+        loop {
+            let packed = lexer.consume().unwrap();
+            let finished = packed.0 == Token::EndOfInput;
+            tokens.push(packed);
+            if finished {
+                break;
+            }
+        }
+        let parser = Parser::default();
+        let expected = format!("{:?}", parser.parse_str(input).unwrap());
+        let mut replay = Replay {
+            tokens: tokens.clone().into_iter(),
+            peeked: None,
+        };
+        let parsed = parser.parse_tokens(&mut replay).unwrap();
+        assert_eq!(format!("{:?}", parsed), expected);
+
+        // a truncated stream of tokens is an error
+        tokens.remove(tokens.len() - 2);
+        let mut replay = Replay {
+            tokens: tokens.into_iter(),
+            peeked: None,
+        };
+        assert!(parser.parse_tokens(&mut replay).is_err());
+    }
+
+    #[test]
+    fn should_decode_escaped_strings() {
+        let input = r#"{"a\"b": ["\u00e9\n", "\ud83d\ude00"]}"#;
+        let parsed = Parser::default().parse_str(input).unwrap();
+        match parsed {
+            JsonValue::Object(pairs) => {
+                assert_eq!(pairs[0].key, "a\"b");
+                match &pairs[0].value {
+                    JsonValue::Array(values) => {
+                        assert_eq!(string_value(values[0].clone()).unwrap(), "\u{e9}\n");
+                        assert_eq!(string_value(values[1].clone()).unwrap(), "\u{1f600}");
+                    }
+                    _ => panic!("expected an array"),
+                }
+            }
+            _ => panic!("expected an object"),
+        }
+        let err = Parser::default().parse_str(r#"["\udc00"]"#).err().unwrap();
+        assert_eq!(err.coords.unwrap().column, 3);
+    }
+
+    #[test]
+    fn should_apply_lossy_decode_policies() {
+        let input = b"{\"a\": \"b\xffc\xc0\"}";
+        let parser = Parser::default().with_decode_policy(DecodePolicy::Replace);
+        let parsed = parser.parse_bytes(input).unwrap();
+        assert!(format!("{:?}", parsed).contains("b\u{fffd}c\u{fffd}"));
+        assert_eq!(parser.replacements(), 2);
+
+        let parser = Parser::with_encoding(Encoding::Utf8).with_decode_policy(DecodePolicy::Skip);
+        let parsed = format!("{:?}", parser.parse_bytes(input).unwrap());
+        assert!(parsed.contains("bc") && !parsed.contains('\u{fffd}'));
+        assert_eq!(parser.replacements(), 2);
+    }
+
+    #[test]
+    fn should_keep_integers_beyond_i64_exact() {
+        for (input, expected) in [
+            ("[12345678901234567890]", 12345678901234567890),
+            ("[18446744073709551000]", 18446744073709551000),
+        ] {
+            match Parser::default().parse_str(input).unwrap() {
+                JsonValue::Array(values) => match values.as_slice() {
+                    [JsonValue::Number(value)] => {
+                        assert_eq!(value.as_u64(), Ok(expected));
+                        assert_eq!(value.as_i128(), Ok(expected as i128));
+                        assert!(value.as_f64_lossless().is_err());
+                    }
+                    _ => panic!("expected a single number"),
+                },
+                _ => panic!("expected an array"),
+            }
+        }
+    }
+
+    #[test]
+    fn should_report_rounded_floats_with_or_without_lazy_numerics() {
+        let input = "[0.1, 1e23, 4096.0]";
+        match Parser::default().parse_str(input).unwrap() {
+            JsonValue::Array(values) => match values.as_slice() {
+                [JsonValue::Number(tenth), JsonValue::Number(big), JsonValue::Number(whole)] => {
+                    assert!(tenth.as_f64_lossless().is_err());
+                    assert!(big.as_f64_lossless().is_err());
+                    assert_eq!(whole.as_f64_lossless(), Ok(4096.0));
+                }
+                _ => panic!("expected three numbers"),
+            },
+            _ => panic!("expected an array"),
+        }
+    }
+
+    #[cfg(feature = "lazy-numerics")]
+    #[test]
+    fn should_preserve_numeric_fidelity() {
+        let input = "[18446744073709551615, 170141183460469231731687303715884105727, 0.1]";
+        match Parser::default().parse_str(input).unwrap() {
+            JsonValue::Array(values) => match values.as_slice() {
+                [JsonValue::Number(id), JsonValue::Number(big), JsonValue::Number(tenth)] => {
+                    assert_eq!(id.as_u64(), Ok(u64::MAX));
+                    assert_eq!(big.as_i128(), Ok(i128::MAX));
+                    assert!(big.as_u64().is_err());
+                    assert_eq!(tenth.to_string(), "0.1");
+                    assert!(tenth.as_f64_lossless().is_err());
+                }
+                _ => panic!("expected three numbers"),
+            },
+            _ => panic!("expected an array"),
+        }
+    }
+
+    #[test]
+    fn should_parse_json5() {
+        let parser = Parser::default().with_dialect(Dialect::Json5);
+        let json5 = parser.parse_file("fixtures/json5/example.json5").unwrap();
+        let json = Parser::default()
+            .parse_file("fixtures/json5/example.json")
+            .unwrap();
+        assert_eq!(format!("{:?}", json5), format!("{:?}", json));
+        assert!(Parser::default()
+            .parse_file("fixtures/json5/example.json5")
+            .is_err());
+
+        let values = match parser.parse_str("[Infinity, -Infinity, NaN, 'it\\'s']") {
+            Ok(JsonValue::Array(values)) => values,
+            _ => panic!("expected an array"),
+        };
+        let float = |value: &JsonValue| match value {
+            JsonValue::Number(JsonNumeric::Float(value)) => *value,
+            _ => panic!("expected a float"),
+        };
+        assert_eq!(float(&values[0]), f64::INFINITY);
+        assert_eq!(float(&values[1]), f64::NEG_INFINITY);
+        assert!(float(&values[2]).is_nan());
+        assert_eq!(string_value(values[3].clone()).unwrap(), "it's");
+
+        for (input, details, column) in [
+            ("{a: 1,, }", ParserErrorDetails::PairExpected, 7),
+            ("[1,,]", ParserErrorDetails::ValueExpected, 4),
+            (
+                "[1, Infinite]",
+                ParserErrorDetails::UnexpectedToken("Identifier(Infinite)".into()),
+                5,
+            ),
+        ] {
+            let err = parser.parse_str(input).err().unwrap();
+            assert_eq!(err.details, details);
+            assert_eq!(err.coords.unwrap().column, column);
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn should_parse_mapped_files() {
+        for f in fs::read_dir("fixtures/json/valid").unwrap() {
+            let path = f.unwrap().path();
+            let parser = Parser::default();
+            let buffered = format!("{:?}", parser.parse_file(&path).unwrap());
+            let mapped = format!("{:?}", parser.parse_mmap(&path).unwrap());
+            assert_eq!(buffered, mapped);
+        }
+        assert!(Parser::default()
+            .parse_mmap("fixtures/json/missing.json")
+            .is_err());
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    #[test]
+    fn should_parse_compressed_files() {
+        let parser = Parser::default();
+        let expected = format!(
+            "{:?}",
+            parser
+                .parse_file("fixtures/json/valid/simple_structure.json")
+                .unwrap()
+        );
+        let uncompressed = parser
+            .parse_file("fixtures/json/invalid/invalid_array_1.json")
+            .err()
+            .unwrap();
+        let mut extensions = vec![];
+        #[cfg(feature = "gzip")]
+        extensions.push("gz");
+        #[cfg(feature = "zstd")]
+        extensions.push("zst");
+        for extension in extensions {
+            let path = format!("fixtures/json/compressed/simple_structure.json.{extension}");
+            assert_eq!(format!("{:?}", parser.parse_file(&path).unwrap()), expected);
+
+            // error coordinates are relative to the decompressed text
+            let path = format!("fixtures/json/compressed/invalid_array_1.json.{extension}");
+            let err = parser.parse_file(&path).err().unwrap();
+            assert_eq!(err.to_string(), uncompressed.to_string());
+        }
+    }
+
     #[test]
     fn should_parse_lengthy_arrays() {
         let path = relative_file!("fixtures/json/valid/bc_block.json");
@@ -213,6 +790,41 @@ mod tests {
         println!("{parsed:?}");
         assert!(parsed.is_ok());
     }
+
+    #[test]
+    fn should_limit_the_depth_of_nesting() {
+        let parser = Parser::default().with_max_depth(3);
+        assert!(parser.parse_str("[{\"a\": [1]}, [], {}]").is_ok());
+        let err = parser.parse_str("[{\"a\": [[1]]}]").err().unwrap();
+        assert_eq!(err.details, ParserErrorDetails::MaximumDepthExceeded(3));
+        assert_eq!(err.coords.unwrap().column, 9);
+        let nested = "[".repeat(100_000);
+        let err = Parser::default().parse_str(&nested).err().unwrap();
+        assert_eq!(err.details, ParserErrorDetails::MaximumDepthExceeded(128));
+        assert_eq!(err.coords.unwrap().column, 129);
+    }
+
+    #[test]
+    fn should_conform_to_the_test_suite() {
+        let mut failures = vec![];
+        for f in fs::read_dir(specs::SUITE_DIRECTORY).unwrap() {
+            let path = f.unwrap().path();
+            let filename = path.file_name().unwrap().to_str().unwrap().to_string();
+            let parsed = Parser::default().parse_file(&path);
+            match specs::suite_expectation(&filename) {
+                Some(spec) if spec.accepted == parsed.is_ok() => (),
+                Some(spec) => failures.push(format!(
+                    "{filename} ({}): {:?}",
+                    spec.reason,
+                    parsed.as_ref().err()
+                )),
+                None => failures.push(format!("{filename}: missing from the table")),
+            }
+        }
+        failures.sort();
+        assert!(failures.is_empty(), "{:#?}", failures);
+    }
+
     #[test]
     fn should_successfully_handle_basic_invalid_inputs() {
         for spec in specs::invalid_json_specs() {
@@ -7,6 +7,7 @@ use std::fmt::Display;
 use chisel_common::char::span::Span;
 use chisel_json_pointer::JsonPointer;
 use chisel_lexers::json::numerics::LazyNumeric;
+use chisel_lexers::json::strings::LazyString;
 
 /// Enumeration of the various different matches that can be produced during a parse
 #[derive(PartialEq)]
@@ -27,6 +28,10 @@ pub enum Match<'a> {
     EndArray,
     /// Emitted when a string is matched
     String(Cow<'a, str>),
+    /// Emitted in place of [Match::String] when a string is matched and the `lazy-strings` feature
+    /// is enabled, so that a given build only ever emits one of the two. [LazyString::into_str]
+    /// gives the same value that [Match::String] would have held
+    LazyString(LazyString<'a>),
     /// Emitted when an integer is matched
     Integer(i64),
     /// Emitted when a float is matched
@@ -50,12 +55,10 @@ impl<'a> Display for Match<'a> {
             Match::StartArray => write!(f, "StartArray"),
             Match::EndArray => write!(f, "EndArray"),
             Match::String(value) => write!(f, "String({})", value),
+            Match::LazyString(value) => write!(f, "LazyString({})", value),
             Match::Integer(value) => write!(f, "Integer({})", value),
             Match::Float(value) => write!(f, "Float({})", value),
-            Match::Numeric(value) => {
-                let fval: f64 = value.into();
-                write!(f, "Lazy({})", fval)
-            }
+            Match::Numeric(value) => write!(f, "Lazy({})", value),
             Match::Boolean(b) => write!(f, "Boolean({})", b),
             Match::Null => write!(f, "Null"),
         }
@@ -83,16 +86,9 @@ impl<'a> Event<'a> {
 
 impl<'a> Display for Event<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.pointer.is_some() {
-            write!(
-                f,
-                "Event[{}, {}, {}]",
-                self.matched,
-                self.span,
-                self.pointer.unwrap()
-            )
-        } else {
-            write!(f, "Event[{}, {}]", self.matched, self.span)
+        match self.pointer {
+            Some(pointer) => write!(f, "Event[{}, {}, {}]", self.matched, self.span, pointer),
+            None => write!(f, "Event[{}, {}]", self.matched, self.span),
         }
     }
 }
@@ -1,18 +1,331 @@
-use chisel_lexers::json::numerics::LazyNumeric;
+use chisel_decoders::auto::detect_encoding;
+use chisel_decoders::common::{CharIterSource, CharSource, DecodePolicy};
+#[cfg(feature = "mmap")]
+use chisel_decoders::mmap::MappedFile;
+use chisel_decoders::{new_decoder_with_policy, Encoding};
+use chisel_lexers::json::dialect::Dialect;
+use chisel_lexers::json::lexer::{Lexer, LexerError};
+#[cfg(feature = "big-decimal")]
+use chisel_lexers::json::numerics::BigDecimal;
+use chisel_lexers::json::numerics::{LazyNumeric, NumericError};
+use chisel_lexers::json::strings::LazyString;
+use chisel_lexers::json::tokens::Token;
+use chisel_lexers::lex::Lex;
+use chisel_lexers::position::PositionModel;
+use chisel_lexers::scanner::Scanner;
+use chisel_lexers::slice::SliceScanner;
 use std::borrow::Cow;
-use std::fmt::Debug;
+use std::cell::Cell;
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::{parser_error, ParserError, ParserErrorDetails, ParserResult};
 
 /// The JSON DOM parser
 pub mod dom;
 
 pub mod events;
+#[cfg(test)]
+mod properties;
 /// The JSON SAX parser
 pub mod sax;
 #[cfg(test)]
 pub(crate) mod specs;
 
+/// The default limit on the depth to which objects and arrays may be nested. The parsers are
+/// recursive, so a limit is needed to stop hostile input from exhausting the stack
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Open a file ready for parsing. If either the `gzip` or `zstd` features are enabled, then any
+/// compressed content is transparently decompressed
+pub(crate) fn open_file<PathLike: AsRef<Path>>(path: PathLike) -> ParserResult<Box<dyn BufRead>> {
+    let reader = match File::open(&path) {
+        Ok(f) => BufReader::new(f),
+        Err(_) => return parser_error!(ParserErrorDetails::InvalidFile),
+    };
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    {
+        use chisel_decoders::compression::DecompressingReader;
+        match DecompressingReader::new(reader) {
+            Ok(reader) => Ok(Box::new(reader)),
+            Err(_) => parser_error!(ParserErrorDetails::StreamFailure),
+        }
+    }
+    #[cfg(not(any(feature = "gzip", feature = "zstd")))]
+    {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Check whether a byte slice can be parsed in place as a string slice, rather than going through a
+/// decoder. This is only the case if the selected [Encoding] is compatible with UTF-8 and the bytes
+/// are valid UTF-8. Anything else, including input which starts with a BOM, is left to the decoders
+pub(crate) fn as_str(bytes: &[u8], encoding: Encoding) -> Option<&str> {
+    let compatible = match encoding {
+        Encoding::Utf8 => !bytes.starts_with(&[0xef, 0xbb, 0xbf]),
+        Encoding::Ascii => bytes.is_ascii(),
+        Encoding::Auto => detect_encoding(bytes) == (Encoding::Utf8, 0),
+        _ => false,
+    };
+    if compatible {
+        std::str::from_utf8(bytes).ok()
+    } else {
+        None
+    }
+}
+
+/// The options shared by the DOM and SAX parsers, which control how the input is decoded and
+/// lexed, and what is accepted by the parser
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParserOptions {
+    /// The encoding of the input
+    pub(crate) encoding: Encoding,
+    /// How malformed input should be dealt with during decoding
+    pub(crate) policy: DecodePolicy,
+    /// How the positions reported in spans and errors are calculated
+    pub(crate) position_model: PositionModel,
+    /// The maximum depth to which objects and arrays may be nested
+    pub(crate) max_depth: usize,
+    /// The dialect of JSON to be parsed
+    pub(crate) dialect: Dialect,
+}
+
+impl Default for ParserOptions {
+    /// The default encoding is Utf-8, malformed input is rejected and only strict JSON is accepted
+    fn default() -> Self {
+        Self {
+            encoding: Default::default(),
+            policy: Default::default(),
+            position_model: Default::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            dialect: Default::default(),
+        }
+    }
+}
+
+impl ParserOptions {
+    /// Set the [Encoding] used to decode the input
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Set the [DecodePolicy] used to deal with malformed input
+    pub fn with_decode_policy(mut self, policy: DecodePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the [PositionModel] used to calculate the positions reported in spans and errors
+    pub fn with_position_model(mut self, model: PositionModel) -> Self {
+        self.position_model = model;
+        self
+    }
+
+    /// Set the maximum depth to which objects and arrays may be nested, which defaults to
+    /// [DEFAULT_MAX_DEPTH]. Anything nested more deeply results in a
+    /// [ParserErrorDetails::MaximumDepthExceeded] error
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Set the [Dialect] of JSON to be parsed, which defaults to strict JSON. Parsing JSON5 allows
+    /// for comments, trailing commas, unquoted keys and the other extensions listed against
+    /// [Dialect::Json5]
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+/// Implemented by each of the parsers, in order to parse a complete JSON text from a stream of
+/// [Token]s
+pub(crate) trait ParseRoot<'a> {
+    /// The result of a successful parse
+    type Output;
+
+    /// Parse a single value, which must be followed by the end of the input
+    fn parse_root<L: Lex<Token = Token<'a>, Error = LexerError>>(
+        &mut self,
+        lexer: &mut L,
+    ) -> ParserResult<Self::Output>;
+}
+
+/// The entry points shared by the parsers. Each kind of input is turned into a stream of [Token]s
+/// according to a set of [ParserOptions], and then handed over to an implementation of
+/// [ParseRoot]. Also keeps track of what happened to the input during the last parse
+pub(crate) struct Frontend {
+    /// The options in effect for each parse
+    pub(crate) options: ParserOptions,
+    /// The number of malformed sequences replaced or skipped during the last parse
+    replacements: Cell<usize>,
+    /// The encoding which the input was decoded from during the last parse
+    detected_encoding: Cell<Option<Encoding>>,
+}
+
+impl Frontend {
+    pub fn new(options: ParserOptions) -> Self {
+        Frontend {
+            options,
+            replacements: Cell::new(0),
+            detected_encoding: Cell::new(None),
+        }
+    }
+
+    pub fn replacements(&self) -> usize {
+        self.replacements.get()
+    }
+
+    pub fn detected_encoding(&self) -> Option<Encoding> {
+        self.detected_encoding.get()
+    }
+
+    pub fn parse_file<'a, PathLike: AsRef<Path>, R: ParseRoot<'a>>(
+        &self,
+        path: PathLike,
+        root: R,
+    ) -> ParserResult<R::Output> {
+        let mut reader = open_file(path)?;
+        self.parse_buffer(&mut reader, root)
+    }
+
+    #[cfg(feature = "mmap")]
+    pub fn parse_mmap<'a, PathLike: AsRef<Path>, R: ParseRoot<'a>>(
+        &self,
+        path: PathLike,
+        root: R,
+    ) -> ParserResult<R::Output> {
+        match MappedFile::open(&path) {
+            Ok(mapped) => self.parse_buffer(&mut mapped.reader(), root),
+            Err(_) => {
+                parser_error!(ParserErrorDetails::InvalidFile)
+            }
+        }
+    }
+
+    /// Bytes which can be parsed in place are, and anything else goes through a decoder
+    pub fn parse_bytes<'a, R: ParseRoot<'a>>(
+        &self,
+        bytes: &'a [u8],
+        root: R,
+    ) -> ParserResult<R::Output> {
+        match as_str(bytes, self.options.encoding) {
+            Some(str) => self.parse_slice(str, root),
+            None => self.parse_encoded(bytes, self.options.encoding, root),
+        }
+    }
+
+    /// Rust strings are always UTF-8, so the selected [Encoding] is ignored. Input which starts
+    /// with a byte order mark goes through the UTF-8 decoder, so that the BOM is skipped
+    pub fn parse_str<'a, R: ParseRoot<'a>>(
+        &self,
+        str: &'a str,
+        root: R,
+    ) -> ParserResult<R::Output> {
+        match as_str(str.as_bytes(), Encoding::Utf8) {
+            Some(str) => self.parse_slice(str, root),
+            None => self.parse_encoded(str.as_bytes(), Encoding::Utf8, root),
+        }
+    }
+
+    pub fn parse_buffer<'a, R: ParseRoot<'a>>(
+        &self,
+        buffer: &mut impl BufRead,
+        root: R,
+    ) -> ParserResult<R::Output> {
+        let mut chars = new_decoder_with_policy(buffer, self.options.encoding, self.options.policy);
+        self.parse_source(&mut chars, root)
+    }
+
+    pub fn parse<'a, R: ParseRoot<'a>>(
+        &self,
+        chars: &mut impl Iterator<Item = char>,
+        root: R,
+    ) -> ParserResult<R::Output> {
+        self.parse_source(&mut CharIterSource::new(chars), root)
+    }
+
+    pub fn parse_source<'a, R: ParseRoot<'a>>(
+        &self,
+        chars: &mut impl CharSource,
+        mut root: R,
+    ) -> ParserResult<R::Output> {
+        let scanner = Scanner::with_position_model(&mut *chars, self.options.position_model);
+        let parsed =
+            root.parse_root(&mut Lexer::with_scanner(scanner).with_dialect(self.options.dialect));
+        self.replacements.set(chars.replacements());
+        self.detected_encoding.set(chars.encoding());
+        parsed
+    }
+
+    pub fn parse_tokens<'a, L, R>(&self, lexer: &mut L, mut root: R) -> ParserResult<R::Output>
+    where
+        L: Lex<Token = Token<'a>, Error = LexerError>,
+        R: ParseRoot<'a>,
+    {
+        self.replacements.set(0);
+        self.detected_encoding.set(None);
+        root.parse_root(lexer)
+    }
+
+    /// Parse the contents of a byte slice by going through the decoder for a given [Encoding]
+    fn parse_encoded<'a, R: ParseRoot<'a>>(
+        &self,
+        bytes: &[u8],
+        encoding: Encoding,
+        root: R,
+    ) -> ParserResult<R::Output> {
+        let mut reader = BufReader::new(bytes);
+        let mut chars = new_decoder_with_policy(&mut reader, encoding, self.options.policy);
+        self.parse_source(&mut chars, root)
+    }
+
+    /// Parse a string slice in place, without going through a decoder
+    fn parse_slice<'a, R: ParseRoot<'a>>(
+        &self,
+        str: &'a str,
+        mut root: R,
+    ) -> ParserResult<R::Output> {
+        self.replacements.set(0);
+        self.detected_encoding.set(Some(Encoding::Utf8));
+        let scanner = SliceScanner::with_position_model(str, self.options.position_model);
+        root.parse_root(&mut Lexer::with_scanner(scanner).with_dialect(self.options.dialect))
+    }
+}
+
+/// Convert a [Token] into the key of a KV pair, if it's allowed to be one. JSON only allows for
+/// string keys, whereas JSON5 also allows for unquoted identifiers (which includes the likes of
+/// `null` and `true`)
+pub(crate) fn object_key(token: Token, dialect: Dialect) -> Option<Cow<str>> {
+    match token {
+        Token::Str(key) => Some(key),
+        Token::LazyString(key) => Some(key.into_str()),
+        Token::Identifier(key) if dialect == Dialect::Json5 => Some(key),
+        Token::Null if dialect == Dialect::Json5 => Some(Cow::Borrowed("null")),
+        Token::Boolean(value) if dialect == Dialect::Json5 => {
+            Some(Cow::Borrowed(if value { "true" } else { "false" }))
+        }
+        _ => None,
+    }
+}
+
+/// Convert an unsigned JSON5 identifier into the non-finite value it represents, if any
+pub(crate) fn non_finite(identifier: &str, dialect: Dialect) -> Option<f64> {
+    match identifier {
+        "Infinity" if dialect == Dialect::Json5 => Some(f64::INFINITY),
+        "NaN" if dialect == Dialect::Json5 => Some(f64::NAN),
+        _ => None,
+    }
+}
+
 /// Enumeration of possible numeric types. Lazy numerics will be returned by the lexer backend if
-/// the associated feature is enabled, otherwise either floats or integer numerics are spat out
+/// the associated feature is enabled, otherwise either floats or integer numerics are spat out.
+/// Enable the `lazy-numerics` feature in order to preserve the exact value of every number.
+/// Without it, integers outside the range of an [i64] are still returned as [JsonNumeric::Lazy]
+/// so that their exact value is kept, but all other non-integral values are parsed as floats
 #[derive(Debug, Clone)]
 pub enum JsonNumeric {
     Float(f64),
@@ -20,11 +333,94 @@ pub enum JsonNumeric {
     Lazy(LazyNumeric),
 }
 
+impl JsonNumeric {
+    /// Convert into a [u64], failing if the value has a fractional part or is out of range
+    pub fn as_u64(&self) -> Result<u64, NumericError> {
+        match self {
+            JsonNumeric::Lazy(lazy) => lazy.as_u64(),
+            _ => u64::try_from(self.as_i128()?)
+                .map_err(|_| NumericError::OutOfRange(self.to_string())),
+        }
+    }
+
+    /// Convert into an [i128], failing if the value has a fractional part or is out of range.
+    /// Floats with a magnitude of 2^53 or more may have been rounded when they were parsed, and so
+    /// fail with [NumericError::PrecisionLoss]
+    pub fn as_i128(&self) -> Result<i128, NumericError> {
+        match self {
+            JsonNumeric::Float(value) if !value.is_finite() => {
+                Err(NumericError::OutOfRange(self.to_string()))
+            }
+            JsonNumeric::Float(value) if *value < -(2f64.powi(127)) || *value >= 2f64.powi(127) => {
+                Err(NumericError::OutOfRange(self.to_string()))
+            }
+            JsonNumeric::Float(value) if !is_exact_float(*value) => {
+                Err(NumericError::PrecisionLoss(self.to_string()))
+            }
+            JsonNumeric::Float(value) => Ok(*value as i128),
+            JsonNumeric::Integer(value) => Ok(*value as i128),
+            JsonNumeric::Lazy(lazy) => lazy.as_i128(),
+        }
+    }
+
+    /// Convert into an [f64], failing if the value can't be represented exactly. A
+    /// [JsonNumeric::Float] no longer holds the representation it was parsed from, so the only
+    /// floats known to be exact are integers with a magnitude below 2^53.  Anything else, such as
+    /// `0.1`, is reported as a potential [NumericError::PrecisionLoss]. Enable the `lazy-numerics`
+    /// feature in order to check other values against the text they were parsed from
+    pub fn as_f64_lossless(&self) -> Result<f64, NumericError> {
+        match self {
+            JsonNumeric::Float(value) if !value.is_finite() => {
+                Err(NumericError::OutOfRange(self.to_string()))
+            }
+            JsonNumeric::Float(value) if !is_exact_float(*value) => {
+                Err(NumericError::PrecisionLoss(self.to_string()))
+            }
+            JsonNumeric::Float(value) => Ok(*value),
+            JsonNumeric::Integer(value) if (*value as f64) as i128 == *value as i128 => {
+                Ok(*value as f64)
+            }
+            JsonNumeric::Integer(_) => Err(NumericError::PrecisionLoss(self.to_string())),
+            JsonNumeric::Lazy(lazy) => lazy.as_f64_lossless(),
+        }
+    }
+
+    /// Convert into a [BigDecimal], which holds the exact value of the number
+    #[cfg(feature = "big-decimal")]
+    pub fn as_big_decimal(&self) -> Result<BigDecimal, NumericError> {
+        match self {
+            JsonNumeric::Float(value) => {
+                BigDecimal::try_from(*value).map_err(|_| NumericError::OutOfRange(self.to_string()))
+            }
+            JsonNumeric::Integer(value) => Ok(BigDecimal::from(*value)),
+            JsonNumeric::Lazy(lazy) => lazy.as_big_decimal(),
+        }
+    }
+}
+
+/// Checks whether a float is known to hold exactly the value it was parsed from, which is only the
+/// case for integers strictly within ±2^53. Every integer in that range can be represented, so it
+/// can't have been rounded from a neighbouring value
+#[inline]
+fn is_exact_float(value: f64) -> bool {
+    value.fract() == 0.0 && value.abs() < 2f64.powi(f64::MANTISSA_DIGITS as i32)
+}
+
+impl Display for JsonNumeric {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonNumeric::Float(value) => write!(f, "{}", value),
+            JsonNumeric::Integer(value) => write!(f, "{}", value),
+            JsonNumeric::Lazy(lazy) => write!(f, "{}", lazy),
+        }
+    }
+}
+
 /// Structure representing a JSON key value pair
 #[derive(Debug, Clone)]
 pub struct JsonKeyValue<'a> {
     /// The key for the pair
-    pub key: String,
+    pub key: Cow<'a, str>,
     /// The JSON value
     pub value: JsonValue<'a>,
 }
@@ -38,6 +434,11 @@ pub enum JsonValue<'a> {
     Array(Vec<JsonValue<'a>>),
     /// Canonical string value
     String(Cow<'a, str>),
+    /// String value, the escape sequences of which are decoded on demand. Returned in place of
+    /// [JsonValue::String] if the `lazy-strings` feature is enabled, so that a given build only
+    /// ever produces one of the two variants for string values. [LazyString::into_str] gives the
+    /// same value that [JsonValue::String] would have held. Object keys are always decoded
+    LazyString(LazyString<'a>),
     /// Number value which will be a member of the union [JsonNumeric]
     Number(JsonNumeric),
     /// Floating point numeric value
@@ -49,3 +450,122 @@ pub enum JsonValue<'a> {
     /// Canonical null value
     Null,
 }
+
+#[cfg(test)]
+mod tests {
+    use chisel_lexers::json::dialect::Dialect;
+    use chisel_lexers::json::numerics::{LazyNumeric, NumericError};
+
+    use crate::json::{dom, sax, JsonNumeric, ParserOptions};
+    use crate::ParserErrorDetails;
+
+    #[test]
+    fn should_apply_the_same_options_in_both_parsers() {
+        let options = ParserOptions::default()
+            .with_dialect(Dialect::Json5)
+            .with_max_depth(1);
+        let dom = dom::Parser::with_options(options);
+        let sax = sax::Parser::with_options(options);
+        assert_eq!(*dom.options(), options);
+        assert_eq!(*sax.options(), options);
+        assert!(dom.parse_str("{a: 1,}").is_ok());
+        assert!(sax.parse_str("{a: 1,}", &mut |_e| Ok(())).is_ok());
+        let err = dom.parse_str("[[1]]").err().unwrap();
+        assert_eq!(err.details, ParserErrorDetails::MaximumDepthExceeded(1));
+        let err = sax.parse_str("[[1]]", &mut |_e| Ok(())).err().unwrap();
+        assert_eq!(err.details, ParserErrorDetails::MaximumDepthExceeded(1));
+        assert_eq!(
+            dom::Parser::default()
+                .with_dialect(Dialect::Json5)
+                .with_max_depth(1)
+                .options(),
+            &options
+        );
+    }
+
+    #[test]
+    fn should_convert_numerics_without_hiding_precision_loss() {
+        assert_eq!(JsonNumeric::Integer(42).as_u64(), Ok(42));
+        assert_eq!(
+            JsonNumeric::Integer(-42).as_u64(),
+            Err(NumericError::OutOfRange("-42".into()))
+        );
+        assert_eq!(
+            JsonNumeric::Integer(i64::MAX).as_f64_lossless(),
+            Err(NumericError::PrecisionLoss(i64::MAX.to_string()))
+        );
+        assert_eq!(JsonNumeric::Float(4096.0).as_i128(), Ok(4096));
+        assert_eq!(
+            JsonNumeric::Float(1e20).as_i128(),
+            Err(NumericError::PrecisionLoss(1e20.to_string()))
+        );
+        assert_eq!(
+            JsonNumeric::Float(1.2345678901234567e19).as_u64(),
+            Err(NumericError::PrecisionLoss(
+                1.2345678901234567e19.to_string()
+            ))
+        );
+        assert_eq!(
+            JsonNumeric::Float(2f64.powi(53)).as_f64_lossless(),
+            Err(NumericError::PrecisionLoss(2f64.powi(53).to_string()))
+        );
+        assert_eq!(
+            JsonNumeric::Float(-(2f64.powi(53))).as_i128(),
+            Err(NumericError::PrecisionLoss((-(2f64.powi(53))).to_string()))
+        );
+        assert_eq!(
+            JsonNumeric::Float(2f64.powi(53) - 1.0).as_f64_lossless(),
+            Ok(2f64.powi(53) - 1.0)
+        );
+        assert_eq!(JsonNumeric::Float(-4096.0).as_f64_lossless(), Ok(-4096.0));
+        assert_eq!(
+            JsonNumeric::Float(0.5).as_f64_lossless(),
+            Err(NumericError::PrecisionLoss("0.5".into()))
+        );
+        assert_eq!(
+            JsonNumeric::Float(0.1).as_f64_lossless(),
+            Err(NumericError::PrecisionLoss("0.1".into()))
+        );
+        assert_eq!(
+            JsonNumeric::Lazy(LazyNumeric::new(b"0.1")).as_f64_lossless(),
+            Err(NumericError::PrecisionLoss("0.1".into()))
+        );
+        assert_eq!(
+            JsonNumeric::Float(1.5).as_i128(),
+            Err(NumericError::PrecisionLoss("1.5".into()))
+        );
+        assert_eq!(
+            JsonNumeric::Float(1e40).as_i128(),
+            Err(NumericError::OutOfRange(1e40.to_string()))
+        );
+        assert_eq!(
+            JsonNumeric::Float(f64::INFINITY).as_u64(),
+            Err(NumericError::OutOfRange("inf".into()))
+        );
+        let lazy = JsonNumeric::Lazy(LazyNumeric::new(b"18446744073709551615"));
+        assert_eq!(lazy.as_u64(), Ok(u64::MAX));
+        assert_eq!(
+            lazy.as_f64_lossless(),
+            Err(NumericError::PrecisionLoss("18446744073709551615".into()))
+        );
+    }
+
+    #[cfg(feature = "big-decimal")]
+    #[test]
+    fn should_convert_numerics_into_big_decimals() {
+        use chisel_lexers::json::numerics::BigDecimal;
+        use std::str::FromStr;
+        assert_eq!(
+            JsonNumeric::Float(0.1).as_big_decimal(),
+            Ok(
+                BigDecimal::from_str("0.1000000000000000055511151231257827021181583404541015625")
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            JsonNumeric::Integer(7).as_big_decimal(),
+            Ok(BigDecimal::from(7))
+        );
+        assert!(JsonNumeric::Float(f64::NAN).as_big_decimal().is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Property based tests, which check that the parsers never panic regardless of the input they're
+//! given.  Any sequence of bytes must result in either an [Ok] or an [Err], whichever encoding,
+//! decode policy, dialect and entry point is used
+use std::fs;
+use std::io::BufReader;
+
+use chisel_decoders::common::DecodePolicy;
+use chisel_decoders::Encoding;
+use chisel_lexers::json::dialect::Dialect;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::select;
+
+use crate::json::{dom, sax, specs};
+
+/// A single change made to an input, where positions are taken modulo the length of the input
+#[derive(Debug, Clone)]
+enum Mutation {
+    Replace(usize, u8),
+    Insert(usize, u8),
+    Remove(usize),
+    Duplicate(usize, usize),
+    Truncate(usize),
+}
+
+/// The fixtures used as the starting point for mutated inputs. Larger files are skipped, so that
+/// the mutations are reasonably likely to be noticed
+fn seeds() -> Vec<Vec<u8>> {
+    [
+        "fixtures/json/valid",
+        "fixtures/json/invalid",
+        "fixtures/json5",
+        specs::SUITE_DIRECTORY,
+    ]
+    .iter()
+    .flat_map(|dir| fs::read_dir(dir).unwrap())
+    .map(|f| fs::read(f.unwrap().path()).unwrap())
+    .filter(|bytes| bytes.len() <= 4096)
+    .collect()
+}
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+    prop_oneof![
+        (any::<usize>(), any::<u8>()).prop_map(|(at, byte)| Mutation::Replace(at, byte)),
+        (any::<usize>(), any::<u8>()).prop_map(|(at, byte)| Mutation::Insert(at, byte)),
+        any::<usize>().prop_map(Mutation::Remove),
+        (any::<usize>(), 1..64usize).prop_map(|(at, len)| Mutation::Duplicate(at, len)),
+        any::<usize>().prop_map(Mutation::Truncate),
+    ]
+}
+
+fn mutate(mut bytes: Vec<u8>, mutations: &[Mutation]) -> Vec<u8> {
+    for mutation in mutations {
+        if bytes.is_empty() {
+            break;
+        }
+        let len = bytes.len();
+        match *mutation {
+            Mutation::Replace(at, byte) => bytes[at % len] = byte,
+            Mutation::Insert(at, byte) => bytes.insert(at % (len + 1), byte),
+            Mutation::Remove(at) => {
+                bytes.remove(at % len);
+            }
+            Mutation::Duplicate(at, count) => {
+                let start = at % len;
+                let copied = bytes[start..(start + count).min(len)].to_vec();
+                bytes.splice(start..start, copied);
+            }
+            Mutation::Truncate(at) => bytes.truncate(at % len),
+        }
+    }
+    bytes
+}
+
+fn encoding() -> impl Strategy<Value = Encoding> {
+    select(vec![
+        Encoding::Auto,
+        Encoding::Utf8,
+        Encoding::Utf16Le,
+        Encoding::Utf16Be,
+        Encoding::Utf32Le,
+        Encoding::Utf32Be,
+        Encoding::Ascii,
+        Encoding::Latin1,
+        Encoding::Windows1252,
+    ])
+}
+
+fn policy() -> impl Strategy<Value = DecodePolicy> {
+    select(vec![
+        DecodePolicy::Strict,
+        DecodePolicy::Replace,
+        DecodePolicy::Skip,
+    ])
+}
+
+fn dialect() -> impl Strategy<Value = Dialect> {
+    select(vec![Dialect::Json, Dialect::Json5])
+}
+
+/// Run some input through both of the parsers, using both the in-memory and streaming paths
+fn parse_everywhere(bytes: &[u8], encoding: Encoding, policy: DecodePolicy, dialect: Dialect) {
+    let parser = dom::Parser::with_encoding(encoding)
+        .with_decode_policy(policy)
+        .with_dialect(dialect);
+    let _ = parser.parse_bytes(bytes);
+    let _ = parser.parse_buffer(&mut BufReader::new(bytes));
+    let parser = sax::Parser::with_encoding(encoding)
+        .with_decode_policy(policy)
+        .with_dialect(dialect);
+    let _ = parser.parse_bytes(bytes, &mut |_e| Ok(()));
+    let _ = parser.parse_buffer(&mut BufReader::new(bytes), &mut |_e| Ok(()));
+}
+
+proptest! {
+    #[test]
+    fn should_never_panic_on_arbitrary_bytes(
+        bytes in vec(any::<u8>(), 0..512),
+        encoding in encoding(),
+        policy in policy(),
+        dialect in dialect()
+    ) {
+        parse_everywhere(&bytes, encoding, policy, dialect);
+    }
+
+    #[test]
+    fn should_never_panic_on_json_like_text(
+        text in "[\\[\\]{}:,\"'\\\\/*0-9eE.+\\-a-zINx$_ \t\r\n\u{0}-\u{1f}\u{a0}\u{2028}\u{fffd}\u{1f600}]{0,256}",
+        dialect in dialect()
+    ) {
+        parse_everywhere(text.as_bytes(), Encoding::Utf8, DecodePolicy::Strict, dialect);
+        let _ = dom::Parser::default().with_dialect(dialect).parse(&mut text.chars());
+    }
+
+    #[test]
+    fn should_never_panic_on_mutated_fixtures(
+        seed in select(seeds()),
+        mutations in vec(mutation(), 1..16),
+        encoding in encoding(),
+        policy in policy(),
+        dialect in dialect()
+    ) {
+        parse_everywhere(&mutate(seed, &mutations), encoding, policy, dialect);
+    }
+}
@@ -1,16 +1,21 @@
 //! The SAX parser
 use std::borrow::Cow;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
 use std::path::Path;
 
 use chisel_common::char::coords::Coords;
-use chisel_decoders::{default_decoder, new_decoder, Encoding};
+use chisel_common::char::span::Span;
+use chisel_decoders::common::{CharSource, DecodePolicy};
+use chisel_decoders::Encoding;
 use chisel_json_pointer::JsonPointer;
-use chisel_lexers::json::lexer::Lexer;
+use chisel_lexers::json::dialect::Dialect;
+use chisel_lexers::json::lexer::LexerError;
 use chisel_lexers::json::tokens::Token;
+use chisel_lexers::lex::Lex;
+use chisel_lexers::position::PositionModel;
 
 use crate::json::events::{Event, Match};
+use crate::json::{non_finite, object_key, Frontend, ParseRoot, ParserOptions};
 use crate::parser_error;
 use crate::{ParserError, ParserErrorDetails, ParserResult};
 
@@ -33,22 +38,74 @@ macro_rules! emit_event {
 
 /// Main JSON parser struct
 pub struct Parser {
-    encoding: Encoding,
+    /// The options in effect, along with the state of the last parse
+    frontend: Frontend,
 }
 
 impl Default for Parser {
-    /// The default encoding is Utf-8
+    /// The default encoding is Utf-8, and malformed input is rejected
     fn default() -> Self {
-        Self {
-            encoding: Default::default(),
-        }
+        Self::with_options(ParserOptions::default())
     }
 }
 
 impl Parser {
     /// Create a new instance of the parser using a specific [Encoding]
     pub fn with_encoding(encoding: Encoding) -> Self {
-        Self { encoding }
+        Self::with_options(ParserOptions::default().with_encoding(encoding))
+    }
+
+    /// Create a new instance of the parser using a given set of [ParserOptions]
+    pub fn with_options(options: ParserOptions) -> Self {
+        Self {
+            frontend: Frontend::new(options),
+        }
+    }
+
+    /// Set the [DecodePolicy] used to deal with malformed input
+    pub fn with_decode_policy(mut self, policy: DecodePolicy) -> Self {
+        self.frontend.options = self.frontend.options.with_decode_policy(policy);
+        self
+    }
+
+    /// Set the [PositionModel] used to calculate the positions reported in spans and errors
+    pub fn with_position_model(mut self, model: PositionModel) -> Self {
+        self.frontend.options = self.frontend.options.with_position_model(model);
+        self
+    }
+
+    /// Set the maximum depth to which objects and arrays may be nested, which defaults to
+    /// [DEFAULT_MAX_DEPTH](crate::json::DEFAULT_MAX_DEPTH). Anything nested more deeply results in a
+    /// [ParserErrorDetails::MaximumDepthExceeded] error
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.frontend.options = self.frontend.options.with_max_depth(depth);
+        self
+    }
+
+    /// Set the [Dialect] of JSON to be parsed, which defaults to strict JSON. Parsing JSON5 allows
+    /// for comments, trailing commas, unquoted keys and the other extensions listed against
+    /// [Dialect::Json5]
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.frontend.options = self.frontend.options.with_dialect(dialect);
+        self
+    }
+
+    /// The [ParserOptions] in effect for each parse
+    pub fn options(&self) -> &ParserOptions {
+        &self.frontend.options
+    }
+
+    /// The number of malformed sequences which were replaced or skipped during the last parse, as
+    /// a result of a lossy [DecodePolicy]
+    pub fn replacements(&self) -> usize {
+        self.frontend.replacements()
+    }
+
+    /// The [Encoding] which the input was decoded from during the last parse. When parsing with
+    /// [Encoding::Auto], this is the encoding which was detected. [None] if the last parse didn't
+    /// involve any decoding, because the input was supplied as [char]s or [Token]s
+    pub fn detected_encoding(&self) -> Option<Encoding> {
+        self.frontend.detected_encoding()
     }
 
     pub fn parse_file<PathLike: AsRef<Path>, Callback>(
@@ -59,18 +116,25 @@ impl Parser {
     where
         Callback: FnMut(&Event) -> ParserResult<()>,
     {
-        match File::open(&path) {
-            Ok(f) => {
-                let mut reader = BufReader::new(f);
-                let mut chars = new_decoder(&mut reader, self.encoding);
-                self.parse(&mut chars, cb)
-            }
-            Err(_) => {
-                parser_error!(ParserErrorDetails::InvalidFile)
-            }
-        }
+        self.frontend.parse_file(path, Handler { parser: self, cb })
     }
 
+    /// Parse the contents of a file, which is mapped into memory rather than read through a buffer.
+    /// This is generally the better option for very large files. The file must not be modified
+    /// whilst it is being parsed
+    #[cfg(feature = "mmap")]
+    pub fn parse_mmap<PathLike: AsRef<Path>, Callback>(
+        &self,
+        path: PathLike,
+        cb: &mut Callback,
+    ) -> ParserResult<()>
+    where
+        Callback: FnMut(&Event) -> ParserResult<()>,
+    {
+        self.frontend.parse_mmap(path, Handler { parser: self, cb })
+    }
+
+    /// Parse the contents of a byte slice, decoded using the selected [Encoding]
     pub fn parse_bytes<Callback>(&self, bytes: &[u8], cb: &mut Callback) -> ParserResult<()>
     where
         Callback: FnMut(&Event) -> ParserResult<()>,
@@ -78,11 +142,14 @@ impl Parser {
         if bytes.is_empty() {
             return parser_error!(ParserErrorDetails::ZeroLengthInput, Coords::default());
         }
-        let mut reader = BufReader::new(bytes);
-        let mut chars = default_decoder(&mut reader);
-        self.parse(&mut chars, cb)
+        self.frontend
+            .parse_bytes(bytes, Handler { parser: self, cb })
     }
 
+    /// Parse the contents of a string slice. Rust strings are always UTF-8, so the selected
+    /// [Encoding] is ignored.  The input is parsed in place, so no string values need to be copied.
+    /// Input which starts with a byte order mark goes through the UTF-8 decoder instead, so that
+    /// the BOM is skipped
     pub fn parse_str<Callback>(&self, str: &str, cb: &mut Callback) -> ParserResult<()>
     where
         Callback: FnMut(&Event) -> ParserResult<()>,
@@ -90,12 +157,11 @@ impl Parser {
         if str.is_empty() {
             return parser_error!(ParserErrorDetails::ZeroLengthInput, Coords::default());
         }
-        let mut reader = BufReader::new(str.as_bytes());
-        let mut chars = default_decoder(&mut reader);
-        self.parse(&mut chars, cb)
+        self.frontend.parse_str(str, Handler { parser: self, cb })
     }
 
-    /// Parse the contents extracted from an instance of [BufRead]
+    /// Parse the contents extracted from an instance of [BufRead], decoded using the selected
+    /// [Encoding]
     pub fn parse_buffer<Callback>(
         &self,
         buffer: &mut impl BufRead,
@@ -104,41 +170,93 @@ impl Parser {
     where
         Callback: FnMut(&Event) -> ParserResult<()>,
     {
-        let mut chars = default_decoder(buffer);
-        self.parse(&mut chars, cb)
+        self.frontend
+            .parse_buffer(buffer, Handler { parser: self, cb })
     }
 
+    /// Parse the contents of an [Iterator] over already decoded [char]s
     pub fn parse<Callback>(
         &self,
         chars: &mut impl Iterator<Item = char>,
         cb: &mut Callback,
     ) -> ParserResult<()>
+    where
+        Callback: FnMut(&Event) -> ParserResult<()>,
+    {
+        self.frontend.parse(chars, Handler { parser: self, cb })
+    }
+
+    /// Parse the contents of a [CharSource], such as one of the decoders. Any failure to decode
+    /// the input is reported as a [ParserErrorDetails::Decode] error
+    pub fn parse_source<Callback>(
+        &self,
+        chars: &mut impl CharSource,
+        cb: &mut Callback,
+    ) -> ParserResult<()>
+    where
+        Callback: FnMut(&Event) -> ParserResult<()>,
+    {
+        self.frontend
+            .parse_source(chars, Handler { parser: self, cb })
+    }
+
+    /// Parse a stream of [Token]s pulled from an implementation of [Lex]. This allows for the use
+    /// of an alternative lexer, or for tokens which have been recorded earlier to be replayed. The
+    /// stream must be terminated by a [Token::EndOfInput]
+    pub fn parse_tokens<'a, L, Callback>(
+        &self,
+        lexer: &mut L,
+        cb: &mut Callback,
+    ) -> ParserResult<()>
+    where
+        L: Lex<Token = Token<'a>, Error = LexerError>,
+        Callback: FnMut(&Event) -> ParserResult<()>,
+    {
+        self.frontend
+            .parse_tokens(lexer, Handler { parser: self, cb })
+    }
+
+    /// A JSON text is a single value of any kind, which must be followed by the end of the input
+    #[inline]
+    fn parse_root<'a, L: Lex<Token = Token<'a>, Error = LexerError>, Callback>(
+        &self,
+        lexer: &mut L,
+        cb: &mut Callback,
+    ) -> ParserResult<()>
     where
         Callback: FnMut(&Event) -> ParserResult<()>,
     {
         let mut pointer = JsonPointer::root();
-        let mut lexer = Lexer::new(chars);
+        match lexer.peek()? {
+            (
+                Token::EndOfInput
+                | Token::EndObject
+                | Token::EndArray
+                | Token::Colon
+                | Token::Comma,
+                span,
+            ) => return parser_error!(ParserErrorDetails::InvalidRootObject, span.start),
+            (_, span) => emit_event!(cb, Match::StartOfInput, *span)?,
+        }
+        self.parse_value(lexer, &mut pointer, cb)?;
         match lexer.consume()? {
-            (Token::StartObject, span) => {
-                emit_event!(cb, Match::StartOfInput, span)?;
-                emit_event!(cb, Match::StartObject, span, pointer)?;
-                self.parse_object(&mut lexer, &mut pointer, cb)
-            }
-            (Token::StartArray, span) => {
-                emit_event!(cb, Match::StartOfInput, span, pointer)?;
-                emit_event!(cb, Match::StartArray, span, pointer)?;
-                self.parse_array(&mut lexer, &mut pointer, cb)
-            }
-            (_, span) => {
-                parser_error!(ParserErrorDetails::InvalidRootObject, span.start)
+            (Token::EndOfInput, span) => emit_event!(cb, Match::EndOfInput, span),
+            (token, span) => {
+                parser_error!(
+                    ParserErrorDetails::UnexpectedToken(token.to_string()),
+                    span.start
+                )
             }
         }
     }
 
+    /// Parse a value, located by the given pointer. Each component of the pointer (other than the
+    /// root) corresponds to one of the objects and arrays enclosing the value, and so the length of
+    /// the pointer is used to limit the depth of nesting
     #[inline]
-    fn parse_value<Callback>(
+    fn parse_value<'a, L: Lex<Token = Token<'a>, Error = LexerError>, Callback>(
         &self,
-        lexer: &mut Lexer,
+        lexer: &mut L,
         pointer: &mut JsonPointer,
         cb: &mut Callback,
     ) -> ParserResult<()>
@@ -146,6 +264,14 @@ impl Parser {
         Callback: FnMut(&Event) -> ParserResult<()>,
     {
         match lexer.consume()? {
+            (Token::StartObject | Token::StartArray, span)
+                if pointer.len() > self.frontend.options.max_depth =>
+            {
+                parser_error!(
+                    ParserErrorDetails::MaximumDepthExceeded(self.frontend.options.max_depth),
+                    span.start
+                )
+            }
             (Token::StartObject, span) => {
                 emit_event!(cb, Match::StartObject, span, pointer)?;
                 self.parse_object(lexer, pointer, cb)
@@ -157,6 +283,9 @@ impl Parser {
             (Token::Str(str), span) => {
                 emit_event!(cb, Match::String(Cow::Borrowed(&str)), span, pointer)
             }
+            (Token::LazyString(str), span) => {
+                emit_event!(cb, Match::LazyString(str.as_borrowed()), span, pointer)
+            }
             (Token::LazyNumeric(value), span) => {
                 emit_event!(cb, Match::Numeric(value), span, pointer)
             }
@@ -172,6 +301,16 @@ impl Parser {
             (Token::Null, span) => {
                 emit_event!(cb, Match::Null, span, pointer)
             }
+            (Token::Identifier(name), span) => {
+                match non_finite(&name, self.frontend.options.dialect) {
+                    Some(value) => emit_event!(cb, Match::Float(value), span, pointer),
+                    None => parser_error!(
+                        ParserErrorDetails::UnexpectedToken(Token::Identifier(name).to_string()),
+                        span.start
+                    ),
+                }
+            }
+            (Token::Comma, span) => parser_error!(ParserErrorDetails::ValueExpected, span.start),
             (token, span) => {
                 parser_error!(
                     ParserErrorDetails::UnexpectedToken(token.to_string()),
@@ -181,50 +320,75 @@ impl Parser {
         }
     }
 
-    /// An object is just a list of comma separated KV pairs
-    fn parse_object<Callback>(
+    /// An object is just a list of comma separated KV pairs, which may be empty. Trailing commas
+    /// are only allowed in JSON5
+    fn parse_object<'a, L: Lex<Token = Token<'a>, Error = LexerError>, Callback>(
         &self,
-        lexer: &mut Lexer,
+        lexer: &mut L,
         pointer: &mut JsonPointer,
         cb: &mut Callback,
     ) -> ParserResult<()>
     where
         Callback: FnMut(&Event) -> ParserResult<()>,
     {
+        match lexer.consume()? {
+            (Token::EndObject, span) => return emit_event!(cb, Match::EndObject, span, pointer),
+            (token, span) => match object_key(token, self.frontend.options.dialect) {
+                Some(key) => self.parse_pair(key, span, lexer, pointer, cb)?,
+                None => return parser_error!(ParserErrorDetails::InvalidObject, span.start),
+            },
+        }
         loop {
             match lexer.consume()? {
-                (Token::Str(str), span) => {
-                    pointer.push_name(str.replace("\"", ""));
-                    emit_event!(cb, Match::ObjectKey(Cow::Borrowed(&str)), span, pointer)?;
-                    let should_be_colon = lexer.consume()?;
-                    match should_be_colon {
-                        (Token::Colon, _) => {
-                            self.parse_value(lexer, pointer, cb)?;
-                            pointer.pop();
-                        }
-                        (_, _) => {
-                            return parser_error!(
-                                ParserErrorDetails::PairExpected,
-                                should_be_colon.1.start
-                            )
-                        }
+                (Token::Comma, _) => match lexer.consume()? {
+                    (Token::EndObject, span) if self.frontend.options.dialect == Dialect::Json5 => {
+                        return emit_event!(cb, Match::EndObject, span, pointer);
                     }
-                }
-                (Token::Comma, _) => (),
+                    (token, span) => match object_key(token, self.frontend.options.dialect) {
+                        Some(key) => self.parse_pair(key, span, lexer, pointer, cb)?,
+                        None => return parser_error!(ParserErrorDetails::PairExpected, span.start),
+                    },
+                },
                 (Token::EndObject, span) => {
                     return emit_event!(cb, Match::EndObject, span, pointer);
                 }
                 (_token, span) => {
-                    return parser_error!(ParserErrorDetails::InvalidArray, span.start)
+                    return parser_error!(ParserErrorDetails::InvalidObject, span.start)
                 }
             }
         }
     }
 
-    /// An array is just a list of comma separated values
-    fn parse_array<Callback>(
+    /// Parse the remainder of a KV pair, the key of which has already been consumed
+    #[inline]
+    fn parse_pair<'a, L: Lex<Token = Token<'a>, Error = LexerError>, Callback>(
+        &self,
+        key: Cow<'a, str>,
+        span: Span,
+        lexer: &mut L,
+        pointer: &mut JsonPointer,
+        cb: &mut Callback,
+    ) -> ParserResult<()>
+    where
+        Callback: FnMut(&Event) -> ParserResult<()>,
+    {
+        pointer.push_name(key.to_string());
+        emit_event!(cb, Match::ObjectKey(Cow::Borrowed(&key)), span, pointer)?;
+        match lexer.consume()? {
+            (Token::Colon, _) => {
+                self.parse_value(lexer, pointer, cb)?;
+                pointer.pop();
+                Ok(())
+            }
+            (_token, span) => parser_error!(ParserErrorDetails::PairExpected, span.start),
+        }
+    }
+
+    /// An array is just a list of comma separated values, which may be empty. Trailing commas
+    /// are only allowed in JSON5
+    fn parse_array<'a, L: Lex<Token = Token<'a>, Error = LexerError>, Callback>(
         &self,
-        lexer: &mut Lexer,
+        lexer: &mut L,
         pointer: &mut JsonPointer,
         cb: &mut Callback,
     ) -> ParserResult<()>
@@ -232,61 +396,50 @@ impl Parser {
         Callback: FnMut(&Event) -> ParserResult<()>,
     {
         let mut index = 0;
-        let mut expect_value: bool = true;
-        let mut first_pass = true;
         loop {
+            if index == 0 || self.frontend.options.dialect == Dialect::Json5 {
+                if let (Token::EndArray, span) = lexer.peek()? {
+                    let span = *span;
+                    lexer.consume()?;
+                    return emit_event!(cb, Match::EndArray, span, pointer);
+                }
+            }
             pointer.push_index(index);
+            self.parse_value(lexer, pointer, cb)?;
+            pointer.pop();
             match lexer.consume()? {
-                (Token::StartArray, span) => {
-                    emit_event!(cb, Match::StartArray, span, pointer)?;
-                    self.parse_array(lexer, pointer, cb)?;
-                }
+                (Token::Comma, _) => index += 1,
                 (Token::EndArray, span) => {
-                    return if !expect_value || first_pass {
-                        pointer.pop();
-                        emit_event!(cb, Match::EndArray, span, pointer)
-                    } else {
-                        parser_error!(ParserErrorDetails::ValueExpected, span.start)
-                    }
-                }
-                (Token::StartObject, span) => {
-                    emit_event!(cb, Match::StartObject, span, pointer)?;
-                    self.parse_object(lexer, pointer, cb)?;
-                }
-                (Token::Str(str), span) => {
-                    emit_event!(cb, Match::String(Cow::Borrowed(&str)), span, pointer)?;
-                }
-                (Token::LazyNumeric(value), span) => {
-                    emit_event!(cb, Match::Numeric(value), span, pointer)?;
-                }
-                (Token::Float(value), span) => {
-                    emit_event!(cb, Match::Float(value), span, pointer)?;
-                }
-                (Token::Integer(value), span) => {
-                    emit_event!(cb, Match::Integer(value), span, pointer)?;
-                }
-                (Token::Boolean(value), span) => {
-                    emit_event!(cb, Match::Boolean(value), span, pointer)?;
-                }
-                (Token::Null, span) => emit_event!(cb, Match::Null, span, pointer)?,
-                (Token::Comma, span) => {
-                    if !expect_value {
-                        index += 1
-                    } else {
-                        return parser_error!(ParserErrorDetails::ValueExpected, span.start);
-                    }
+                    return emit_event!(cb, Match::EndArray, span, pointer);
                 }
                 (_token, span) => {
                     return parser_error!(ParserErrorDetails::InvalidArray, span.start);
                 }
             }
-            first_pass = false;
-            expect_value = !expect_value;
-            pointer.pop();
         }
     }
 }
 
+/// A [Parser] paired with the callback which receives its events
+struct Handler<'p, 'c, Callback> {
+    parser: &'p Parser,
+    cb: &'c mut Callback,
+}
+
+impl<'a, 'p, 'c, Callback> ParseRoot<'a> for Handler<'p, 'c, Callback>
+where
+    Callback: FnMut(&Event) -> ParserResult<()>,
+{
+    type Output = ();
+
+    fn parse_root<L: Lex<Token = Token<'a>, Error = LexerError>>(
+        &mut self,
+        lexer: &mut L,
+    ) -> ParserResult<()> {
+        self.parser.parse_root(lexer, self.cb)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::BufReader;
@@ -298,7 +451,11 @@ mod tests {
     use chisel_common::char::coords::Coords;
 
     use chisel_common::relative_file;
+    use chisel_decoders::common::{DecodePolicy, DecoderErrorCode};
+    use chisel_decoders::Encoding;
+    use chisel_lexers::json::dialect::Dialect;
 
+    use crate::json::events::Match;
     use crate::json::sax::Parser;
     use crate::json::specs;
     use crate::ParserErrorDetails;
@@ -315,10 +472,228 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_report_decoding_failures() {
+        let parser = Parser::default();
+        let parsed = parser.parse_bytes(b"[1, 2,\n \"\xff\"]", &mut |_e| Ok(()));
+        let err = parsed.err().unwrap();
+        assert_eq!(
+            err.details,
+            ParserErrorDetails::Decode(DecoderErrorCode::InvalidByteSequence)
+        );
+        assert_eq!(err.coords.unwrap().line, 2);
+        assert_eq!(err.coords.unwrap().column, 3);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn should_parse_mapped_files() {
+        let path = relative_file!("fixtures/json/valid/simple_structure.json");
+        let parser = Parser::default();
+        let mut buffered = vec![];
+        parser
+            .parse_file(&path, &mut |e| {
+                buffered.push(e.to_string());
+                Ok(())
+            })
+            .unwrap();
+        let mut mapped = vec![];
+        parser
+            .parse_mmap(&path, &mut |e| {
+                mapped.push(e.to_string());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(buffered, mapped);
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    #[test]
+    fn should_parse_compressed_files() {
+        let parser = Parser::default();
+        let collect = |path: &str| {
+            let mut events = vec![];
+            parser
+                .parse_file(path, &mut |e| {
+                    events.push(e.to_string());
+                    Ok(())
+                })
+                .map(|_| events)
+                .map_err(|err| err.to_string())
+        };
+        let expected = collect("fixtures/json/valid/simple_structure.json");
+        let uncompressed = collect("fixtures/json/invalid/invalid_array_1.json");
+        assert!(uncompressed.is_err());
+        let mut extensions = vec![];
+        #[cfg(feature = "gzip")]
+        extensions.push("gz");
+        #[cfg(feature = "zstd")]
+        extensions.push("zst");
+        for extension in extensions {
+            assert_eq!(
+                collect(&format!(
+                    "fixtures/json/compressed/simple_structure.json.{extension}"
+                )),
+                expected
+            );
+            assert_eq!(
+                collect(&format!(
+                    "fixtures/json/compressed/invalid_array_1.json.{extension}"
+                )),
+                uncompressed
+            );
+        }
+    }
+
+    #[test]
+    fn should_parse_single_byte_encodings() {
+        let mut buffer = BufReader::new(&b"[\"\x80 and \xa3\"]"[..]);
+        let mut strings = vec![];
+        let parser = Parser::with_encoding(Encoding::Windows1252);
+        let parsed = parser.parse_buffer(&mut buffer, &mut |e| {
+            if e.to_string().contains("String") {
+                strings.push(e.to_string());
+            }
+            Ok(())
+        });
+        assert!(parsed.is_ok());
+        assert!(strings[0].contains("\u{20ac} and \u{a3}"));
+    }
+
+    #[test]
+    fn should_build_pointers_from_decoded_keys() {
+        let mut pointers = vec![];
+        let parsed = Parser::default().parse_str(r#"{"a\"/b": {"\u00e9": 1}}"#, &mut |e| {
+            if let Match::Integer(_) | Match::Numeric(_) = e.matched {
+                pointers.push(e.pointer.unwrap().to_string());
+            }
+            Ok(())
+        });
+        assert!(parsed.is_ok());
+        assert_eq!(pointers, vec!["/a\"~1b/\u{e9}"]);
+    }
+
+    #[test]
+    fn should_apply_lossy_decode_policies() {
+        let parser = Parser::default().with_decode_policy(DecodePolicy::Replace);
+        let mut strings = vec![];
+        let parsed = parser.parse_bytes(b"[\"\xe2\x82\", \"\xed\xa0\x80\"]", &mut |e| {
+            if e.to_string().contains("String") {
+                strings.push(e.to_string());
+            }
+            Ok(())
+        });
+        assert!(parsed.is_ok());
+        assert_eq!(strings.len(), 2);
+        assert!(strings[0].contains("String(\u{fffd})"));
+        assert!(strings[1].contains("String(\u{fffd}\u{fffd}\u{fffd})"));
+        assert_eq!(parser.replacements(), 4);
+    }
+
+    #[test]
+    fn should_parse_utf16_input() {
+        let source = "[\"\u{1f600}\", {\"a\": null}]";
+        let bytes: Vec<u8> = source.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let mut events = vec![];
+        let parser = Parser::with_encoding(Encoding::Utf16Be);
+        let parsed = parser.parse_bytes(&bytes, &mut |e| {
+            events.push((e.matched.to_string(), e.span.start.absolute));
+            Ok(())
+        });
+        assert!(parsed.is_ok());
+        let mut expected = vec![];
+        Parser::default()
+            .parse_str(source, &mut |e| {
+                expected.push((e.matched.to_string(), e.span.start.absolute));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn should_parse_json5() {
+        let input = "{a: [1, 2,], 'b': {null: NaN,}, /* done */ }";
+        let mut events = vec![];
+        let parser = Parser::default().with_dialect(Dialect::Json5);
+        let parsed = parser.parse_str(input, &mut |e| {
+            match &e.matched {
+                Match::StartOfInput | Match::EndOfInput => (),
+                Match::StartObject | Match::StartArray | Match::ObjectKey(_) => (),
+                Match::EndObject | Match::EndArray => {
+                    events.push(format!("{} {}", e.pointer.unwrap(), e.matched))
+                }
+                Match::Float(value) => events.push(format!("{} {}", e.pointer.unwrap(), value)),
+                _ => events.push(e.pointer.unwrap().to_string()),
+            }
+            Ok(())
+        });
+        assert!(parsed.is_ok());
+        assert_eq!(
+            events,
+            [
+                "/a/0",
+                "/a/1",
+                "/a EndArray",
+                "/b/null NaN",
+                "/b EndObject",
+                "/ EndObject"
+            ]
+        );
+        assert!(Parser::default()
+            .parse_str(input, &mut |_e| Ok(()))
+            .is_err());
+    }
+
+    #[cfg(feature = "lazy-strings")]
+    #[test]
+    fn should_defer_decoding_of_strings() {
+        let mut matches = vec![];
+        Parser::default()
+            .parse_str(r#"{"key": ["plain", "\u00e9"]}"#, &mut |e| {
+                match &e.matched {
+                    Match::ObjectKey(key) => matches.push(key.to_string()),
+                    Match::LazyString(str) => {
+                        matches.push(format!("{}:{}", str.raw(), str.has_escapes()))
+                    }
+                    _ => (),
+                }
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(matches, ["key", "plain:false", "\\u00e9:true"]);
+    }
+
+    #[test]
+    fn should_report_byte_ranges_for_events() {
+        let source = "{\"caf\u{e9}\": [\"\u{1f600}\", 12, true]}";
+        let mut slices = vec![];
+        Parser::default()
+            .parse_bytes(source.as_bytes(), &mut |e| {
+                if let Match::String(_)
+                | Match::LazyString(_)
+                | Match::Integer(_)
+                | Match::Numeric(_)
+                | Match::Boolean(_) = e.matched
+                {
+                    slices.push(&source[e.span.byte_range()]);
+                }
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(slices, ["\"\u{1f600}\"", "12", "true"]);
+
+        let err = Parser::default()
+            .parse_bytes("[\"\u{e9}\", nul]".as_bytes(), &mut |_| Ok(()))
+            .err()
+            .unwrap();
+        assert_eq!(err.coords.unwrap().byte_offset, 10);
+    }
+
     #[test]
     fn should_parse_successfully() {
         let mut counter = 0;
-        let path = relative_file!("fixtures/json/valid/events.json");
+        let path = relative_file!("fixtures/json/valid/simple_structure.json");
         let parser = Parser::default();
         let parsed = parser.parse_file(&path, &mut |_e| {
             counter += 1;
@@ -328,6 +703,48 @@ mod tests {
         assert!(parsed.is_ok());
     }
 
+    #[test]
+    fn should_limit_the_depth_of_nesting() {
+        let parser = Parser::default().with_max_depth(2);
+        assert!(parser
+            .parse_str("{\"a\": [1], \"b\": {}}", &mut |_e| Ok(()))
+            .is_ok());
+        let err = parser
+            .parse_str("{\"a\": [{}]}", &mut |_e| Ok(()))
+            .err()
+            .unwrap();
+        assert_eq!(err.details, ParserErrorDetails::MaximumDepthExceeded(2));
+        assert_eq!(err.coords.unwrap().column, 8);
+    }
+
+    #[test]
+    fn should_conform_to_the_test_suite() {
+        let mut failures = vec![];
+        for f in fs::read_dir(specs::SUITE_DIRECTORY).unwrap() {
+            let path = f.unwrap().path();
+            let filename = path.file_name().unwrap().to_str().unwrap().to_string();
+            let mut events = vec![];
+            let parsed = Parser::default().parse_file(&path, &mut |e| {
+                events.push(e.matched.to_string());
+                Ok(())
+            });
+            match specs::suite_expectation(&filename) {
+                Some(spec) if spec.accepted == parsed.is_ok() => (),
+                Some(spec) => failures.push(format!(
+                    "{filename} ({}): {:?}",
+                    spec.reason,
+                    parsed.as_ref().err()
+                )),
+                None => failures.push(format!("{filename}: missing from the table")),
+            }
+            if parsed.is_ok() && events.last().map(String::as_str) != Some("EndOfInput") {
+                failures.push(format!("{filename}: no end of input event"));
+            }
+        }
+        failures.sort();
+        assert!(failures.is_empty(), "{:#?}", failures);
+    }
+
     #[test]
     fn should_successfully_handle_basic_invalid_inputs() {
         for spec in specs::invalid_json_specs() {
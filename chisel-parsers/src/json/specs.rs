@@ -29,12 +29,15 @@ impl TestSpecification {
 /// that the errors occur (and where they should be identified as occurring)
 pub(crate) fn invalid_json_specs() -> Vec<TestSpecification> {
     vec![
+        // RFC 8259 allows any value at the root, so the leading "this" is accepted as a complete
+        // document and the error is only picked up at the unexpected colon which follows it
         TestSpecification::new(
             "fixtures/json/invalid/invalid_root_object.json",
             Coords {
                 line: 1,
-                column: 1,
+                column: 8,
                 absolute: 1,
+                ..Default::default()
             },
         ),
         TestSpecification::new(
@@ -43,6 +46,7 @@ pub(crate) fn invalid_json_specs() -> Vec<TestSpecification> {
                 line: 2,
                 column: 23,
                 absolute: 1,
+                ..Default::default()
             },
         ),
         TestSpecification::new(
@@ -51,6 +55,7 @@ pub(crate) fn invalid_json_specs() -> Vec<TestSpecification> {
                 line: 3,
                 column: 22,
                 absolute: 1,
+                ..Default::default()
             },
         ),
         TestSpecification::new(
@@ -59,6 +64,7 @@ pub(crate) fn invalid_json_specs() -> Vec<TestSpecification> {
                 line: 3,
                 column: 35,
                 absolute: 1,
+                ..Default::default()
             },
         ),
         TestSpecification::new(
@@ -67,6 +73,7 @@ pub(crate) fn invalid_json_specs() -> Vec<TestSpecification> {
                 line: 2,
                 column: 22,
                 absolute: 1,
+                ..Default::default()
             },
         ),
         TestSpecification::new(
@@ -75,6 +82,7 @@ pub(crate) fn invalid_json_specs() -> Vec<TestSpecification> {
                 line: 2,
                 column: 33,
                 absolute: 1,
+                ..Default::default()
             },
         ),
         TestSpecification::new(
@@ -83,6 +91,7 @@ pub(crate) fn invalid_json_specs() -> Vec<TestSpecification> {
                 line: 530,
                 column: 5,
                 absolute: 1,
+                ..Default::default()
             },
         ),
         TestSpecification::new(
@@ -91,7 +100,227 @@ pub(crate) fn invalid_json_specs() -> Vec<TestSpecification> {
                 line: 719,
                 column: 24,
                 absolute: 1,
+                ..Default::default()
             },
         ),
     ]
 }
+
+/// The behaviour of the parsers for one of the implementation defined (`i_`) cases within the
+/// JSONTestSuite corpus
+#[derive(Debug, Clone)]
+pub struct ImplementationDefined {
+    /// The name of the file within `fixtures/json/suite`
+    pub filename: &'static str,
+    /// Whether the input is accepted
+    pub accepted: bool,
+    /// Why the input is treated the way it is
+    pub reason: &'static str,
+}
+
+/// The directory holding cases taken from the JSONTestSuite (<https://github.com/nst/JSONTestSuite>,
+/// MIT licensed), which was put together by Nicolas Seriot.  Files prefixed with `y_`
+/// must be accepted and files prefixed with `n_` must be rejected.  Files prefixed with `i_` are
+/// left up to the implementation by RFC 8259, and so the behaviour for each of these is listed in
+/// [implementation_defined_specs]
+pub(crate) const SUITE_DIRECTORY: &str = "fixtures/json/suite";
+
+/// Work out how a file from the JSONTestSuite should be treated, based on its name. Returns [None]
+/// for an implementation defined case which is missing from [implementation_defined_specs]
+pub(crate) fn suite_expectation(filename: &str) -> Option<ImplementationDefined> {
+    let (accepted, reason) = match filename.split('_').next() {
+        Some("y") => (true, "valid JSON"),
+        Some("n") => (false, "invalid JSON"),
+        _ => {
+            return implementation_defined_specs()
+                .into_iter()
+                .find(|spec| spec.filename == filename)
+        }
+    };
+    Some(ImplementationDefined {
+        filename: "",
+        accepted,
+        reason,
+    })
+}
+
+/// Table of the implementation defined cases from the JSONTestSuite
+pub(crate) fn implementation_defined_specs() -> Vec<ImplementationDefined> {
+    vec![
+        ImplementationDefined {
+            filename: "i_number_double_huge_neg_exp.json",
+            accepted: true,
+            reason: "underflows to zero",
+        },
+        ImplementationDefined {
+            filename: "i_number_huge_exp.json",
+            accepted: true,
+            reason: "overflows to infinity",
+        },
+        ImplementationDefined {
+            filename: "i_number_neg_int_huge_exp.json",
+            accepted: true,
+            reason: "overflows to negative infinity",
+        },
+        ImplementationDefined {
+            filename: "i_number_pos_double_huge_exp.json",
+            accepted: true,
+            reason: "overflows to infinity",
+        },
+        ImplementationDefined {
+            filename: "i_number_real_neg_overflow.json",
+            accepted: true,
+            reason: "overflows to negative infinity",
+        },
+        ImplementationDefined {
+            filename: "i_number_real_pos_overflow.json",
+            accepted: true,
+            reason: "overflows to infinity",
+        },
+        ImplementationDefined {
+            filename: "i_number_real_underflow.json",
+            accepted: true,
+            reason: "underflows to zero",
+        },
+        ImplementationDefined {
+            filename: "i_number_too_big_neg_int.json",
+            accepted: true,
+            reason: "integers outside the range of an i64 are kept exactly as lazy numerics",
+        },
+        ImplementationDefined {
+            filename: "i_number_too_big_pos_int.json",
+            accepted: true,
+            reason: "integers outside the range of an i64 are kept exactly as lazy numerics",
+        },
+        ImplementationDefined {
+            filename: "i_number_very_big_negative_int.json",
+            accepted: true,
+            reason: "integers outside the range of an i64 are kept exactly as lazy numerics",
+        },
+        ImplementationDefined {
+            filename: "i_object_key_lone_2nd_surrogate.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_1st_surrogate_but_2nd_missing.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_1st_valid_surrogate_2nd_invalid.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_UTF-16LE_with_BOM.json",
+            accepted: false,
+            reason: "input is decoded as UTF-8 unless another encoding is selected",
+        },
+        ImplementationDefined {
+            filename: "i_string_UTF-8_invalid_sequence.json",
+            accepted: false,
+            reason: "malformed UTF-8 is rejected by the default decode policy",
+        },
+        ImplementationDefined {
+            filename: "i_string_UTF8_surrogate_U+D800.json",
+            accepted: false,
+            reason: "malformed UTF-8 is rejected by the default decode policy",
+        },
+        ImplementationDefined {
+            filename: "i_string_incomplete_surrogate_and_escape_valid.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_incomplete_surrogate_pair.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_incomplete_surrogates_escape_valid.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_invalid_lonely_surrogate.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_invalid_surrogate.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_invalid_utf-8.json",
+            accepted: false,
+            reason: "malformed UTF-8 is rejected by the default decode policy",
+        },
+        ImplementationDefined {
+            filename: "i_string_inverted_surrogates_U+1D11E.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_iso_latin_1.json",
+            accepted: false,
+            reason: "input is decoded as UTF-8 unless another encoding is selected",
+        },
+        ImplementationDefined {
+            filename: "i_string_lone_second_surrogate.json",
+            accepted: false,
+            reason: "unpaired surrogate escapes can't be decoded",
+        },
+        ImplementationDefined {
+            filename: "i_string_lone_utf8_continuation_byte.json",
+            accepted: false,
+            reason: "malformed UTF-8 is rejected by the default decode policy",
+        },
+        ImplementationDefined {
+            filename: "i_string_not_in_unicode_range.json",
+            accepted: false,
+            reason: "malformed UTF-8 is rejected by the default decode policy",
+        },
+        ImplementationDefined {
+            filename: "i_string_overlong_sequence_2_bytes.json",
+            accepted: false,
+            reason: "malformed UTF-8 is rejected by the default decode policy",
+        },
+        ImplementationDefined {
+            filename: "i_string_overlong_sequence_6_bytes.json",
+            accepted: false,
+            reason: "malformed UTF-8 is rejected by the default decode policy",
+        },
+        ImplementationDefined {
+            filename: "i_string_overlong_sequence_6_bytes_null.json",
+            accepted: false,
+            reason: "malformed UTF-8 is rejected by the default decode policy",
+        },
+        ImplementationDefined {
+            filename: "i_string_truncated-utf-8.json",
+            accepted: false,
+            reason: "malformed UTF-8 is rejected by the default decode policy",
+        },
+        ImplementationDefined {
+            filename: "i_string_utf16BE_no_BOM.json",
+            accepted: false,
+            reason: "input is decoded as UTF-8 unless another encoding is selected",
+        },
+        ImplementationDefined {
+            filename: "i_string_utf16LE_no_BOM.json",
+            accepted: false,
+            reason: "input is decoded as UTF-8 unless another encoding is selected",
+        },
+        ImplementationDefined {
+            filename: "i_structure_500_nested_arrays.json",
+            accepted: false,
+            reason: "nesting is limited to 128 levels by default",
+        },
+        ImplementationDefined {
+            filename: "i_structure_UTF-8_BOM_empty_object.json",
+            accepted: true,
+            reason: "the UTF-8 decoder skips a leading byte order mark",
+        },
+    ]
+}
@@ -1,5 +1,6 @@
 use chisel_common::char::coords::Coords;
-use chisel_lexers::json::lexer::LexerError;
+use chisel_decoders::common::DecoderErrorCode;
+use chisel_lexers::json::lexer::{LexerError, LexerErrorDetails};
 use std::fmt::{Display, Formatter};
 
 /// JSON parser implementations
@@ -25,11 +26,11 @@ pub enum ParserErrorDetails {
     /// Edge case error condition. This means that something has gone horribly wrong with the
     /// parse.
     UnexpectedToken(String),
-    /// An array value is expected but not detected
+    /// A value is expected, but a comma was found in its place
     ValueExpected,
     /// KV pair is expected but not detected.
     PairExpected,
-    /// Supplied JSON doesn't have an object or array as a root object.
+    /// Supplied JSON doesn't start with a valid value.
     InvalidRootObject,
     /// The parse of an object has failed.
     InvalidObject,
@@ -47,6 +48,10 @@ pub enum ParserErrorDetails {
     InvalidUnicodeEscapeSequence(String),
     /// A bubbled error from the lexical analysis backend
     LexerError(String),
+    /// The input couldn't be decoded using the selected encoding
+    Decode(DecoderErrorCode),
+    /// Objects and arrays are nested more deeply than the configured limit
+    MaximumDepthExceeded(usize),
 }
 
 impl Display for ParserErrorDetails {
@@ -87,6 +92,10 @@ impl Display for ParserErrorDetails {
             ParserErrorDetails::LexerError(repr) => {
                 write!(f, "lexer error reported: \"{}\"", repr)
             }
+            ParserErrorDetails::Decode(code) => write!(f, "failed to decode input: {}", code),
+            ParserErrorDetails::MaximumDepthExceeded(depth) => {
+                write!(f, "maximum nesting depth of {} exceeded", depth)
+            }
         }
     }
 }
@@ -102,15 +111,9 @@ pub struct ParserError {
 
 impl Display for ParserError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.coords.is_some() {
-            write!(
-                f,
-                "details: {}, coords: {}",
-                self.details,
-                self.coords.unwrap()
-            )
-        } else {
-            write!(f, "details: {}", self.details)
+        match self.coords {
+            Some(coords) => write!(f, "details: {}, coords: {}", self.details, coords),
+            None => write!(f, "details: {}", self.details),
         }
     }
 }
@@ -119,7 +122,10 @@ impl Display for ParserError {
 impl From<LexerError> for ParserError {
     fn from(value: LexerError) -> Self {
         ParserError {
-            details: ParserErrorDetails::LexerError(value.details.to_string()),
+            details: match value.details {
+                LexerErrorDetails::Decode(code) => ParserErrorDetails::Decode(code),
+                details => ParserErrorDetails::LexerError(details.to_string()),
+            },
             coords: value.coords,
         }
     }